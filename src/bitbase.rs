@@ -0,0 +1,214 @@
+use once_cell::sync::Lazy;
+
+/// Added to the eval of a position the bitbase knows as won.
+pub const KNOWN_WIN_BONUS: i16 = 10000;
+
+/// King + pawn positions with the pawn on files a-d and ranks 2-7, for both sides to move.
+const KPK_INDEX_SIZE: usize = 2 * 24 * 64 * 64;
+
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Won KPK positions (white has the pawn), one bit per index. Built by retrograde analysis on first use.
+static KPK_BITBASE: Lazy<Vec<u64>> = Lazy::new(generate_kpk);
+
+/// Index of a KPK position, the pawn must be on files a-d.
+fn kpk_index(white_to_move: bool, black_king: usize, white_king: usize, pawn: usize) -> usize {
+    white_king | (black_king << 6) | ((!white_to_move as usize) << 12) | ((pawn & 7) << 13) | ((6 - (pawn >> 3)) << 15)
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    let file_distance = ((a & 7) as i32 - (b & 7) as i32).unsigned_abs();
+    let rank_distance = ((a >> 3) as i32 - (b >> 3) as i32).unsigned_abs();
+    file_distance.max(rank_distance) as usize
+}
+
+fn king_attacks(sq: usize) -> u64 {
+    (0..64).filter(|&to| distance(sq, to) == 1).fold(0, |bb, to| bb | 1u64 << to)
+}
+
+fn white_pawn_attacks(sq: usize) -> u64 {
+    let mut bb = 0;
+    if sq & 7 > 0 {
+        bb |= 1u64 << (sq + 7);
+    }
+    if sq & 7 < 7 {
+        bb |= 1u64 << (sq + 9);
+    }
+    bb
+}
+
+struct KpkPosition {
+    white_to_move: bool,
+    white_king: usize,
+    black_king: usize,
+    pawn: usize,
+    result: u8,
+}
+
+impl KpkPosition {
+    fn new(idx: usize, king_attacks_table: &[u64; 64]) -> Self {
+        let white_king = idx & 0x3F;
+        let black_king = (idx >> 6) & 0x3F;
+        let white_to_move = (idx >> 12) & 1 == 0;
+        let pawn = (6 - ((idx >> 15) & 7)) * 8 + ((idx >> 13) & 3);
+        let mut position = KpkPosition { white_to_move, white_king, black_king, pawn, result: UNKNOWN };
+
+        let promotion_sq = pawn + 8;
+        if distance(white_king, black_king) <= 1
+            || white_king == pawn
+            || black_king == pawn
+            || (white_to_move && white_pawn_attacks(pawn) & (1u64 << black_king) != 0)
+        {
+            position.result = INVALID;
+        } else if white_to_move
+            && pawn >> 3 == 6
+            && white_king != promotion_sq
+            && (distance(black_king, promotion_sq) > 1 || distance(white_king, promotion_sq) == 1)
+        {
+            // pawn promotes without getting captured
+            position.result = WIN;
+        } else if !white_to_move
+            && (king_attacks_table[black_king] & !(king_attacks_table[white_king] | white_pawn_attacks(pawn)) == 0
+                || king_attacks_table[black_king] & !king_attacks_table[white_king] & (1u64 << pawn) != 0)
+        {
+            // stalemate or the pawn gets captured
+            position.result = DRAW;
+        }
+        position
+    }
+
+    /// White needs one move to a won position, black one move to a drawn position.
+    fn classify(&self, db: &[KpkPosition], king_attacks_table: &[u64; 64]) -> u8 {
+        let (good, bad) = if self.white_to_move { (WIN, DRAW) } else { (DRAW, WIN) };
+        let mut r = INVALID;
+
+        let mut b = king_attacks_table[if self.white_to_move { self.white_king } else { self.black_king }];
+        while b != 0 {
+            let to = b.trailing_zeros() as usize;
+            r |= if self.white_to_move {
+                db[kpk_index(false, self.black_king, to, self.pawn)].result
+            } else {
+                db[kpk_index(true, to, self.white_king, self.pawn)].result
+            };
+            b &= b - 1;
+        }
+
+        if self.white_to_move {
+            if self.pawn >> 3 < 6 {
+                r |= db[kpk_index(false, self.black_king, self.white_king, self.pawn + 8)].result;
+            }
+            if self.pawn >> 3 == 1 && self.pawn + 8 != self.white_king && self.pawn + 8 != self.black_king {
+                r |= db[kpk_index(false, self.black_king, self.white_king, self.pawn + 16)].result;
+            }
+        }
+
+        if r & good != 0 {
+            good
+        } else if r & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            bad
+        }
+    }
+}
+
+fn generate_kpk() -> Vec<u64> {
+    let mut king_attacks_table = [0u64; 64];
+    for (sq, attacks) in king_attacks_table.iter_mut().enumerate() {
+        *attacks = king_attacks(sq);
+    }
+
+    let mut db: Vec<KpkPosition> = (0..KPK_INDEX_SIZE).map(|idx| KpkPosition::new(idx, &king_attacks_table)).collect();
+
+    // iterate until no position changes, the remaining unknown positions are draws
+    let mut repeat = true;
+    while repeat {
+        repeat = false;
+        for idx in 0..KPK_INDEX_SIZE {
+            if db[idx].result == UNKNOWN {
+                let result = db[idx].classify(&db, &king_attacks_table);
+                if result != UNKNOWN {
+                    db[idx].result = result;
+                    repeat = true;
+                }
+            }
+        }
+    }
+
+    let mut bitbase = vec![0u64; KPK_INDEX_SIZE / 64];
+    for (idx, position) in db.iter().enumerate() {
+        if position.result == WIN {
+            bitbase[idx / 64] |= 1u64 << (idx % 64);
+        }
+    }
+    bitbase
+}
+
+/// True if the side with the pawn wins. Squares are given from the view of the pawn owner, who moves up the board.
+pub fn probe_kpk(strong_king: usize, pawn: usize, weak_king: usize, strong_to_move: bool) -> bool {
+    // mirror to the a-d files
+    let (strong_king, pawn, weak_king) = if pawn & 7 > 3 {
+        (strong_king ^ 7, pawn ^ 7, weak_king ^ 7)
+    } else {
+        (strong_king, pawn, weak_king)
+    };
+    let idx = kpk_index(strong_to_move, weak_king, strong_king, pawn);
+    KPK_BITBASE[idx / 64] & (1u64 << (idx % 64)) != 0
+}
+
+/// Builds the bitbases up front, so the first probe inside a timed search doesn't pay for it.
+pub fn init() {
+    Lazy::force(&KPK_BITBASE);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(name: &str) -> usize {
+        let bytes = name.as_bytes();
+        (bytes[1] - b'1') as usize * 8 + (bytes[0] - b'a') as usize
+    }
+
+    #[test]
+    fn kpk_known_positions_test() {
+        // king in front of the pawn on the sixth rank wins
+        assert!(probe_kpk(sq("e6"), sq("e5"), sq("e8"), true));
+        assert!(probe_kpk(sq("e6"), sq("e5"), sq("e8"), false));
+
+        // rook pawn with the defending king in the corner is a draw
+        assert!(!probe_kpk(sq("g6"), sq("h5"), sq("h8"), true));
+        assert!(!probe_kpk(sq("g6"), sq("h5"), sq("h8"), false));
+
+        // king behind the pawn, defender has the opposition
+        assert!(!probe_kpk(sq("e5"), sq("e6"), sq("e8"), true));
+        assert!(!probe_kpk(sq("e5"), sq("e6"), sq("e8"), false));
+
+        // defending king outside the square of the pawn
+        assert!(probe_kpk(sq("h1"), sq("a4"), sq("h5"), true));
+        assert!(probe_kpk(sq("h1"), sq("a4"), sq("h5"), false));
+
+        // defending king reaches the square only when on move
+        assert!(probe_kpk(sq("h1"), sq("a4"), sq("f5"), true));
+        assert!(!probe_kpk(sq("h1"), sq("a4"), sq("f5"), false));
+    }
+
+    #[test]
+    fn kpk_file_mirror_test() {
+        for white_king in 0..64 {
+            for black_king in [sq("a8"), sq("d8"), sq("e5"), sq("h1")] {
+                if distance(white_king, black_king) <= 1 || white_king == sq("c4") || black_king == sq("c4") {
+                    continue;
+                }
+                assert_eq!(
+                    probe_kpk(white_king, sq("c4"), black_king, false),
+                    probe_kpk(white_king ^ 7, sq("f4"), black_king ^ 7, false)
+                );
+            }
+        }
+    }
+}
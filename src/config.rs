@@ -7,6 +7,30 @@ pub enum Aggressiveness {
     HighAggressive,
 }
 
+/// Handling of PV and null window nodes that have no transposition table move. Under PVS every
+/// non-PV node searches a null window, so the non-PV nodes are not told apart further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IirMode {
+    Off,
+    /// Internal iterative reduction: search the node one ply shallower.
+    Reduction,
+    /// Internal iterative deepening: run a reduced search first to find a move for ordering.
+    Deepening,
+}
+
+/// How a move is picked among the book moves of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSelection {
+    /// Always the move with the highest weight.
+    Best,
+    /// Random, proportional to the weight.
+    Weighted,
+    /// Random, every book move alike.
+    Uniform,
+    /// Random, proportional to weight^(100 / book_temperature).
+    Temperature,
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub version: &'static str,
@@ -16,6 +40,21 @@ pub struct Config {
     pub use_book: bool,
     pub cache_book_in_ram: bool,
     pub book_file: String,
+    pub book_selection: BookSelection,
+    /// Temperature in percent for `BookSelection::Temperature`, below 100 sharpens towards the best move.
+    pub book_temperature: i32,
+    /// Plies up to which the books are used, 0 for no limit.
+    pub book_depth: i32,
+    /// Book moves with a lower weight are ignored.
+    pub book_min_weight: i32,
+    /// Seed for the book move choice, 0 picks moves non-deterministically.
+    pub book_seed: u64,
+    /// Adjusts weight and learn field of the played `book_file` moves after each game.
+    pub book_learning: bool,
+    /// Depth of the search verifying book moves before they are played, 0 plays them unchecked.
+    pub book_check_depth: i32,
+    /// Book moves scoring more than this many cp below the best move are rejected.
+    pub book_check_margin: i32,
     pub max_zobrist_hash_entries: usize,
     /// Default capacity: 1,000,000 entries (~16 MB).
     /// Proven sweet spot in tournament play (+30 Elo over 10M entries).
@@ -37,12 +76,57 @@ pub struct Config {
     #[allow(dead_code)]
     pub game_loop: u64,
     pub smp_thread_eval_noise: i16,
+    /// UCI_LimitStrength: weaken the engine to the strength given by uci_elo.
+    pub limit_strength: bool,
+    /// Approximate target rating, mapped linearly onto the skill levels (uncalibrated).
+    pub uci_elo: i32,
+    /// Skill Level 0..=20, 20 is full strength.
+    pub skill_level: i32,
+    /// Seed for weakened move selection and eval noise, 0 picks moves non-deterministically.
+    pub skill_seed: u64,
+    /// Number of root moves searched with an exact score (no alpha raise at the root).
+    pub multi_pv: usize,
+    /// Amplitude of the deterministic per-position eval noise in cp.
+    pub eval_noise: i16,
+    /// UCI_Chess960: castling moves are sent and expected as king captures own rook.
+    pub chess960: bool,
+    /// SyzygyPath: directories of the Syzygy tablebase files, separated like PATH.
+    pub syzygy_path: String,
+    /// ParamsFile: parameter file applied on top of the defaults, see `params::load_params_file`.
+    pub params_file: String,
     pub skip_strong_validation: bool,
     pub max_eval_mult: f32,
     pub aggressiveness: Aggressiveness,
     pub enable_positional_cap: bool,
     pub positional_cap_damping: i16,
     pub move_overhead: u64,
+    /// Draw score in cp from the engine's (root side's) view is -contempt, so positive values avoid draws.
+    pub contempt: i16,
+    /// Keep contempt in analysis searches (go infinite, UCI_AnalyseMode); otherwise draws score 0 there.
+    pub analysis_contempt: bool,
+    pub uci_analyse_mode: bool,
+    /// Scale the soft time limit from the iteration history (best-move changes, score drops, stability).
+    pub enable_dynamic_time: bool,
+    /// Hard limit in percent of the soft limit.
+    pub tm_hard_limit_factor: u64,
+    /// The hard limit never exceeds this percentage of the remaining clock.
+    pub tm_max_remaining_percent: u64,
+    /// Number of recent iterations inspected for best-move changes.
+    pub tm_instability_window: usize,
+    /// Soft limit extension in percent per best-move change inside the window.
+    pub tm_best_move_change_extension: u64,
+    /// Score drop in cp (root side view) versus the previous iteration that triggers an extension.
+    pub tm_score_drop_threshold: i16,
+    /// Soft limit extension in percent on a score drop.
+    pub tm_score_drop_extension: u64,
+    /// Iterations with an unchanged best move after which the soft limit shrinks.
+    pub tm_stable_iterations: usize,
+    /// Soft limit in percent for a stable best move.
+    pub tm_stable_scale: u64,
+    /// Share of root nodes in percent spent on the best move to count as dominating.
+    pub tm_node_dominance_percent: u64,
+    /// Soft limit in percent for a best move that dominates the node count.
+    pub tm_dominant_scale: u64,
 
     pub is_hashed_rank_bonus: i32,
     pub give_check_rank_bonus: i32,
@@ -116,6 +200,10 @@ pub struct Config {
     pub bishop_outpost_true_eg: i16,
     pub opposite_bishops_draw_scale: i16,
     pub enable_endgame_mopup: bool,
+    /// Exact KPK results from the bitbase, drawn positions evaluate to 0.
+    pub enable_kpk_bitbase: bool,
+    /// Specialized evaluators and scale factors for known endgame material signatures.
+    pub enable_endgame_evaluators: bool,
     pub mopup_center_weight: i16,
     pub mopup_proximity_weight: i16,
     pub mopup_eval_threshold: i16,
@@ -160,6 +248,8 @@ pub struct Config {
     pub enable_lmr: bool,
     pub enable_nmp: bool,
     pub enable_aspiration: bool,
+    /// Order root moves by the node counts of the previous iteration.
+    pub enable_root_node_ordering: bool,
     pub enable_rfp: bool,
     pub enable_futility_pruning: bool,
     pub enable_qs_tt: bool,
@@ -183,7 +273,27 @@ pub struct Config {
     pub nmp_depth_threshold: i32,
     pub nmp_reduction: i32,
     pub nmp_verification_threshold: i32,
+    /// Depth divisor of the additional NMP reduction, 0 disables the depth dependent part.
     pub nmp_dynamic_divisor: i32,
+    /// Half width of the first aspiration window around the previous score in cp (5..200).
+    pub aspiration_delta: i16,
+    /// Factor the aspiration window widens by after a fail low or high (2..10).
+    pub aspiration_growth: i16,
+    /// Percentage of searched root moves from which on the time target is extended (50..100).
+    pub root_time_extension_threshold: i32,
+    /// Extended time target in percent of the search target (100..200).
+    pub root_time_extension: i32,
+    /// LMR reduces quiet moves with a history score above this one ply less (0..9000).
+    pub lmr_history_good_threshold: u32,
+    /// LMR reduces quiet moves with a history score below this one ply more (0..9000).
+    pub lmr_history_bad_threshold: u32,
+    /// Rank penalty for captures losing material by SEE, sorts them behind the quiet moves (1000..1000000).
+    pub see_rank_penalty: i32,
+    pub iir_mode: IirMode,
+    /// Minimum remaining depth for IIR/IID to kick in.
+    pub iir_depth_threshold: i32,
+    /// Depth reduction of the IID pre-search.
+    pub iid_reduction: i32,
     pub log_path: std::sync::Arc<str>,
 }
 
@@ -198,6 +308,14 @@ impl Config {
             use_book: true,
             cache_book_in_ram: true,
             book_file: String::new(),
+            book_selection: BookSelection::Weighted,
+            book_temperature: 100,
+            book_depth: 0,
+            book_min_weight: 0,
+            book_seed: 0,
+            book_learning: false,
+            book_check_depth: 0,
+            book_check_margin: 100,
             max_zobrist_hash_entries: 50_000_000, // 800 MB
             max_pawn_hash_entries: 1_000_000, // 16 MB: Proven +30 Elo sweet spot (avoids CPU L3 & TLB thrashing)
             search_depth: 4, // only used as default for tests
@@ -210,17 +328,40 @@ impl Config {
             quiescence_search_mode: QuiescenceSearchMode::Alpha2,
             print_info_string_during_search: false,
 
-            search_threads: 2,
+            search_threads: 1,
             use_pv_nodes: true,
             min_thinking_time: 2,
             game_loop: 3,
             smp_thread_eval_noise: 0,
+            limit_strength: false,
+            uci_elo: crate::skill::MAX_ELO,
+            skill_level: 20,
+            skill_seed: 0,
+            multi_pv: 1,
+            eval_noise: 0,
+            chess960: false,
+            syzygy_path: String::new(),
+            params_file: String::new(),
             skip_strong_validation: false,
             max_eval_mult: 2.0,
             aggressiveness: Aggressiveness::Normal,
             enable_positional_cap: true,
             positional_cap_damping: 5,
             move_overhead: 0,
+            contempt: 0,
+            analysis_contempt: false,
+            uci_analyse_mode: false,
+            enable_dynamic_time: true,
+            tm_hard_limit_factor: 300,
+            tm_max_remaining_percent: 25,
+            tm_instability_window: 3,
+            tm_best_move_change_extension: 35,
+            tm_score_drop_threshold: 30,
+            tm_score_drop_extension: 40,
+            tm_stable_iterations: 4,
+            tm_stable_scale: 70,
+            tm_node_dominance_percent: 90,
+            tm_dominant_scale: 50,
 
             is_hashed_rank_bonus: 4,
             give_check_rank_bonus: 5,
@@ -294,6 +435,8 @@ impl Config {
             bishop_outpost_true_eg: 11,
             opposite_bishops_draw_scale: 51,
             enable_endgame_mopup: true,
+            enable_kpk_bitbase: true,
+            enable_endgame_evaluators: true,
             mopup_center_weight: 10,
             mopup_proximity_weight: 15,
             mopup_eval_threshold: 400,
@@ -340,6 +483,7 @@ impl Config {
             enable_lmr: true,
             enable_nmp: true,
             enable_aspiration: true,
+            enable_root_node_ordering: true,
             enable_rfp: true,
             enable_futility_pruning: true,
             enable_qs_tt: true,
@@ -374,6 +518,16 @@ impl Config {
             nmp_reduction: 2,
             nmp_verification_threshold: 6,
             nmp_dynamic_divisor: 6,
+            aspiration_delta: 15,
+            aspiration_growth: 4,
+            root_time_extension_threshold: 85,
+            root_time_extension: 130,
+            lmr_history_good_threshold: 4000,
+            lmr_history_bad_threshold: 500,
+            see_rank_penalty: 100000,
+            iir_mode: IirMode::Off,
+            iir_depth_threshold: 4,
+            iid_reduction: 2,
             log_path: std::sync::Arc::from(""),
         }
     }
@@ -394,6 +548,15 @@ impl Config {
         Self::new_raw()
     }
 
+    /// Contempt used by the search, analysis searches stay neutral unless analysis_contempt is set.
+    pub fn effective_contempt(&self, analysing: bool) -> i16 {
+        if (analysing || self.uci_analyse_mode) && !self.analysis_contempt {
+            0
+        } else {
+            self.contempt
+        }
+    }
+
     pub fn set_aggressiveness(&mut self, aggressiveness: Aggressiveness) {
         let raw = Self::new_raw();
         self.aggressiveness = aggressiveness;
@@ -493,6 +656,7 @@ impl Config {
         let mut config = Config::new();
         config.use_nnue = false;
         config.enable_endgame_mopup = false;
+        config.enable_endgame_evaluators = false;
         config.aggressiveness = Aggressiveness::Normal;
         config.enable_positional_cap = false;
         config.print_info_string_during_search = false;
@@ -577,4 +741,19 @@ mod tests {
         assert_eq!(config.max_pawn_hash_entries, 1_000_000);
         assert_eq!(config.max_zobrist_hash_entries, 50_000_000);
     }
+
+    #[test]
+    fn test_effective_contempt() {
+        let mut config = Config::new();
+        config.contempt = 30;
+        assert_eq!(30, config.effective_contempt(false));
+        assert_eq!(0, config.effective_contempt(true));
+
+        config.analysis_contempt = true;
+        assert_eq!(30, config.effective_contempt(true));
+
+        config.analysis_contempt = false;
+        config.uci_analyse_mode = true;
+        assert_eq!(0, config.effective_contempt(false));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,332 @@
+//! Training data generation for the NNUE networks.
+//!
+//! Self-play games start from the initial position after a number of random plies and are
+//! played at a fixed node limit with the selfplay adjudication. Every position before an
+//! engine move is recorded unless the side to move is in check, the best move is a capture or
+//! the score is a mate score. Each record holds the search score and the final game result.
+//!
+//! The text format has one `<fen> | <score> | <result>` line per position, the score in
+//! centipawns and the result 1.0, 0.5 or 0.0, both from the view of white.
+//!
+//! The binary format has 32 bytes per position, the board seen from the side to move (ranks
+//! flipped and colors swapped when black is to move), all integers little endian:
+//!
+//! | bytes | field |
+//! | :--- | :--- |
+//! | 0..8 | occupancy bitboard, a1 = bit 0 |
+//! | 8..24 | one nibble per occupied square in ascending square order, low nibble first: piece 0..5 for pawn, knight, bishop, rook, queen, king, plus 8 for the opponent |
+//! | 24..26 | score in centipawns, i16 |
+//! | 26 | result: 2 win, 1 draw, 0 loss |
+//! | 27 | king square |
+//! | 28 | opponent king square, mirrored to the opponent's view |
+//! | 29..32 | zero |
+//!
+//! This is the layout of the `ChessBoard` records read by the bullet trainer.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::fen_service::FenService;
+use crate::model::{Board, EngineState, SearchLimit, Turn};
+use crate::pgn::START_FEN;
+use crate::selfplay::{play_game, Adjudication, Opening, Outcome, Player};
+use crate::service::Service;
+
+pub const PACKED_POSITION_SIZE: usize = 32;
+
+/// Scores beyond this are mate scores and not recorded.
+const MAX_RECORDED_SCORE: i16 = 32000;
+
+/// Packs the position in the binary format, score and outcome from the view of white.
+pub fn pack_position(board: &Board, white_score: i16, outcome: Outcome) -> [u8; PACKED_POSITION_SIZE] {
+    let white = board.white_to_move;
+    let view = |square: usize| if white { square } else { square ^ 56 };
+    let mut pieces: Vec<(usize, u8)> = (0..64).filter(|square| board.mailbox[*square] != 0).map(|square| {
+        let piece = board.mailbox[square];
+        let piece_type = match piece % 10 {
+            0 => 0,
+            2 => 1,
+            3 => 2,
+            1 => 3,
+            4 => 4,
+            _ => 5,
+        };
+        let own = (piece < 20) == white;
+        (view(square), if own { piece_type } else { 8 | piece_type })
+    }).collect();
+    pieces.sort_unstable();
+
+    let mut packed = [0u8; PACKED_POSITION_SIZE];
+    let occupancy = pieces.iter().fold(0u64, |occupancy, (square, _)| occupancy | 1 << square);
+    packed[0..8].copy_from_slice(&occupancy.to_le_bytes());
+    for (i, (_, piece)) in pieces.iter().enumerate() {
+        packed[8 + i / 2] |= piece << (4 * (i % 2));
+    }
+    let score = if white { white_score } else { -white_score };
+    packed[24..26].copy_from_slice(&score.to_le_bytes());
+    let white_result = match outcome {
+        Outcome::WhiteWin => 2,
+        Outcome::Draw => 1,
+        Outcome::BlackWin => 0,
+    };
+    packed[26] = if white { white_result } else { 2 - white_result };
+    let king = |own: bool| pieces.iter().find(|(_, piece)| *piece == if own { 5 } else { 13 }).map_or(0, |(square, _)| *square as u8);
+    packed[27] = king(true);
+    packed[28] = king(false) ^ 56;
+    packed
+}
+
+/// Line of the text format.
+pub fn text_line(fen: &str, white_score: i16, outcome: Outcome) -> String {
+    let result = match outcome {
+        Outcome::WhiteWin => "1.0",
+        Outcome::Draw => "0.5",
+        Outcome::BlackWin => "0.0",
+    };
+    format!("{} | {} | {}", fen, white_score, result)
+}
+
+fn is_capture(board: &Board, turn: &Turn) -> bool {
+    let target = board.mailbox[turn.to as usize];
+    let own = |piece: u8| (piece < 20) == board.white_to_move;
+    let en_passant = matches!(board.mailbox[turn.from as usize], 10 | 20) && board.field_for_en_passante == turn.to as i8;
+    (target != 0 && !own(target)) || en_passant
+}
+
+/// Position of a finished game with its packed record and text line.
+pub struct DataPoint {
+    pub packed: [u8; PACKED_POSITION_SIZE],
+    pub line: String,
+}
+
+/// Replays the game and returns the positions worth training on.
+pub fn game_data_points(opening: &Opening, turns: &[Turn], scores: &[i16], outcome: Outcome, service: &Service) -> Vec<DataPoint> {
+    let mut board = FenService.set_fen(&opening.fen);
+    let mut data_points = Vec::new();
+    for (ply, turn) in turns.iter().enumerate() {
+        if let Some(score) = ply.checked_sub(opening.turns.len()).map(|engine_ply| scores[engine_ply])
+            && score.abs() < MAX_RECORDED_SCORE
+            && !service.move_gen.is_in_check(&board)
+            && !is_capture(&board, turn) {
+            let fen = FenService.get_fen(&board);
+            data_points.push(DataPoint { packed: pack_position(&board, score, outcome), line: text_line(&fen, score, outcome) });
+        }
+        board.do_move(turn);
+    }
+    data_points
+}
+
+/// Plays `plies` random moves from the initial position. Openings that end the game or that
+/// a search at the limit scores beyond `max_score` are drawn again.
+pub fn random_opening(rng: &mut StdRng, plies: usize, max_score: i16, limit: SearchLimit, player: &Player) -> Opening {
+    loop {
+        let mut board = FenService.set_fen(START_FEN);
+        let mut turns = Vec::new();
+        for _ in 0..plies {
+            let moves = player.service.move_gen.legal_moves(&mut board, player.config);
+            let Some(turn) = moves.choose(rng) else {
+                break;
+            };
+            board.do_move(turn);
+            turns.push(*turn);
+        }
+        if turns.len() < plies || player.service.move_gen.legal_moves(&mut board, player.config).is_empty() {
+            continue;
+        }
+        player.engine_state.clear();
+        let (result, _) = player.service.search.search_with_limit(&mut board, limit, player.config, player.service, player.engine_state, |_| {});
+        if result.is_some_and(|result| result.get_eval().abs() <= max_score) {
+            return Opening { fen: START_FEN.to_string(), turns };
+        }
+    }
+}
+
+pub struct DatagenOptions {
+    pub games: usize,
+    pub concurrency: usize,
+    pub nodes: u64,
+    pub random_plies: usize,
+    pub max_opening_score: i16,
+    pub hash_mb: usize,
+    pub seed: u64,
+    pub params_file: Option<String>,
+    pub bin_out: Option<String>,
+    pub text_out: Option<String>,
+    pub adjudication: Adjudication,
+}
+
+impl DatagenOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let usage = "usage: suprah datagen [--games <n>] [--concurrency <n>] [--nodes <n>] [--random-plies <n>] [--max-opening-score <cp>]\n\
+                     \x20      [--hash <mb>] [--seed <n>] [--params <file>] [--bin <file>] [--text <file>]";
+        let mut options = DatagenOptions {
+            games: 100,
+            concurrency: 1,
+            nodes: 5000,
+            random_plies: 8,
+            max_opening_score: 400,
+            hash_mb: 16,
+            seed: 0,
+            params_file: None,
+            bin_out: None,
+            text_out: None,
+            adjudication: Adjudication { draw_after: 30, ..Default::default() },
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().cloned().ok_or(format!("missing value for {}\n{}", arg, usage));
+            let invalid = |_| format!("invalid value for {}", arg);
+            match arg.trim_start_matches("--") {
+                "games" => options.games = value()?.parse().map_err(invalid)?,
+                "concurrency" => options.concurrency = value()?.parse::<usize>().map_err(invalid)?.max(1),
+                "nodes" => options.nodes = value()?.parse::<u64>().map_err(invalid)?.max(1),
+                "random-plies" => options.random_plies = value()?.parse().map_err(invalid)?,
+                "max-opening-score" => options.max_opening_score = value()?.parse().map_err(invalid)?,
+                "hash" => options.hash_mb = value()?.parse::<usize>().map_err(invalid)?.max(1),
+                "seed" => options.seed = value()?.parse().map_err(invalid)?,
+                "params" => options.params_file = Some(value()?),
+                "bin" => options.bin_out = Some(value()?),
+                "text" => options.text_out = Some(value()?),
+                _ => return Err(format!("unknown argument {}\n{}", arg, usage)),
+            }
+        }
+        if options.bin_out.is_none() && options.text_out.is_none() {
+            options.bin_out = Some("datagen.bin".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Entry point of `suprah datagen`, see `DatagenOptions::parse` for the arguments.
+pub fn run(args: &[String]) {
+    let fail = |msg: String| -> ! {
+        eprintln!("{}", msg);
+        std::process::exit(2);
+    };
+    let options = DatagenOptions::parse(args).unwrap_or_else(|e| fail(e));
+    let engine = crate::selfplay::EngineOptions { params_file: options.params_file.clone(), options: Vec::new() };
+    let config = engine.config().unwrap_or_else(|e| fail(e));
+    let create = |path: &Option<String>| path.as_ref().map(|path| {
+        std::io::BufWriter::new(std::fs::File::create(path).unwrap_or_else(|e| fail(format!("could not create {}: {}", path, e))))
+    });
+    let (mut bin_file, mut text_file) = (create(&options.bin_out), create(&options.text_out));
+
+    let zobrist_entries = options.hash_mb * 1024 * 1024 / 16;
+    let next_game = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel::<Vec<DataPoint>>();
+    let start = Instant::now();
+    let (mut games, mut positions) = (0, 0);
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.min(options.games.max(1)) {
+            let tx = tx.clone();
+            let (config, options) = (&config, &options);
+            let (next_game, stop) = (&next_game, &stop);
+            scope.spawn(move || {
+                let service = Service::new();
+                let engine_state: Arc<EngineState> = EngineState::offline(zobrist_entries);
+                let player = Player { config, service: &service, engine_state: &engine_state };
+                let limit = SearchLimit::Nodes(options.nodes);
+                while !stop.load(Ordering::SeqCst) {
+                    let index = next_game.fetch_add(1, Ordering::SeqCst);
+                    if index >= options.games {
+                        break;
+                    }
+                    let mut rng = if options.seed == 0 { StdRng::from_entropy() } else { StdRng::seed_from_u64(options.seed ^ index as u64) };
+                    let opening = random_opening(&mut rng, options.random_plies, options.max_opening_score, limit, &player);
+                    let record = play_game(&opening, &player, &player, limit, &options.adjudication);
+                    let data_points = game_data_points(&opening, &record.turns, &record.scores, record.outcome, &service);
+                    if tx.send(data_points).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for data_points in rx.iter() {
+            let written = data_points.iter().try_for_each(|data_point| {
+                if let Some(file) = bin_file.as_mut() {
+                    file.write_all(&data_point.packed)?;
+                }
+                if let Some(file) = text_file.as_mut() {
+                    writeln!(file, "{}", data_point.line)?;
+                }
+                Ok::<(), std::io::Error>(())
+            });
+            if let Err(e) = written {
+                eprintln!("could not write training data: {}", e);
+                stop.store(true, Ordering::SeqCst);
+                break;
+            }
+            games += 1;
+            positions += data_points.len();
+            let elapsed = start.elapsed().as_secs_f64().max(0.001);
+            println!("games {}/{}, positions {}, {:.0} positions/s", games, options.games, positions, positions as f64 / elapsed);
+        }
+    });
+
+    for file in [bin_file.as_mut(), text_file.as_mut()].into_iter().flatten() {
+        file.flush().unwrap_or_else(|e| fail(format!("could not write training data: {}", e)));
+    }
+    println!("{} positions from {} games in {:.1}s", positions, games, start.elapsed().as_secs_f64());
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn pack_position_test() {
+        let board = FenService.set_fen(START_FEN);
+        let packed = pack_position(&board, 25, Outcome::WhiteWin);
+        assert_eq!(0xFFFF00000000FFFFu64.to_le_bytes(), packed[0..8]);
+        // a1 rook, b1 knight
+        assert_eq!(0x13, packed[8]);
+        // a8 rook, b8 knight of the opponent
+        assert_eq!(0x9B, packed[20]);
+        assert_eq!(25i16.to_le_bytes(), packed[24..26]);
+        assert_eq!((2, 4, 4), (packed[26], packed[27], packed[28]));
+
+        // black to move sees its own pieces on the first ranks
+        let board = FenService.set_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1");
+        let packed = pack_position(&board, 50, Outcome::WhiteWin);
+        assert_eq!((1u64 << 4 | 1 << 52 | 1 << 60).to_le_bytes(), packed[0..8]);
+        assert_eq!([0x85, 0x0D], packed[8..10]);
+        assert_eq!((-50i16).to_le_bytes(), packed[24..26]);
+        assert_eq!((0, 4, 4), (packed[26], packed[27], packed[28]));
+
+        assert_eq!("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1 | 50 | 0.5", text_line("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1", 50, Outcome::Draw));
+    }
+
+    #[test]
+    fn game_data_points_test() {
+        let config = Config::for_tests();
+        let service = Service::new();
+        let engine_state = EngineState::offline(100_000);
+        let player = Player { config: &config, service: &service, engine_state: &engine_state };
+        let mut rng = StdRng::seed_from_u64(7);
+        let opening = random_opening(&mut rng, 4, 400, SearchLimit::Depth(1), &player);
+        assert_eq!(4, opening.turns.len());
+
+        let adjudication = Adjudication { max_plies: 10, ..Default::default() };
+        let record = play_game(&opening, &player, &player, SearchLimit::Nodes(500), &adjudication);
+        let data_points = game_data_points(&opening, &record.turns, &record.scores, record.outcome, &service);
+        assert!(!data_points.is_empty() && data_points.len() <= record.scores.len());
+        for data_point in &data_points {
+            let (fen, rest) = data_point.line.split_once(" | ").unwrap();
+            let board = FenService.set_fen(fen);
+            assert!(!service.move_gen.is_in_check(&board));
+            assert!(rest.ends_with("| 0.5"));
+            assert_eq!(1, data_point.packed[26]);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use crate::bitbase::KNOWN_WIN_BONUS;
+use crate::model::{material_key_unit, Board};
+use crate::model::{BLACK_BISHOP, BLACK_KING, BLACK_PAWN, WHITE_BISHOP, WHITE_KING, WHITE_PAWN};
+use crate::pst::{PIECE_EVAL_BISHOP, PIECE_EVAL_KNIGHT, PIECE_EVAL_PAWN, PIECE_EVAL_QUEEN, PIECE_EVAL_ROOK};
+
+/// Exact score from the view of the strong side, None if the evaluator has no knowledge of the position.
+pub type ExactFn = fn(&Board, bool) -> Option<i16>;
+/// Scale factor in percent for the eval of the strong side, 100 leaves the eval unchanged.
+pub type ScaleFn = fn(&Board, bool) -> i16;
+
+/// Specialized evaluation of a material signature.
+#[derive(Clone, Copy)]
+pub struct Endgame {
+    pub strong_white: bool,
+    pub exact: Option<ExactFn>,
+    pub scale: Option<ScaleFn>,
+}
+
+/// Most pieces besides kings and pawns in a registered endgame, boards with more skip the lookup.
+const MAX_PIECES: u32 = 2;
+
+/// Registry of the endgames with a fixed material signature, keyed by `Board::material_key`.
+pub struct Endgames {
+    map: HashMap<u64, Endgame>,
+}
+
+pub static ENDGAMES: Lazy<Endgames> = Lazy::new(Endgames::new);
+
+/// Bitboard indices in material code order "PRNBQK".
+const CODE_PIECES: &str = "PRNBQK";
+
+/// Material key of a code like "KBNvK", the first side is white.
+fn material_key_of(code: &str) -> u64 {
+    let (white, black) = code.split_once('v').expect("RIP invalid endgame code");
+    let mut key = 0;
+    for (side, pieces) in [white, black].iter().enumerate() {
+        for c in pieces.chars() {
+            let bb_idx = CODE_PIECES.find(c).expect("RIP invalid endgame piece");
+            key += material_key_unit(side * 6 + bb_idx);
+        }
+    }
+    key
+}
+
+/// Count of the pieces of a bitboard index in a material key.
+fn count(key: u64, bb_idx: usize) -> u64 {
+    (key >> (4 * bb_idx)) & 0xF
+}
+
+impl Endgames {
+    fn new() -> Self {
+        let mut endgames = Endgames { map: HashMap::new() };
+        endgames.add("KBNvK", Some(kbnk), None);
+        endgames.add("KQvKR", Some(kqkr), None);
+        endgames.add("KRvKB", None, Some(krkb));
+        endgames.add("KRvKN", None, Some(krkn));
+        endgames
+    }
+
+    fn add(&mut self, code: &str, exact: Option<ExactFn>, scale: Option<ScaleFn>) {
+        let (white, black) = code.split_once('v').expect("RIP invalid endgame code");
+        assert!(code.chars().filter(|c| "RNBQ".contains(*c)).count() as u32 <= MAX_PIECES, "RIP endgame {} has more than {} pieces", code, MAX_PIECES);
+        self.map.insert(material_key_of(code), Endgame { strong_white: true, exact, scale });
+        self.map.insert(material_key_of(&format!("{}v{}", black, white)), Endgame { strong_white: false, exact, scale });
+    }
+
+    /// Endgame of the board, the fixed signatures first, then the pawn endings with a bare king.
+    pub fn probe(&self, board: &Board) -> Option<Endgame> {
+        let pawns_and_kings = board.bitboards[WHITE_PAWN] | board.bitboards[BLACK_PAWN] | board.bitboards[WHITE_KING] | board.bitboards[BLACK_KING];
+        if (board.occupied & !pawns_and_kings).count_ones() > MAX_PIECES {
+            return None;
+        }
+        if let Some(endgame) = self.map.get(&board.material_key) {
+            return Some(*endgame);
+        }
+
+        let key = board.material_key;
+        for strong_white in [true, false] {
+            let (strong, weak) = if strong_white { (0, 6) } else { (6, 0) };
+            let weak_pieces: u64 = (0..5).map(|i| count(key, weak + i)).sum();
+            if weak_pieces != 0 {
+                continue;
+            }
+            let pawns = count(key, strong + WHITE_PAWN);
+            let bishops = count(key, strong + WHITE_BISHOP);
+            let others: u64 = (1..5).map(|i| count(key, strong + i)).sum::<u64>() - bishops;
+
+            if pawns >= 2 && bishops == 0 && others == 0 {
+                return Some(Endgame { strong_white, exact: Some(kpsk), scale: Some(rook_pawns_fortress) });
+            }
+            if pawns >= 1 && bishops == 1 && others == 0 {
+                return Some(Endgame { strong_white, exact: None, scale: Some(rook_pawns_fortress) });
+            }
+        }
+        None
+    }
+}
+
+fn distance(a: usize, b: usize) -> i16 {
+    let file_distance = ((a & 7) as i16 - (b & 7) as i16).abs();
+    let rank_distance = ((a >> 3) as i16 - (b >> 3) as i16).abs();
+    file_distance.max(rank_distance)
+}
+
+/// Bonus for kings close to each other.
+fn push_close(a: usize, b: usize) -> i16 {
+    140 - 20 * distance(a, b)
+}
+
+/// Bonus for a king near the edge, 90 in the corners and 0 in the center.
+fn push_to_edge(sq: usize) -> i16 {
+    let file = (sq & 7).min(7 - (sq & 7)) as i16;
+    let rank = (sq >> 3).min(7 - (sq >> 3)) as i16;
+    (6 - file - rank) * 15
+}
+
+/// Bonus for a king near the a1 or h8 corner, 7 in the corners.
+fn push_to_corner(sq: usize) -> i16 {
+    (7 - (sq >> 3) as i16 - (sq & 7) as i16).abs()
+}
+
+fn is_light_square(sq: usize) -> bool {
+    ((sq >> 3) + (sq & 7)) % 2 == 1
+}
+
+fn kings(board: &Board, strong_white: bool) -> (usize, usize) {
+    let white_king = board.bitboards[WHITE_KING].trailing_zeros() as usize;
+    let black_king = board.bitboards[BLACK_KING].trailing_zeros() as usize;
+    if strong_white { (white_king, black_king) } else { (black_king, white_king) }
+}
+
+/// Mate with bishop and knight, drives the weak king into a corner of the bishop color.
+fn kbnk(board: &Board, strong_white: bool) -> Option<i16> {
+    let (strong_king, weak_king) = kings(board, strong_white);
+    let bishop = board.bitboards[if strong_white { WHITE_BISHOP } else { BLACK_BISHOP }].trailing_zeros() as usize;
+    // light corners a8/h1 are mapped onto a1/h8
+    let weak_king = if is_light_square(bishop) { weak_king ^ 7 } else { weak_king };
+    Some(KNOWN_WIN_BONUS + PIECE_EVAL_BISHOP + PIECE_EVAL_KNIGHT + push_close(strong_king, weak_king) + 40 * push_to_corner(weak_king))
+}
+
+/// Queen against rook is a win, the rook side is driven to the edge.
+fn kqkr(board: &Board, strong_white: bool) -> Option<i16> {
+    let (strong_king, weak_king) = kings(board, strong_white);
+    Some(PIECE_EVAL_QUEEN - PIECE_EVAL_ROOK + push_to_edge(weak_king) + push_close(strong_king, weak_king))
+}
+
+/// Rook against bishop is usually a draw.
+fn krkb(_board: &Board, _strong_white: bool) -> i16 {
+    20
+}
+
+/// Rook against knight is usually a draw, but the knight gets lost more often than the bishop.
+fn krkn(board: &Board, strong_white: bool) -> i16 {
+    let (_, weak_king) = kings(board, strong_white);
+    if push_to_edge(weak_king) >= 75 { 50 } else { 30 }
+}
+
+/// Pawns against the bare king, a pawn out of reach of the king promotes.
+fn kpsk(board: &Board, strong_white: bool) -> Option<i16> {
+    let (strong_king, weak_king) = kings(board, strong_white);
+    let strong_to_move = board.white_to_move == strong_white;
+    let mut pawns = board.bitboards[if strong_white { WHITE_PAWN } else { BLACK_PAWN }];
+    let mut best_rank = None;
+
+    while pawns != 0 {
+        let sq = pawns.trailing_zeros() as usize;
+        pawns &= pawns - 1;
+        // from the view of the strong side
+        let (sq, strong_king, weak_king) = if strong_white { (sq, strong_king, weak_king) } else { (sq ^ 56, strong_king ^ 56, weak_king ^ 56) };
+        let rank = (sq >> 3) as i16;
+        let promotion_sq = 56 + (sq & 7);
+
+        // own king in the way of the pawn
+        if strong_king & 7 == sq & 7 && strong_king > sq {
+            continue;
+        }
+        let pawn_moves = if rank == 1 { 5 } else { 7 - rank };
+        let king_moves = distance(weak_king, promotion_sq) - if strong_to_move { 0 } else { 1 };
+        if king_moves > pawn_moves {
+            best_rank = best_rank.max(Some(rank));
+        }
+    }
+
+    let pawn_count = board.bitboards[if strong_white { WHITE_PAWN } else { BLACK_PAWN }].count_ones() as i16;
+    best_rank.map(|rank| KNOWN_WIN_BONUS + pawn_count * PIECE_EVAL_PAWN + 20 * rank)
+}
+
+/// Pawns on a single rook file can't be promoted against a king in the corner, unless the
+/// bishop controls the promotion square.
+fn rook_pawns_fortress(board: &Board, strong_white: bool) -> i16 {
+    let (_, weak_king) = kings(board, strong_white);
+    let pawns = board.bitboards[if strong_white { WHITE_PAWN } else { BLACK_PAWN }];
+    const FILE_A: u64 = 0x0101_0101_0101_0101;
+    const FILE_H: u64 = FILE_A << 7;
+
+    let file = if pawns & !FILE_A == 0 {
+        0
+    } else if pawns & !FILE_H == 0 {
+        7
+    } else {
+        return 100;
+    };
+    let promotion_sq = if strong_white { 56 + file } else { file };
+
+    let bishops = board.bitboards[if strong_white { WHITE_BISHOP } else { BLACK_BISHOP }];
+    if bishops != 0 && is_light_square(bishops.trailing_zeros() as usize) == is_light_square(promotion_sq) {
+        return 100;
+    }
+    if distance(weak_king, promotion_sq) <= 1 { 0 } else { 100 }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    fn probe(fen: &str) -> Option<Endgame> {
+        ENDGAMES.probe(&Service::new().fen.set_fen(fen))
+    }
+
+    #[test]
+    fn registry_lookup_test() {
+        let kbnk_white = probe("8/8/8/4k3/8/8/8/2B1KN2 w - - 0 1").unwrap();
+        assert!(kbnk_white.strong_white);
+        assert!(kbnk_white.exact.is_some());
+        let kbnk_black = probe("2b1kn2/8/8/8/4K3/8/8/8 w - - 0 1").unwrap();
+        assert!(!kbnk_black.strong_white);
+
+        assert!(probe("8/8/8/4k3/3b4/8/8/3RK3 w - - 0 1").unwrap().scale.is_some());
+        assert!(probe("8/8/8/4k3/8/8/PP6/4K3 w - - 0 1").unwrap().exact.is_some());
+        assert!(probe("8/8/8/4k3/8/8/P7/2B1K3 w - - 0 1").is_some());
+        assert!(probe("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_none());
+        assert!(probe("8/8/8/4k3/8/8/PP5p/4K3 w - - 0 1").is_none());
+        // more pieces than any registered endgame skip the lookup
+        assert!(probe("8/8/8/4k3/8/8/P7/1NB1K3 w - - 0 1").is_none());
+    }
+
+    #[test]
+    fn kbnk_corner_drive_test() {
+        let service = Service::new();
+        // dark squared bishop on c1 mates in a1/h8
+        let right_corner = service.fen.set_fen("7k/8/5K2/8/8/8/8/2B2N2 w - - 0 1");
+        let wrong_corner = service.fen.set_fen("k7/8/2K5/8/8/8/8/2B2N2 w - - 0 1");
+        let center = service.fen.set_fen("8/8/8/4k3/8/2K5/8/2B2N2 w - - 0 1");
+        let score = |board: &Board| kbnk(board, true).unwrap();
+        assert!(score(&right_corner) > score(&center));
+        assert!(score(&center) > score(&wrong_corner));
+        assert!(score(&wrong_corner) > KNOWN_WIN_BONUS);
+    }
+
+    #[test]
+    fn rook_pawn_wrong_bishop_test() {
+        let service = Service::new();
+        // h8 is dark, a light squared bishop can't drive the king away
+        let wrong_bishop = service.fen.set_fen("7k/8/8/7P/8/8/8/4KB2 w - - 0 1");
+        assert_eq!(0, rook_pawns_fortress(&wrong_bishop, true));
+        let right_bishop = service.fen.set_fen("7k/8/8/7P/8/8/8/2B1K3 w - - 0 1");
+        assert_eq!(100, rook_pawns_fortress(&right_bishop, true));
+        let king_too_far = service.fen.set_fen("8/8/8/1k5P/8/8/8/4KB2 w - - 0 1");
+        assert_eq!(100, rook_pawns_fortress(&king_too_far, true));
+        // black pawns on the a-file queen on a1, a dark square the bishop on f8 covers
+        let black_right_bishop = service.fen.set_fen("4kb2/8/8/8/p7/8/p7/1K6 w - - 0 1");
+        assert_eq!(100, rook_pawns_fortress(&black_right_bishop, false));
+        // the light squared bishop on g8 can't
+        let black_fortress = service.fen.set_fen("4k1b1/8/8/8/p7/8/p7/1K6 w - - 0 1");
+        assert_eq!(0, rook_pawns_fortress(&black_fortress, false));
+    }
+
+    #[test]
+    fn kpsk_unstoppable_pawn_test() {
+        let service = Service::new();
+        // a-pawn is out of the square of the king on h6
+        let board = service.fen.set_fen("8/8/7k/8/P7/8/5P2/4K3 w - - 0 1");
+        assert!(kpsk(&board, true).unwrap() > KNOWN_WIN_BONUS);
+        // king reaches the square of both pawns
+        let board = service.fen.set_fen("8/8/2k5/8/P7/8/5P2/4K3 w - - 0 1");
+        assert_eq!(None, kpsk(&board, true));
+        // black pawn on h5, the white king on a1 is too far away
+        let board = service.fen.set_fen("8/8/4k3/7p/8/8/1p6/K7 b - - 0 1");
+        assert!(kpsk(&board, false).is_some());
+    }
+}
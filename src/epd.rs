@@ -0,0 +1,304 @@
+//! EPD test suite runner.
+//!
+//! Every position of the suite is searched with iterative deepening up to the limit. A position
+//! counts as solved if the final best move is one of its `bm` moves and none of its `am` moves,
+//! the time to solution is the end of the iteration from which on the best move stayed correct.
+//! The LCT II scoring gives 30 to 5 points by that time and rates the engine 1900 + points.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::config::Config;
+use crate::fen_service::FenService;
+use crate::model::{EngineState, SearchLimit, Turn};
+use crate::pgn::{san_to_turn, turn_to_san};
+use crate::service::Service;
+
+/// One position of an EPD file with its operations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EpdPosition {
+    pub fen: String,
+    pub id: String,
+    /// Best moves in SAN.
+    pub bm: Vec<String>,
+    /// Moves to avoid in SAN.
+    pub am: Vec<String>,
+    pub comment: String,
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl EpdPosition {
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter().find(|(op, _)| op == opcode).map(|(_, operands)| operands.as_slice())
+    }
+}
+
+/// Operands of an operation, quoted strings stay one operand.
+fn split_operands(operands: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut chars = operands.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => result.push(chars.by_ref().take_while(|c| *c != '"').collect()),
+            c if c.is_whitespace() => {},
+            c => {
+                let mut operand = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    operand.push(c);
+                }
+                result.push(operand);
+            },
+        }
+    }
+    result
+}
+
+/// Parses an EPD line: four FEN fields followed by `opcode operands;` operations. The move
+/// counters come from `hmvc` and `fmvn`, 0 and 1 otherwise.
+pub fn parse_epd_line(line: &str) -> Option<EpdPosition> {
+    let line = line.trim();
+    let mut fields = line.splitn(5, char::is_whitespace);
+    let fen_fields: Vec<&str> = fields.by_ref().take(4).collect();
+    if fen_fields.len() < 4 || line.starts_with('#') {
+        return None;
+    }
+
+    let mut operations = Vec::new();
+    let mut rest = fields.next().unwrap_or("");
+    while !rest.trim().is_empty() {
+        // semicolons inside quoted operands do not end the operation
+        let mut in_quotes = false;
+        let end = rest.find(|c| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            c == ';' && !in_quotes
+        }).unwrap_or(rest.len());
+        let operation = rest[..end].trim();
+        rest = rest.get(end + 1..).unwrap_or("");
+        if let Some((opcode, operands)) = operation.split_once(char::is_whitespace) {
+            operations.push((opcode.to_string(), split_operands(operands)));
+        } else if !operation.is_empty() {
+            operations.push((operation.to_string(), Vec::new()));
+        }
+    }
+
+    let mut position = EpdPosition { operations, ..Default::default() };
+    let first = |opcode: &str| position.operation(opcode).and_then(|operands| operands.first()).cloned();
+    let halfmove_clock = first("hmvc").unwrap_or("0".to_string());
+    let fullmove_number = first("fmvn").unwrap_or("1".to_string());
+    let id = first("id").unwrap_or_default();
+    let comment = first("c0").unwrap_or_default();
+    position.fen = format!("{} {} {}", fen_fields.join(" "), halfmove_clock, fullmove_number);
+    position.id = id;
+    position.comment = comment;
+    position.bm = position.operation("bm").map(<[String]>::to_vec).unwrap_or_default();
+    position.am = position.operation("am").map(<[String]>::to_vec).unwrap_or_default();
+    Some(position)
+}
+
+pub fn parse_epd(content: &str) -> Vec<EpdPosition> {
+    content.lines().filter_map(parse_epd_line).collect()
+}
+
+pub struct EpdOptions {
+    pub file: String,
+    pub limit: SearchLimit,
+    pub lct: bool,
+}
+
+impl EpdOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let usage = "usage: suprah epd <file> [movetime <ms>|depth <n>|nodes <n>] [--lct]";
+        let mut iter = args.iter();
+        let file = iter.next().filter(|file| !file.starts_with("--")).ok_or(usage)?.clone();
+        let mut options = EpdOptions { file, limit: SearchLimit::Movetime(10000), lct: false };
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("missing value for {}", arg));
+            let invalid = |_| format!("invalid value for {}", arg);
+            match arg.trim_start_matches("--") {
+                "movetime" => options.limit = SearchLimit::Movetime(value()?.parse().map_err(invalid)?),
+                "depth" => options.limit = SearchLimit::Depth(value()?.parse().map_err(invalid)?),
+                "nodes" => options.limit = SearchLimit::Nodes(value()?.parse().map_err(invalid)?),
+                "lct" => options.lct = true,
+                _ => return Err(format!("unknown argument {}\n{}", arg, usage)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EpdResult {
+    /// Engine move in SAN, empty if the search found none.
+    pub best_move: String,
+    pub solved: bool,
+    pub solve_time_ms: Option<u64>,
+    pub depth: i32,
+    pub nodes: u64,
+}
+
+/// Turns of the SAN moves, None if one of them is not legal in the position.
+fn san_moves(fen: &str, sans: &[String], service: &Service, config: &Config) -> Option<Vec<Turn>> {
+    let mut board = FenService.set_fen(fen);
+    sans.iter().map(|san| san_to_turn(&mut board, san, &service.move_gen, config)).collect()
+}
+
+/// Searches the position and scores the best move against its `bm` and `am` moves.
+pub fn solve(position: &EpdPosition, limit: SearchLimit, config: &Config, service: &Service, engine_state: &Arc<EngineState>) -> Result<EpdResult, String> {
+    let best_moves = san_moves(&position.fen, &position.bm, service, config).ok_or(format!("illegal bm {}", position.bm.join(" ")))?;
+    let avoid_moves = san_moves(&position.fen, &position.am, service, config).ok_or(format!("illegal am {}", position.am.join(" ")))?;
+    let is_solution = |turn: &Turn| (best_moves.is_empty() || best_moves.contains(turn)) && !avoid_moves.contains(turn);
+
+    engine_state.clear();
+    service.pawn_table.clear();
+    let mut board = FenService.set_fen(&position.fen);
+    let start = Instant::now();
+    let mut result = EpdResult { best_move: String::new(), solved: false, solve_time_ms: None, depth: 0, nodes: 0 };
+    let (best_result, nodes) = service.search.search_with_limit(&mut board, limit, config, service, engine_state, |search_result| {
+        let solved = search_result.variants.first().and_then(|variant| variant.best_move).is_some_and(|turn| is_solution(&turn));
+        if !solved {
+            result.solve_time_ms = None;
+        } else if result.solve_time_ms.is_none() {
+            result.solve_time_ms = Some(start.elapsed().as_millis() as u64);
+        }
+    });
+    result.nodes = nodes;
+    let best_turn = best_result.as_ref().and_then(|search_result| {
+        result.depth = search_result.calculated_depth;
+        search_result.variants.first().and_then(|variant| variant.best_move)
+    });
+
+    if let Some(turn) = best_turn {
+        result.best_move = turn_to_san(&mut board, &turn, &service.move_gen, config);
+        result.solved = is_solution(&turn);
+    }
+    Ok(result)
+}
+
+/// LCT II points for a solution found after the time.
+pub fn lct_points(solve_time_ms: u64) -> u32 {
+    match solve_time_ms {
+        0..=9000 => 30,
+        9001..=29000 => 25,
+        29001..=89000 => 20,
+        89001..=209000 => 15,
+        209001..=389000 => 10,
+        389001..=600000 => 5,
+        _ => 0,
+    }
+}
+
+/// Entry point of `suprah epd <file> [movetime <ms>|depth <n>|nodes <n>] [--lct]`.
+pub fn run(args: &[String]) {
+    let options = match EpdOptions::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let content = std::fs::read_to_string(&options.file).unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", options.file, e);
+        std::process::exit(2);
+    });
+    let positions = parse_epd(&content);
+
+    let service = Service::new();
+    let mut config = Config::new();
+    config.print_info_string_during_search = false;
+    let engine_state = EngineState::offline(128 * 1024 * 1024 / 16);
+
+    println!("{:<16} {:<16} {:<8} {:<7} {:>9} {:>5} {:>12}", "id", "expected", "engine", "solved", "time", "depth", "nodes");
+    let (mut solved, mut points, mut total_nodes) = (0, 0, 0);
+    let start = Instant::now();
+    for (i, position) in positions.iter().enumerate() {
+        let id = if position.id.is_empty() { format!("#{}", i + 1) } else { position.id.clone() };
+        let expected = match (position.bm.is_empty(), position.am.is_empty()) {
+            (false, _) => format!("bm {}", position.bm.join(" ")),
+            (true, false) => format!("am {}", position.am.join(" ")),
+            (true, true) => "-".to_string(),
+        };
+        match solve(position, options.limit, &config, &service, &engine_state) {
+            Ok(result) => {
+                let time = match (result.solved, result.solve_time_ms) {
+                    (true, Some(ms)) => format!("{:.2}s", ms as f64 / 1000.0),
+                    _ => "-".to_string(),
+                };
+                if result.solved {
+                    solved += 1;
+                    points += result.solve_time_ms.map_or(0, lct_points);
+                }
+                total_nodes += result.nodes;
+                println!("{:<16} {:<16} {:<8} {:<7} {:>9} {:>5} {:>12}", id, expected, result.best_move,
+                    if result.solved { "YES" } else { "NO" }, time, result.depth, result.nodes);
+            },
+            Err(msg) => println!("{:<16} {:<16} {}", id, expected, msg),
+        }
+    }
+
+    let total = positions.len().max(1);
+    println!("solved {} / {} ({:.1}%) in {:.1}s, {} nodes", solved, positions.len(), solved as f64 * 100.0 / total as f64,
+        start.elapsed().as_secs_f64(), total_nodes);
+    if options.lct {
+        println!("LCT points {}, rating {}", points, 1900 + points);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_epd_test() {
+        let line = "1k1r3r/pp2qpp1/3b1n1p/3pNQ2/2pP1P2/2N1P3/PP4PP/1K1RR3 b - - bm Bb4; id \"LCTII.POS.02\"; c0 \"Lilienthal - Botvinnik; Moskau 1945\";";
+        let position = parse_epd_line(line).unwrap();
+        assert_eq!("1k1r3r/pp2qpp1/3b1n1p/3pNQ2/2pP1P2/2N1P3/PP4PP/1K1RR3 b - - 0 1", position.fen);
+        assert_eq!(vec!["Bb4"], position.bm);
+        assert_eq!("LCTII.POS.02", position.id);
+        assert_eq!("Lilienthal - Botvinnik; Moskau 1945", position.comment);
+
+        let position = parse_epd_line("8/8/8/4k3/8/8/4P3/4K3 w - - am Kd1 Kf1; hmvc 3; fmvn 40").unwrap();
+        assert_eq!("8/8/8/4k3/8/8/4P3/4K3 w - - 3 40", position.fen);
+        assert_eq!(vec!["Kd1", "Kf1"], position.am);
+        assert!(position.bm.is_empty());
+
+        assert_eq!(None, parse_epd_line("# comment line"));
+        assert_eq!(1, parse_epd("\n8/8/8/4k3/8/8/4P3/4K3 w - -\n\n").len());
+    }
+
+    #[test]
+    fn solve_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let engine_state = EngineState::offline(100_000);
+
+        let mate = parse_epd_line("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; id \"mate\";").unwrap();
+        let result = solve(&mate, SearchLimit::Depth(3), &config, &service, &engine_state).unwrap();
+        assert!(result.solved);
+        assert_eq!("Rd8#", result.best_move);
+        assert!(result.solve_time_ms.is_some());
+
+        let avoid = parse_epd_line("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - am Rd8#;").unwrap();
+        assert!(!solve(&avoid, SearchLimit::Nodes(1), &config, &service, &engine_state).unwrap().solved);
+
+        let illegal = parse_epd_line("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Qd8;").unwrap();
+        assert!(solve(&illegal, SearchLimit::Depth(1), &config, &service, &engine_state).is_err());
+        assert_eq!((30, 25, 0), (lct_points(9000), lct_points(9001), lct_points(600001)));
+    }
+
+    #[test]
+    fn underpromotion_test() {
+        let service = Service::new();
+        // as in `run`, the defaults skip underpromotions in the search
+        let mut config = Config::new();
+        config.print_info_string_during_search = false;
+        let engine_state = EngineState::offline(100_000);
+
+        let avoid = parse_epd_line("8/P7/8/8/8/8/k7/4K3 w - - am a8=B a8=R;").unwrap();
+        assert!(solve(&avoid, SearchLimit::Depth(3), &config, &service, &engine_state).unwrap().solved);
+        let best = parse_epd_line("8/P7/8/8/8/8/k7/4K3 w - - bm a8=R;").unwrap();
+        assert!(solve(&best, SearchLimit::Depth(1), &config, &service, &engine_state).is_ok());
+    }
+}
@@ -90,6 +90,83 @@ const BLACK_PASSED_PAWN_MASKS: [u64; 64] = {
     masks
 };
 
+/// One row of the eval trace, every side in its own view (positive is good for that side).
+pub struct EvalTerm {
+    pub name: &'static str,
+    pub white_mg: i16,
+    pub white_eg: i16,
+    pub black_mg: i16,
+    pub black_eg: i16,
+}
+
+/// Breakdown of the hand crafted eval into its terms and the scaling steps applied to the tapered sum.
+#[derive(Default)]
+pub struct EvalTrace {
+    pub terms: Vec<EvalTerm>,
+    pub game_phase: i16,
+    /// Eval after each step that changed it, in order of application.
+    pub steps: Vec<(&'static str, i16)>,
+    pub nnue: Option<i16>,
+    pub eval: i16,
+}
+
+impl EvalTrace {
+    /// Adds a white relative mg/eg value of one side to the term with the given name.
+    fn add(&mut self, name: &'static str, white: bool, mg: i16, eg: i16) {
+        let idx = match self.terms.iter().position(|term| term.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.terms.push(EvalTerm { name, white_mg: 0, white_eg: 0, black_mg: 0, black_eg: 0 });
+                self.terms.len() - 1
+            }
+        };
+        let term = &mut self.terms[idx];
+        if white {
+            term.white_mg += mg;
+            term.white_eg += eg;
+        } else {
+            term.black_mg -= mg;
+            term.black_eg -= eg;
+        }
+    }
+
+    fn step(&mut self, name: &'static str, eval: i16) {
+        if self.steps.last().is_none_or(|&(_, last)| last != eval) {
+            self.steps.push((name, eval));
+        }
+    }
+
+    /// Table of all terms, the steps and the final eval (white relative, centipawns).
+    pub fn to_table(&self) -> String {
+        let separator = format!("{}+{}+{}+{}\n", "-".repeat(18), "-".repeat(15), "-".repeat(15), "-".repeat(15));
+        let mut out = String::new();
+        out.push_str(&format!("{:>17} | {:^13} | {:^13} | {:^13}\n", "Term", "White", "Black", "Total"));
+        out.push_str(&format!("{:>17} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}\n", "", "MG", "EG", "MG", "EG", "MG", "EG"));
+        out.push_str(&separator);
+        let (mut total_mg, mut total_eg) = (0i32, 0i32);
+        for term in &self.terms {
+            let mg = term.white_mg as i32 - term.black_mg as i32;
+            let eg = term.white_eg as i32 - term.black_eg as i32;
+            total_mg += mg;
+            total_eg += eg;
+            out.push_str(&format!("{:>17} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}\n",
+                term.name, term.white_mg, term.white_eg, term.black_mg, term.black_eg, mg, eg));
+        }
+        out.push_str(&separator);
+        out.push_str(&format!("{:>17} | {:>13} | {:>13} | {:>6} {:>6}\n", "Total", "", "", total_mg, total_eg));
+        out.push('\n');
+        out.push_str(&format!("Game phase: {} (255 middlegame, 0 endgame)\n", self.game_phase));
+        for (name, eval) in &self.steps {
+            out.push_str(&format!("{:<22} {:>6}\n", format!("{}:", name), eval));
+        }
+        if let Some(nnue) = self.nnue {
+            out.push_str(&format!("{:<22} {:>6}\n", "NNUE eval:", nnue));
+        }
+        out.push_str(&format!("{:<22} {:>6} (white side)", "Final eval:", self.eval));
+        out
+    }
+}
+
 pub struct EvalService {
     _knight_moves: [i16; 8],
     attack_bonus_white: [(i16, i16, i16); 2],
@@ -166,10 +243,98 @@ impl EvalService {
     }
 
     pub fn calc_eval(&self, board: &Board, config: &Config, movegen: &MoveGenService, pawn_table: &crate::pawn_hash::PawnHashTable, alpha: i16, beta: i16, margin: i16) -> i16 {
+        self.calc_eval_traced(board, config, movegen, pawn_table, alpha, beta, margin, None)
+    }
+
+    /// Hand crafted eval of the board split into its terms, plus the NNUE eval if a net is loaded.
+    pub fn trace_eval(&self, board: &Board, config: &Config, movegen: &MoveGenService) -> EvalTrace {
+        let mut trace = EvalTrace { game_phase: self.get_game_phase(board) as i16, ..Default::default() };
+        if self.nnue_net.loaded {
+            trace.nnue = Some(crate::nnue_service::NNUEService::evaluate(board, &self.nnue_net));
+        }
+        let mut hce_config = config.clone();
+        hce_config.use_nnue = false;
+        hce_config.enable_lazy_eval = false;
+        let pawn_table = crate::pawn_hash::PawnHashTable::new(1);
+        trace.eval = self.calc_eval_traced(board, &hce_config, movegen, &pawn_table, i16::MIN, i16::MAX, 0, Some(&mut trace));
+        trace
+    }
+
+    fn calc_eval_traced(&self, board: &Board, config: &Config, movegen: &MoveGenService, pawn_table: &crate::pawn_hash::PawnHashTable, alpha: i16, beta: i16, margin: i16, mut trace: Option<&mut EvalTrace>) -> i16 {
+        let endgame = if config.enable_endgame_evaluators { crate::endgame::ENDGAMES.probe(board) } else { None };
+        if let Some(endgame) = endgame
+            && let Some(score) = endgame.exact.and_then(|exact| exact(board, endgame.strong_white))
+        {
+            let score = if endgame.strong_white { score } else { -score };
+            if let Some(trace) = trace.as_deref_mut() { trace.step("Endgame evaluator", score); }
+            return score;
+        }
+        let eval = self.calc_eval_without_noise(board, config, movegen, pawn_table, alpha, beta, margin, trace.as_deref_mut());
+        let eval = Self::apply_kpk_bitbase(eval, board, config);
+        if let Some(trace) = trace.as_deref_mut() { trace.step("KPK bitbase", eval); }
+        let eval = Self::apply_endgame_scale(eval, board, endgame);
+        if let Some(trace) = trace.as_deref_mut() { trace.step("Endgame scale", eval); }
+        if config.eval_noise > 0 && eval.abs() < 20000 {
+            let eval = eval.saturating_add(Self::eval_noise(board, config));
+            if let Some(trace) = trace { trace.step("Eval noise", eval); }
+            return eval;
+        }
+        eval
+    }
+
+    /// Exact KPK knowledge: drawn positions score 0, won positions get a known win bonus on top of the
+    /// heuristic eval, which keeps the gradient for pawn advance and king proximity.
+    pub fn apply_kpk_bitbase(eval: i16, board: &Board, config: &Config) -> i16 {
+        if !config.enable_kpk_bitbase || board.occupied.count_ones() != 3 {
+            return eval;
+        }
+        let white_pawn = board.bitboards[crate::model::WHITE_PAWN];
+        let black_pawn = board.bitboards[crate::model::BLACK_PAWN];
+        let white_king = board.bitboards[crate::model::WHITE_KING].trailing_zeros() as usize;
+        let black_king = board.bitboards[crate::model::BLACK_KING].trailing_zeros() as usize;
+
+        let (win, sign) = if white_pawn != 0 {
+            (crate::bitbase::probe_kpk(white_king, white_pawn.trailing_zeros() as usize, black_king, board.white_to_move), 1)
+        } else if black_pawn != 0 {
+            (crate::bitbase::probe_kpk(black_king ^ 56, black_pawn.trailing_zeros() as usize ^ 56, white_king ^ 56, !board.white_to_move), -1)
+        } else {
+            return eval;
+        };
+
+        if win {
+            eval.saturating_add(sign * crate::bitbase::KNOWN_WIN_BONUS)
+        } else {
+            0
+        }
+    }
+
+    /// Scales the eval down when it favors the strong side of an endgame with a drawish scale factor.
+    pub fn apply_endgame_scale(eval: i16, board: &Board, endgame: Option<crate::endgame::Endgame>) -> i16 {
+        let Some(endgame) = endgame else { return eval };
+        let Some(scale) = endgame.scale else { return eval };
+        if (eval > 0) != endgame.strong_white || eval == 0 {
+            return eval;
+        }
+        (eval as i32 * scale(board, endgame.strong_white) as i32 / 100) as i16
+    }
+
+    /// Deterministic noise in [-eval_noise, eval_noise] derived from the position hash and the skill seed,
+    /// so transpositions and repeated searches see the same value.
+    pub fn eval_noise(board: &Board, config: &Config) -> i16 {
+        let amplitude = config.eval_noise as i64;
+        let mut x = board.cached_hash ^ config.skill_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        x ^= x >> 33;
+        x = x.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+        x ^= x >> 33;
+        ((x % (2 * amplitude as u64 + 1)) as i64 - amplitude) as i16
+    }
+
+    fn calc_eval_without_noise(&self, board: &Board, config: &Config, movegen: &MoveGenService, pawn_table: &crate::pawn_hash::PawnHashTable, alpha: i16, beta: i16, margin: i16, mut trace: Option<&mut EvalTrace>) -> i16 {
         if config.use_nnue && self.nnue_net.loaded {
             return crate::nnue_service::NNUEService::evaluate(board, &self.nnue_net);
         }
         if Self::is_insufficient_material(board) {
+            if let Some(trace) = trace.as_deref_mut() { trace.step("Insufficient material", 0); }
             return 0;
         }
         let cheap = self.cheap_eval(board, config, pawn_table);
@@ -188,6 +353,7 @@ impl EvalService {
 
         let game_phase = self.get_game_phase(board) as i16;
         let mut eval: i16 = self.calculate_weighted_eval(board.pst_mg, board.pst_eg, game_phase);
+        if let Some(trace) = trace.as_deref_mut() { Self::trace_pst(trace, board); }
 
         // Pawn structure evaluation and caching
         let mut white_passed_pawns = 0u64;
@@ -213,7 +379,8 @@ impl EvalService {
         let mut struct_mg = 0;
         let mut struct_eg = 0;
 
-        if let Some((cached_mg, cached_eg)) = pawn_table.get(board.pawn_key) {
+        // the trace needs the pawn structure per side, which the cache doesn't keep
+        if trace.is_none() && let Some((cached_mg, cached_eg)) = pawn_table.get(board.pawn_key) {
             struct_mg = cached_mg;
             struct_eg = cached_eg;
         } else {
@@ -221,6 +388,7 @@ impl EvalService {
             while temp_w != 0 {
                 let sq = temp_w.trailing_zeros() as u8;
                 let (mg, eg) = self.white_pawn_structure_score(sq, board, config, white_passed_pawns);
+                if let Some(trace) = trace.as_deref_mut() { trace.add("Pawn structure", true, mg, eg); }
                 struct_mg += mg;
                 struct_eg += eg;
                 temp_w &= temp_w - 1;
@@ -229,6 +397,7 @@ impl EvalService {
             while temp_b != 0 {
                 let sq = temp_b.trailing_zeros() as u8;
                 let (mg, eg) = self.black_pawn_structure_score(sq, board, config, black_passed_pawns);
+                if let Some(trace) = trace.as_deref_mut() { trace.add("Pawn structure", false, mg, eg); }
                 struct_mg += mg;
                 struct_eg += eg;
                 temp_b &= temp_b - 1;
@@ -243,6 +412,7 @@ impl EvalService {
         while temp_w != 0 {
             let sq = temp_w.trailing_zeros() as u8;
             let (mg, eg) = self.white_pawn_dynamic_score(sq, board, config, white_passed_pawns);
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Pawn dynamics", true, mg, eg); }
             dyn_mg += mg;
             dyn_eg += eg;
             temp_w &= temp_w - 1;
@@ -251,6 +421,7 @@ impl EvalService {
         while temp_b != 0 {
             let sq = temp_b.trailing_zeros() as u8;
             let (mg, eg) = self.black_pawn_dynamic_score(sq, board, config, black_passed_pawns);
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Pawn dynamics", false, mg, eg); }
             dyn_mg += mg;
             dyn_eg += eg;
             temp_b &= temp_b - 1;
@@ -305,8 +476,9 @@ impl EvalService {
         let mut temp_w_rook = board.bitboards[crate::model::WHITE_ROOK];
         while temp_w_rook != 0 {
             let sq = temp_w_rook.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.white_rook(sq, board, config, game_phase, movegen, black_king_ring, black_pawn_attacks, board.white_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.white_rook(sq, board, config, game_phase, movegen, black_king_ring, black_pawn_attacks, board.white_pieces);
             if config.print_eval_per_figure { println!("{},\t11,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Rooks", true, mg, eg); }
             eval += eval_for_piece;
             white_attackers += attackers;
             white_king_danger += danger;
@@ -315,8 +487,9 @@ impl EvalService {
         let mut temp_w_knight = board.bitboards[crate::model::WHITE_KNIGHT];
         while temp_w_knight != 0 {
             let sq = temp_w_knight.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.white_knight(sq, board, config, game_phase, movegen, black_king_ring, white_true_outposts, black_pawn_attacks, board.white_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.white_knight(sq, board, config, game_phase, movegen, black_king_ring, white_true_outposts, black_pawn_attacks, board.white_pieces);
             if config.print_eval_per_figure { println!("{},\t12,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Knights", true, mg, eg); }
             eval += eval_for_piece;
             white_attackers += attackers;
             white_king_danger += danger;
@@ -325,8 +498,9 @@ impl EvalService {
         let mut temp_w_bishop = board.bitboards[crate::model::WHITE_BISHOP];
         while temp_w_bishop != 0 {
             let sq = temp_w_bishop.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.white_bishop(sq, board, config, game_phase, movegen, black_king_ring, white_true_outposts, black_pawn_attacks, board.white_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.white_bishop(sq, board, config, game_phase, movegen, black_king_ring, white_true_outposts, black_pawn_attacks, board.white_pieces);
             if config.print_eval_per_figure { println!("{},\t13,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Bishops", true, mg, eg); }
             eval += eval_for_piece;
             white_attackers += attackers;
             white_king_danger += danger;
@@ -335,8 +509,9 @@ impl EvalService {
         let mut temp_w_queen = board.bitboards[crate::model::WHITE_QUEEN];
         while temp_w_queen != 0 {
             let sq = temp_w_queen.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.white_queen(sq, board, config, game_phase, movegen, black_king_ring, black_pawn_attacks, board.white_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.white_queen(sq, board, config, game_phase, movegen, black_king_ring, black_pawn_attacks, board.white_pieces);
             if config.print_eval_per_figure { println!("{},\t14,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Queens", true, mg, eg); }
             eval += eval_for_piece;
             white_attackers += attackers;
             white_king_danger += danger;
@@ -345,8 +520,9 @@ impl EvalService {
         let mut temp_w_king = board.bitboards[crate::model::WHITE_KING];
         while temp_w_king != 0 {
             let sq = temp_w_king.trailing_zeros() as u8;
-            let (eval_for_piece, _attackers, _danger) = self.white_king(sq, board, config, game_phase, movegen);
+            let (eval_for_piece, _attackers, _danger, mg, eg) = self.white_king(sq, board, config, game_phase, movegen);
             if config.print_eval_per_figure { println!("{},\t15,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Kings", true, mg, eg); }
             eval += eval_for_piece;
             temp_w_king &= temp_w_king - 1;
         }
@@ -354,8 +530,9 @@ impl EvalService {
         let mut temp_b_rook = board.bitboards[crate::model::BLACK_ROOK];
         while temp_b_rook != 0 {
             let sq = temp_b_rook.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.black_rook(sq, board, config, game_phase, movegen, white_king_ring, white_pawn_attacks, board.black_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.black_rook(sq, board, config, game_phase, movegen, white_king_ring, white_pawn_attacks, board.black_pieces);
             if config.print_eval_per_figure { println!("{},\t21,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Rooks", false, mg, eg); }
             eval += eval_for_piece;
             black_attackers += attackers;
             black_king_danger += danger;
@@ -364,8 +541,9 @@ impl EvalService {
         let mut temp_b_knight = board.bitboards[crate::model::BLACK_KNIGHT];
         while temp_b_knight != 0 {
             let sq = temp_b_knight.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.black_knight(sq, board, config, game_phase, movegen, white_king_ring, black_true_outposts, white_pawn_attacks, board.black_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.black_knight(sq, board, config, game_phase, movegen, white_king_ring, black_true_outposts, white_pawn_attacks, board.black_pieces);
             if config.print_eval_per_figure { println!("{},\t22,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Knights", false, mg, eg); }
             eval += eval_for_piece;
             black_attackers += attackers;
             black_king_danger += danger;
@@ -374,8 +552,9 @@ impl EvalService {
         let mut temp_b_bishop = board.bitboards[crate::model::BLACK_BISHOP];
         while temp_b_bishop != 0 {
             let sq = temp_b_bishop.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.black_bishop(sq, board, config, game_phase, movegen, white_king_ring, black_true_outposts, white_pawn_attacks, board.black_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.black_bishop(sq, board, config, game_phase, movegen, white_king_ring, black_true_outposts, white_pawn_attacks, board.black_pieces);
             if config.print_eval_per_figure { println!("{},\t23,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Bishops", false, mg, eg); }
             eval += eval_for_piece;
             black_attackers += attackers;
             black_king_danger += danger;
@@ -384,8 +563,9 @@ impl EvalService {
         let mut temp_b_queen = board.bitboards[crate::model::BLACK_QUEEN];
         while temp_b_queen != 0 {
             let sq = temp_b_queen.trailing_zeros() as u8;
-            let (eval_for_piece, attackers, danger) = self.black_queen(sq, board, config, game_phase, movegen, white_king_ring, white_pawn_attacks, board.black_pieces);
+            let (eval_for_piece, attackers, danger, mg, eg) = self.black_queen(sq, board, config, game_phase, movegen, white_king_ring, white_pawn_attacks, board.black_pieces);
             if config.print_eval_per_figure { println!("{},\t24,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Queens", false, mg, eg); }
             eval += eval_for_piece;
             black_attackers += attackers;
             black_king_danger += danger;
@@ -394,8 +574,9 @@ impl EvalService {
         let mut temp_b_king = board.bitboards[crate::model::BLACK_KING];
         while temp_b_king != 0 {
             let sq = temp_b_king.trailing_zeros() as u8;
-            let (eval_for_piece, _attackers, _danger) = self.black_king(sq, board, config, game_phase, movegen);
+            let (eval_for_piece, _attackers, _danger, mg, eg) = self.black_king(sq, board, config, game_phase, movegen);
             if config.print_eval_per_figure { println!("{},\t25,\t{}", sq, eval_for_piece); }
+            if let Some(trace) = trace.as_deref_mut() { trace.add("Kings", false, mg, eg); }
             eval += eval_for_piece;
             temp_b_king &= temp_b_king - 1;
         }
@@ -444,12 +625,16 @@ impl EvalService {
         if white_attackers > 0 {
             let effective_attackers = (white_attackers as i16 - (black_defenders as i16 * config.king_ring_defender_value)).max(0) as usize;
             let idx = std::cmp::min(effective_attackers, 5);
-            eval += (white_king_danger * danger_weights[idx]) / 100;
+            let danger = (white_king_danger * danger_weights[idx]) / 100;
+            if let Some(trace) = trace.as_deref_mut() { trace.add("King attack", true, danger, danger); }
+            eval += danger;
         }
         if black_attackers > 0 {
             let effective_attackers = (black_attackers as i16 - (white_defenders as i16 * config.king_ring_defender_value)).max(0) as usize;
             let idx = std::cmp::min(effective_attackers, 5);
-            eval -= (black_king_danger * danger_weights[idx]) / 100;
+            let danger = (black_king_danger * danger_weights[idx]) / 100;
+            if let Some(trace) = trace.as_deref_mut() { trace.add("King attack", false, -danger, -danger); }
+            eval -= danger;
         }
 
         // Connected passed pawns bonus
@@ -483,6 +668,11 @@ impl EvalService {
             let o_bonus = (white_connected_passed_pawns - black_connected_passed_pawns) as i16 * config.connected_passed_pawn_mg;
             let e_bonus = (white_connected_passed_pawns - black_connected_passed_pawns) as i16 * config.connected_passed_pawn_eg;
             eval += self.calculate_weighted_eval(o_bonus, e_bonus, game_phase);
+            if let Some(trace) = trace.as_deref_mut() {
+                let (white_count, black_count) = (white_connected_passed_pawns as i16, black_connected_passed_pawns as i16);
+                trace.add("Connected passers", true, white_count * config.connected_passed_pawn_mg, white_count * config.connected_passed_pawn_eg);
+                trace.add("Connected passers", false, -black_count * config.connected_passed_pawn_mg, -black_count * config.connected_passed_pawn_eg);
+            }
         }
 
         let mut o_bishop_pair = 0;
@@ -496,6 +686,13 @@ impl EvalService {
             e_bishop_pair -= config.bishop_pair_bonus + 15;
         }
         eval += self.calculate_weighted_eval(o_bishop_pair, e_bishop_pair, game_phase);
+        if let Some(trace) = trace.as_deref_mut() {
+            let pair = |bb: usize| if board.bitboards[bb].count_ones() >= 2 { (config.bishop_pair_bonus, config.bishop_pair_bonus + 15) } else { (0, 0) };
+            let (white_mg, white_eg) = pair(crate::model::WHITE_BISHOP);
+            let (black_mg, black_eg) = pair(crate::model::BLACK_BISHOP);
+            trace.add("Bishop pair", true, white_mg, white_eg);
+            trace.add("Bishop pair", false, -black_mg, -black_eg);
+        }
 
 
 
@@ -517,10 +714,19 @@ impl EvalService {
                     // White holds opposition
                     eval += config.king_opposition_bonus;
                 }
+                if let Some(trace) = trace.as_deref_mut() {
+                    let bonus = if board.white_to_move { -config.king_opposition_bonus } else { config.king_opposition_bonus };
+                    trace.add("Opposition", !board.white_to_move, bonus, bonus);
+                }
             }
         }
 
         eval += if board.white_to_move { config.your_turn_bonus } else { -config.your_turn_bonus };
+        if let Some(trace) = trace.as_deref_mut() {
+            let bonus = if board.white_to_move { config.your_turn_bonus } else { -config.your_turn_bonus };
+            trace.add("Tempo", board.white_to_move, bonus, bonus);
+            trace.step("Tapered eval", eval);
+        }
 
         if config.enable_positional_cap {
             let mut material_eval: i16 = 0;
@@ -556,15 +762,19 @@ impl EvalService {
                 sign * (cap + capped_excess)
             };
             eval = material_eval + capped_positional;
+            if let Some(trace) = trace.as_deref_mut() { trace.step("Positional cap", eval); }
         }
 
         if Self::is_opposite_colored_bishops_endgame(board) {
             eval = (eval * config.opposite_bishops_draw_scale) / 100;
+            if let Some(trace) = trace.as_deref_mut() { trace.step("Opposite bishops", eval); }
         }
 
         eval = self.adjust_eval(eval, game_phase, config);
+        if let Some(trace) = trace.as_deref_mut() { trace.step("Adjust eval", eval); }
 
         eval = self.apply_endgame_mopup(eval, board, game_phase, config);
+        if let Some(trace) = trace { trace.step("Endgame mopup", eval); }
 
         if config.print_eval_per_figure {
             println!("{}", eval);
@@ -809,7 +1019,7 @@ impl EvalService {
         (o_eval, e_eval)
     }
 
-    fn white_rook(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn white_rook(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let rank = sq / 8;
@@ -919,10 +1129,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_rook;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn black_rook(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn black_rook(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let rank = sq / 8;
@@ -1032,10 +1242,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_rook;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn white_knight(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn white_knight(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1095,10 +1305,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_knight;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn black_knight(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn black_knight(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1158,10 +1368,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_knight;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn white_bishop(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn white_bishop(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1217,10 +1427,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_bishop;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn black_bishop(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn black_bishop(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, friendly_true_outposts: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1276,10 +1486,10 @@ impl EvalService {
         let danger = attacks_on_ring * config.king_ring_attack_bishop;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn white_queen(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn white_queen(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
 
@@ -1302,10 +1512,10 @@ impl EvalService {
         e_eval += mobility * config.queen_mobility_factor;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
 
-    fn black_queen(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16) {
+    fn black_queen(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService, opp_king_ring: u64, opp_pawn_attacks: u64, friendly_pieces: u64) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
 
@@ -1328,10 +1538,10 @@ impl EvalService {
         e_eval -= mobility * config.queen_mobility_factor;
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, attackers, danger)
+        (eval, attackers, danger, o_eval, e_eval)
     }
  
-    fn white_king(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService) -> (i16, u8, i16) {
+    fn white_king(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1408,10 +1618,10 @@ impl EvalService {
         }
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, 0, 0)
+        (eval, 0, 0, o_eval, e_eval)
     }
 
-    fn black_king(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService) -> (i16, u8, i16) {
+    fn black_king(&self, sq: u8, board: &Board, config: &Config, game_phase: i16, movegen: &MoveGenService) -> (i16, u8, i16, i16, i16) {
         let mut o_eval = 0;
         let mut e_eval = 0;
         let sq = sq as i32;
@@ -1488,7 +1698,28 @@ impl EvalService {
         }
 
         let eval = self.calculate_weighted_eval(o_eval, e_eval, game_phase);
-        (eval, 0, 0)
+        (eval, 0, 0, o_eval, e_eval)
+    }
+
+    /// Material and piece square values per side, the PST tables contain the material.
+    fn trace_pst(trace: &mut EvalTrace, board: &Board) {
+        let material = [
+            crate::pst::PIECE_EVAL_PAWN, crate::pst::PIECE_EVAL_ROOK, crate::pst::PIECE_EVAL_KNIGHT,
+            crate::pst::PIECE_EVAL_BISHOP, crate::pst::PIECE_EVAL_QUEEN, 0,
+        ];
+        for bb_idx in 0..12 {
+            let white = bb_idx < 6;
+            let sign = if white { 1 } else { -1 };
+            let king_value = if bb_idx % 6 == 5 { sign * crate::pst::PIECE_EVAL_KING } else { 0 };
+            let mut pieces = board.bitboards[bb_idx];
+            while pieces != 0 {
+                let sq = pieces.trailing_zeros() as usize;
+                let value = sign * material[bb_idx % 6];
+                trace.add("Material", white, value, value);
+                trace.add("PST", white, crate::pst::PST_MG[bb_idx][sq] - value - king_value, crate::pst::PST_EG[bb_idx][sq] - value - king_value);
+                pieces &= pieces - 1;
+            }
+        }
     }
 
     fn calculate_weighted_eval(&self, o_eval: i16, e_eval: i16, game_phase: i16) -> i16 {
@@ -1816,6 +2047,25 @@ impl EvalService {
         false
     }
 
+    /// No sequence of legal moves can mate: bare kings, a single minor piece or bishops on one
+    /// square color only. Unlike `is_insufficient_material` this holds without any heuristic.
+    pub fn is_dead_position(board: &Board) -> bool {
+        let heavy_or_pawns = board.bitboards[crate::model::WHITE_PAWN] | board.bitboards[crate::model::BLACK_PAWN]
+            | board.bitboards[crate::model::WHITE_ROOK] | board.bitboards[crate::model::BLACK_ROOK]
+            | board.bitboards[crate::model::WHITE_QUEEN] | board.bitboards[crate::model::BLACK_QUEEN];
+        if heavy_or_pawns != 0 {
+            return false;
+        }
+        let knights = board.bitboards[crate::model::WHITE_KNIGHT] | board.bitboards[crate::model::BLACK_KNIGHT];
+        let bishops = board.bitboards[crate::model::WHITE_BISHOP] | board.bitboards[crate::model::BLACK_BISHOP];
+        const DARK_SQUARES: u64 = 0xAA55_AA55_AA55_AA55;
+        match knights.count_ones() {
+            0 => bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0,
+            1 => bishops == 0,
+            _ => false,
+        }
+    }
+
     #[inline(always)]
     pub fn apply_endgame_mopup(&self, mut eval: i16, board: &Board, game_phase: i16, config: &Config) -> i16 {
         if !config.enable_endgame_mopup
@@ -2531,7 +2781,11 @@ mod tests {
         // 7. KPvK (Pawns present -> NOT insufficient material)
         let board_kpvk = fen_service.set_fen("8/8/8/4k3/8/5P2/4K3/8 w - - 0 1");
         assert!(!super::EvalService::is_insufficient_material(&board_kpvk));
-        assert_ne!(eval_service.calc_eval(&board_kpvk, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180), 0);
+        let mut heuristic_config = config.clone();
+        heuristic_config.enable_kpk_bitbase = false;
+        assert_ne!(eval_service.calc_eval(&board_kpvk, &heuristic_config, movegen, &pawn_table, i16::MIN, i16::MAX, 180), 0);
+        // the bitbase knows this one as a draw
+        assert_eq!(eval_service.calc_eval(&board_kpvk, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180), 0);
 
         // 8. KRvK (Rooks present -> NOT insufficient material)
         let board_krvk = fen_service.set_fen("8/8/8/4k3/8/5R2/4K3/8 w - - 0 1");
@@ -2721,12 +2975,12 @@ mod tests {
         // Position A: White Knight on d4 with free squares (Black pawns on a7/h7 far away)
         let board_free_knight = fen_service.set_fen("7k/p6p/8/8/3N4/8/8/K7 w - - 0 1");
         let free_pawn_attacks = super::EvalService::get_black_pawn_attacks(board_free_knight.bitboards[crate::model::BLACK_PAWN]);
-        let (free_knight_eval, _, _) = eval_service.white_knight(27, &board_free_knight, &config, 100, movegen, 0, 0, free_pawn_attacks, board_free_knight.white_pieces);
+        let (free_knight_eval, _, _, _, _) = eval_service.white_knight(27, &board_free_knight, &config, 100, movegen, 0, 0, free_pawn_attacks, board_free_knight.white_pieces);
 
         // Position B: Black pawns on c6 and e6 control knight landing squares - restricting safe mobility
         let board_guarded = fen_service.set_fen("7k/8/2p1p3/8/3N4/8/8/K7 w - - 0 1");
         let guarded_pawn_attacks = super::EvalService::get_black_pawn_attacks(board_guarded.bitboards[crate::model::BLACK_PAWN]);
-        let (guarded_knight_eval, _, _) = eval_service.white_knight(27, &board_guarded, &config, 100, movegen, 0, 0, guarded_pawn_attacks, board_guarded.white_pieces);
+        let (guarded_knight_eval, _, _, _, _) = eval_service.white_knight(27, &board_guarded, &config, 100, movegen, 0, 0, guarded_pawn_attacks, board_guarded.white_pieces);
 
         assert!(
             free_knight_eval > guarded_knight_eval,
@@ -2745,11 +2999,11 @@ mod tests {
 
         // Position A: White Rook on d4 on open board
         let board_open_rook = fen_service.set_fen("7k/8/8/8/3R4/8/8/K7 w - - 0 1");
-        let (open_rook_eval, _, _) = eval_service.white_rook(27, &board_open_rook, &config, 100, movegen, 0, 0, board_open_rook.white_pieces);
+        let (open_rook_eval, _, _, _, _) = eval_service.white_rook(27, &board_open_rook, &config, 100, movegen, 0, 0, board_open_rook.white_pieces);
 
         // Position B: White Rook on d4 hemmed in by friendly pawns on d3, d5, c4, e4
         let board_blocked_rook = fen_service.set_fen("7k/8/8/3P4/2PRP3/3P4/8/K7 w - - 0 1");
-        let (blocked_rook_eval, _, _) = eval_service.white_rook(27, &board_blocked_rook, &config, 100, movegen, 0, 0, board_blocked_rook.white_pieces);
+        let (blocked_rook_eval, _, _, _, _) = eval_service.white_rook(27, &board_blocked_rook, &config, 100, movegen, 0, 0, board_blocked_rook.white_pieces);
 
         assert!(
             open_rook_eval > blocked_rook_eval,
@@ -2769,8 +3023,8 @@ mod tests {
         let board_open_queen = fen_service.set_fen("7k/8/8/8/3Q4/8/8/K7 w - - 0 1");
         let board_trapped_queen = fen_service.set_fen("7k/8/8/8/8/8/1PP5/KPQ5 w - - 0 1");
 
-        let (open_q_eval, _, _) = eval_service.white_queen(27, &board_open_queen, &config, 128, movegen, 0, 0, board_open_queen.white_pieces);
-        let (trapped_q_eval, _, _) = eval_service.white_queen(2, &board_trapped_queen, &config, 128, movegen, 0, 0, board_trapped_queen.white_pieces);
+        let (open_q_eval, _, _, _, _) = eval_service.white_queen(27, &board_open_queen, &config, 128, movegen, 0, 0, board_open_queen.white_pieces);
+        let (trapped_q_eval, _, _, _, _) = eval_service.white_queen(2, &board_trapped_queen, &config, 128, movegen, 0, 0, board_trapped_queen.white_pieces);
 
         assert!(
             open_q_eval > trapped_q_eval,
@@ -2832,6 +3086,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kpk_bitbase_eval() {
+        let fen_service = Service::new().fen;
+        let eval_service = Service::new().eval;
+        let movegen = &Service::new().move_gen;
+        let mut config = Config::new();
+        config.use_nnue = false;
+        let pawn_table = crate::pawn_hash::PawnHashTable::new(16);
+
+        // king in front of its pawn on the sixth rank wins for both colors
+        let board_white = fen_service.set_fen("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1");
+        let eval_w = eval_service.calc_eval(&board_white, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180);
+        assert!(eval_w > crate::bitbase::KNOWN_WIN_BONUS, "eval_w={}", eval_w);
+
+        let board_black = fen_service.set_fen("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1");
+        let eval_b = eval_service.calc_eval(&board_black, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180);
+        assert!(eval_b < -crate::bitbase::KNOWN_WIN_BONUS, "eval_b={}", eval_b);
+
+        // rook pawn with the defending king in the corner
+        let board_draw = fen_service.set_fen("7k/8/6K1/7P/8/8/8/8 w - - 0 1");
+        assert_eq!(0, eval_service.calc_eval(&board_draw, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180));
+        let board_draw = fen_service.set_fen("8/8/8/8/p7/1k6/8/K7 b - - 0 1");
+        assert_eq!(0, eval_service.calc_eval(&board_draw, &config, movegen, &pawn_table, i16::MIN, i16::MAX, 180));
+    }
+
+    #[test]
+    fn test_eval_trace_matches_eval() {
+        let fen_service = Service::new().fen;
+        let eval_service = Service::new().eval;
+        let movegen = &Service::new().move_gen;
+        let mut config = Config::new();
+        config.use_nnue = false;
+        config.enable_lazy_eval = false;
+
+        for fen in [
+            "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+            "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 9",
+            "8/8/8/4k3/3b4/8/8/3RK3 w - - 0 1",
+            "8/8/8/4k3/8/8/8/2B1KN2 w - - 0 1",
+        ] {
+            let board = fen_service.set_fen(fen);
+            let trace = eval_service.trace_eval(&board, &config, movegen);
+            let eval = eval_service.calc_eval(&board, &config, movegen, &crate::pawn_hash::PawnHashTable::new(16), i16::MIN, i16::MAX, 180);
+            assert_eq!(eval, trace.eval, "{}", fen);
+            assert_eq!(eval, trace.steps.last().unwrap().1, "{}", fen);
+        }
+
+        // the tapered sum of the terms matches the tapered eval up to the rounding of each term
+        let board = fen_service.set_fen("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 9");
+        let trace = eval_service.trace_eval(&board, &config, movegen);
+        let (mg, eg) = trace.terms.iter().fold((0i32, 0i32), |(mg, eg), term| {
+            (mg + (term.white_mg - term.black_mg) as i32, eg + (term.white_eg - term.black_eg) as i32)
+        });
+        let tapered = (mg * trace.game_phase as i32 + eg * (256 - trace.game_phase as i32)) / 256;
+        let (name, traced) = trace.steps[0];
+        assert_eq!("Tapered eval", name);
+        assert!((tapered - traced as i32).abs() <= 16, "tapered={} traced={}", tapered, traced);
+        assert!(trace.to_table().contains("Pawn structure"));
+    }
+
+    #[test]
+    fn test_endgame_evaluators_eval() {
+        let fen_service = Service::new().fen;
+        let eval_service = Service::new().eval;
+        let movegen = &Service::new().move_gen;
+        let mut config = Config::new();
+        config.use_nnue = false;
+        let mut heuristic_config = config.clone();
+        heuristic_config.enable_endgame_evaluators = false;
+        let pawn_table = crate::pawn_hash::PawnHashTable::new(16);
+        let eval = |board: &crate::model::Board, config: &Config| eval_service.calc_eval(board, config, movegen, &pawn_table, i16::MIN, i16::MAX, 180);
+
+        // rook against bishop is scaled towards a draw
+        let board = fen_service.set_fen("8/8/8/4k3/3b4/8/8/3RK3 w - - 0 1");
+        assert!(eval(&board, &config) > 0);
+        assert!(eval(&board, &config) < eval(&board, &heuristic_config));
+        let board = fen_service.set_fen("3rk3/8/8/3B4/4K3/8/8/8 w - - 0 1");
+        assert!(eval(&board, &config) < 0);
+        assert!(eval(&board, &config) > eval(&board, &heuristic_config));
+
+        // bishop and knight mate is a known win for both colors
+        let board = fen_service.set_fen("8/8/8/4k3/8/8/8/2B1KN2 w - - 0 1");
+        assert!(eval(&board, &config) > crate::bitbase::KNOWN_WIN_BONUS);
+        let board = fen_service.set_fen("2b1kn2/8/8/8/4K3/8/8/8 w - - 0 1");
+        assert!(eval(&board, &config) < -crate::bitbase::KNOWN_WIN_BONUS);
+
+        // rook pawns with the wrong bishop
+        let board = fen_service.set_fen("7k/8/8/7P/7P/8/8/4KB2 w - - 0 1");
+        assert_eq!(0, eval(&board, &config));
+    }
+
     #[test]
     fn test_extended_insufficient_material_detection() {
         let fen_service = Service::new().fen;
@@ -2863,6 +3208,7 @@ mod tests {
         let movegen = &Service::new().move_gen;
         let mut config = Config::new();
         config.use_nnue = false;
+        config.enable_endgame_evaluators = false;
         let pawn_table = crate::pawn_hash::PawnHashTable::new(16);
 
         // Position A: White pawn phalanx on d4 + e4 (rank 4)
@@ -2914,11 +3260,11 @@ mod tests {
 
         // Position A: White Rook on e1 on open e-file facing Black Queen on e8
         let board_aligned_q = fen_service.set_fen("4q2k/8/8/8/8/8/8/4R1K1 w - - 0 1");
-        let (eval_aligned_q, _, _) = eval_service.white_rook(4, &board_aligned_q, &config, 150, movegen, 0, 0, board_aligned_q.white_pieces);
+        let (eval_aligned_q, _, _, _, _) = eval_service.white_rook(4, &board_aligned_q, &config, 150, movegen, 0, 0, board_aligned_q.white_pieces);
 
         // Position B: White Rook on d1 on open d-file with Black Queen on e8 (not aligned)
         let board_unaligned_q = fen_service.set_fen("4q2k/8/8/8/8/8/8/3R2K1 w - - 0 1");
-        let (eval_unaligned_q, _, _) = eval_service.white_rook(3, &board_unaligned_q, &config, 150, movegen, 0, 0, board_unaligned_q.white_pieces);
+        let (eval_unaligned_q, _, _, _, _) = eval_service.white_rook(3, &board_unaligned_q, &config, 150, movegen, 0, 0, board_unaligned_q.white_pieces);
 
         assert!(
             eval_aligned_q > eval_unaligned_q,
@@ -2937,11 +3283,11 @@ mod tests {
 
         // Position A: White King on e1 with open e-file facing Black Rook on e8 (heavy piece threat)
         let board_threat = fen_service.set_fen("4r2k/8/8/8/8/8/8/4K3 w - - 0 1");
-        let (eval_threat, _, _) = eval_service.white_king(4, &board_threat, &config, 150, movegen);
+        let (eval_threat, _, _, _, _) = eval_service.white_king(4, &board_threat, &config, 150, movegen);
 
         // Position B: White King on e1 with open e-file facing Black Knight on e8 (no heavy piece on file)
         let board_no_threat = fen_service.set_fen("4n2k/8/8/8/8/8/8/4K3 w - - 0 1");
-        let (eval_no_threat, _, _) = eval_service.white_king(4, &board_no_threat, &config, 150, movegen);
+        let (eval_no_threat, _, _, _, _) = eval_service.white_king(4, &board_no_threat, &config, 150, movegen);
 
         assert!(
             eval_threat < eval_no_threat,
@@ -2960,11 +3306,11 @@ mod tests {
 
         // Position A: White Bishop on b2 on open diagonal targeting Black King on g7 (aligned)
         let board_aligned_k = fen_service.set_fen("8/6k1/8/8/8/8/1B6/7K w - - 0 1");
-        let (eval_aligned_k, _, _) = eval_service.white_bishop(9, &board_aligned_k, &config, 150, movegen, 0, 0, 0, board_aligned_k.white_pieces);
+        let (eval_aligned_k, _, _, _, _) = eval_service.white_bishop(9, &board_aligned_k, &config, 150, movegen, 0, 0, 0, board_aligned_k.white_pieces);
 
         // Position B: White Bishop on b2 with Black King on h7 (not on b2 diagonal)
         let board_unaligned_k = fen_service.set_fen("8/7k/8/8/8/8/1B6/7K w - - 0 1");
-        let (eval_unaligned_k, _, _) = eval_service.white_bishop(9, &board_unaligned_k, &config, 150, movegen, 0, 0, 0, board_unaligned_k.white_pieces);
+        let (eval_unaligned_k, _, _, _, _) = eval_service.white_bishop(9, &board_unaligned_k, &config, 150, movegen, 0, 0, 0, board_unaligned_k.white_pieces);
 
         assert!(
             eval_aligned_k > eval_unaligned_k,
@@ -2983,11 +3329,11 @@ mod tests {
 
         // Position A: White Rook on a7 with Black King cut off on e8 (8th rank)
         let board_cutoff = fen_service.set_fen("4k3/R7/8/8/8/8/8/4K3 w - - 0 1");
-        let (eval_cutoff, _, _) = eval_service.white_rook(48, &board_cutoff, &config, 150, movegen, 0, 0, board_cutoff.white_pieces);
+        let (eval_cutoff, _, _, _, _) = eval_service.white_rook(48, &board_cutoff, &config, 150, movegen, 0, 0, board_cutoff.white_pieces);
 
         // Position B: White Rook on a7 with Black King active on e6 (6th rank, not trapped on 8th)
         let board_nocutoff = fen_service.set_fen("8/R7/4k3/8/8/8/8/4K3 w - - 0 1");
-        let (eval_nocutoff, _, _) = eval_service.white_rook(48, &board_nocutoff, &config, 150, movegen, 0, 0, board_nocutoff.white_pieces);
+        let (eval_nocutoff, _, _, _, _) = eval_service.white_rook(48, &board_nocutoff, &config, 150, movegen, 0, 0, board_nocutoff.white_pieces);
 
         assert!(
             eval_cutoff > eval_nocutoff,
@@ -3051,6 +3397,7 @@ mod tests {
         let movegen = &Service::new().move_gen;
         let mut config = Config::new();
         config.use_nnue = false;
+        config.enable_endgame_evaluators = false;
         let pawn_table = crate::pawn_hash::PawnHashTable::new(16);
 
         // Position A: White pawn storming on g4/h4 against Black King castled on g8
@@ -3088,6 +3435,18 @@ mod tests {
         assert!(super::EvalService::is_insufficient_material(&b_wrong_black), "Black wrong-colored bishop + rook pawn with White king on h1 must be recognized as draw");
     }
 
+    #[test]
+    fn dead_position_test() {
+        let fen_service = Service::new().fen;
+        for fen in ["8/8/8/4k3/8/8/4K3/8 w - - 0 1", "8/8/8/4k3/8/5N2/4K3/8 w - - 0 1", "8/8/8/8/5b2/8/4K3/2B1k3 w - - 0 1"] {
+            assert!(super::EvalService::is_dead_position(&fen_service.set_fen(fen)), "{}", fen);
+        }
+        // mate is possible, only unlikely: the eval scales these
+        for fen in ["8/8/8/8/2b5/8/4K3/2B1k3 w - - 0 1", "8/8/8/4k3/8/5NN1/4K3/8 w - - 0 1", "8/8/8/4k3/8/2n2N2/4K3/8 w - - 0 1", "k7/8/8/P7/8/2B5/8/7K w - - 0 1"] {
+            assert!(!super::EvalService::is_dead_position(&fen_service.set_fen(fen)), "{}", fen);
+        }
+    }
+
     #[test]
     fn test_all_new_eval_features_black_white_symmetry() {
         let fen_service = Service::new().fen;
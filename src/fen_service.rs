@@ -1,5 +1,6 @@
 use crate::model::{
-    Board, INIT_BOARD_FEN,
+    Board, INIT_BOARD_FEN, STANDARD_CASTLE_ROOK_SQUARES,
+    CASTLE_WHITE_LONG, CASTLE_WHITE_SHORT, CASTLE_BLACK_LONG, CASTLE_BLACK_SHORT,
     WHITE_PAWN, WHITE_ROOK, WHITE_KNIGHT, WHITE_BISHOP, WHITE_QUEEN, WHITE_KING,
     BLACK_PAWN, BLACK_ROOK, BLACK_KNIGHT, BLACK_BISHOP, BLACK_QUEEN, BLACK_KING,
 };
@@ -17,6 +18,7 @@ impl FenService {
         let turn_part = parts[1];
         let castling_part = parts[2];
         let en_passant_part = parts[3];
+        let halfmove_clock_part = if parts.len() > 4 { parts[4] } else { "0" };
         let move_number_part = if parts.len() > 5 { parts[5] } else { "1" };
 
         // Process the board position
@@ -56,11 +58,10 @@ impl FenService {
         // Process whose turn it is
         let white_to_move = turn_part == "w";
 
-        // Process castling possibilities
-        let white_possible_to_castle_short = castling_part.contains('K');
-        let white_possible_to_castle_long = castling_part.contains('Q');
-        let black_possible_to_castle_short = castling_part.contains('k');
-        let black_possible_to_castle_long = castling_part.contains('q');
+        // Process castling possibilities, KQkq (X-FEN) or rook files (Shredder-FEN)
+        let (castle_rights, castle_rook_squares) = self.parse_castling(castling_part, &bitboards);
+        let [white_possible_to_castle_long, white_possible_to_castle_short,
+            black_possible_to_castle_long, black_possible_to_castle_short] = castle_rights;
 
         // Process en passant possibility
         let mut field_for_en_passante = -1i8;
@@ -74,7 +75,7 @@ impl FenService {
         let white_king = bitboards[WHITE_KING] != 0;
         let black_king = bitboards[BLACK_KING] != 0;
 
-        Board::new(
+        let mut board = Board::new(
             bitboards,
             white_possible_to_castle_long,
             white_possible_to_castle_short,
@@ -85,7 +86,71 @@ impl FenService {
             move_number,
             white_king,
             black_king,
-        )
+        );
+        board.halfmove_clock = halfmove_clock_part.parse::<i32>().unwrap_or(0);
+        board.castle_rook_squares = castle_rook_squares;
+        board
+    }
+
+    /// Parses the castling field into rights and rook origins, indexed white long, white short, black long, black short.
+    /// `K`/`Q` refer to the outermost rook on that side of the king, file letters to the rook on that file.
+    fn parse_castling(&self, castling_part: &str, bitboards: &[u64; 12]) -> ([bool; 4], [u8; 4]) {
+        let mut rights = [false; 4];
+        let mut rook_squares = STANDARD_CASTLE_ROOK_SQUARES;
+
+        for c in castling_part.chars() {
+            let white = c.is_ascii_uppercase();
+            let base: u8 = if white { 0 } else { 56 };
+            let king_bb = bitboards[if white { WHITE_KING } else { BLACK_KING }] & (0xFFu64 << base);
+            let king_file = if king_bb != 0 { (king_bb.trailing_zeros() as u8) % 8 } else { 4 };
+            let back_rank_rooks = (bitboards[if white { WHITE_ROOK } else { BLACK_ROOK }] >> base) & 0xFF;
+
+            let (short, rook_file) = match c.to_ascii_lowercase() {
+                'k' => {
+                    let outer = back_rank_rooks & !((2u64 << king_file) - 1);
+                    (true, if outer != 0 { 63 - outer.leading_zeros() as u8 } else { 7 })
+                }
+                'q' => {
+                    let outer = back_rank_rooks & ((1u64 << king_file) - 1);
+                    (false, if outer != 0 { outer.trailing_zeros() as u8 } else { 0 })
+                }
+                f @ 'a'..='h' => {
+                    let file = f as u8 - b'a';
+                    (file > king_file, file)
+                }
+                _ => continue,
+            };
+            let right = match (white, short) {
+                (true, false) => CASTLE_WHITE_LONG,
+                (true, true) => CASTLE_WHITE_SHORT,
+                (false, false) => CASTLE_BLACK_LONG,
+                (false, true) => CASTLE_BLACK_SHORT,
+            };
+            rights[right] = true;
+            rook_squares[right] = base + rook_file;
+        }
+        (rights, rook_squares)
+    }
+
+    /// Castling letter for a right: `K`/`Q` if the rook is the outermost one on its side of the king, else its file.
+    fn castling_char(&self, board: &Board, right: usize) -> char {
+        let white = right == CASTLE_WHITE_LONG || right == CASTLE_WHITE_SHORT;
+        let short = right == CASTLE_WHITE_SHORT || right == CASTLE_BLACK_SHORT;
+        let rook_square = board.castle_rook_squares[right];
+        let base = rook_square - rook_square % 8;
+        let rook_file = rook_square % 8;
+        let back_rank_rooks = (board.bitboards[if white { WHITE_ROOK } else { BLACK_ROOK }] >> base) & 0xFF;
+        let outer = if short {
+            back_rank_rooks & !((2u64 << rook_file) - 1)
+        } else {
+            back_rank_rooks & ((1u64 << rook_file) - 1)
+        };
+        let c = match (outer == 0, short) {
+            (true, true) => 'k',
+            (true, false) => 'q',
+            (false, _) => (b'a' + rook_file) as char,
+        };
+        if white { c.to_ascii_uppercase() } else { c }
     }
 
     pub fn set_init_board(&self) -> Board {
@@ -147,16 +212,16 @@ impl FenService {
         fen.push(' ');
         let mut castling_rights = String::new();
         if board.white_possible_to_castle_short {
-            castling_rights.push('K');
+            castling_rights.push(self.castling_char(board, CASTLE_WHITE_SHORT));
         }
         if board.white_possible_to_castle_long {
-            castling_rights.push('Q');
+            castling_rights.push(self.castling_char(board, CASTLE_WHITE_LONG));
         }
         if board.black_possible_to_castle_short {
-            castling_rights.push('k');
+            castling_rights.push(self.castling_char(board, CASTLE_BLACK_SHORT));
         }
         if board.black_possible_to_castle_long {
-            castling_rights.push('q');
+            castling_rights.push(self.castling_char(board, CASTLE_BLACK_LONG));
         }
         if castling_rights.is_empty() {
             castling_rights.push('-');
@@ -172,8 +237,7 @@ impl FenService {
         }
 
         // Halfmove clock and fullmove number
-        fen.push_str(" 0 "); // Assuming halfmove clock is always 0 for now
-        fen.push_str(&board.move_count.to_string());
+        fen.push_str(&format!(" {} {}", board.halfmove_clock, board.move_count));
 
         fen
     }
@@ -221,5 +285,10 @@ mod tests {
         let board = fen_service.set_fen(&test_fen);
         let result_fen = fen_service.get_fen(&board);
         assert_eq!(test_fen, result_fen);
+
+        let test_fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 37 52";
+        let board = fen_service.set_fen(test_fen);
+        let result_fen = fen_service.get_fen(&board);
+        assert_eq!(test_fen, result_fen);
     }
 }
\ No newline at end of file
@@ -4,13 +4,23 @@ use std::sync::atomic::Ordering;
 
 
 use crate::Config;
-use crate::model::{EngineState, TimeInfo, TimeMode, SearchResult, UciGame, Stats};
+use crate::model::{EngineState, GameStatus, TimeInfo, TimeMode, SearchResult, UciGame, Stats};
 use crate::service::Service;
 use crate::book::Book;
 use crate::zobrist;
+use crate::time_manager::{IterationInfo, TimeLimits, TimeManager};
+use crate::skill::Skill;
+use crate::perft::{divide_report, Perft};
+use crate::params::{self, Param};
 
 use crate::model::RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE;
 
+/// UCI null move, the best move of a search in a mated or stalemated position.
+const NULL_MOVE: &str = "0000";
+
+/// Last search score (white view, cp) from which a game without a clear end counts as decided.
+const LEARN_DECISIVE_SCORE: i16 = 300;
+
 
 pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_command: Receiver<String>) {
     let service = &Service::new();
@@ -20,222 +30,117 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
     let mut book = Book::new();
     let logger = engine_state.log_sender.clone();
     let mut active_config = config.clone();
+    let mut last_score: Option<i16> = None;
+
+    // parameters given by --config take effect like a setoption
+    for param in params::PARAMS.iter().filter(|param| ["syzygy_path", "log_path"].contains(&param.field)) {
+        if !(param.get)(&active_config).is_empty() {
+            apply_param_effects(param, &active_config, service, &engine_state, &mut game, &mut book);
+        }
+    }
 
     while let Ok(command) = rx_game_command.recv() {
-        if command.trim() == "ucinewgame" {
+        if command.trim() == "ucinewgame" || command.trim() == "quit" {
+            if !book.learn_moves.is_empty() {
+                match finished_game_score(&mut game, last_score, service, &active_config) {
+                    Some(white_score) => learn_book(&mut book, white_score, &active_config, service, &logger),
+                    None => book.learn_moves.clear(),
+                }
+            }
+            if command.trim() == "quit" {
+                break;
+            }
+            last_score = None;
             game = UciGame::new(service.fen.set_init_board());
-            engine_state.stop_flag.store(false, Ordering::SeqCst);
-            engine_state.pv_nodes.lock().unwrap().clear();
-            engine_state.pv_nodes_len.store(0, Ordering::SeqCst);
+            game.board.chess960 = active_config.chess960;
+            engine_state.clear();
             service.pawn_table.clear();
-            engine_state.zobrist_table.read().unwrap().clear();
             logger.send("Start new Game".to_string()).expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
             continue;
         }
 
                 else if command.starts_with("setoption") {
                     let parts: Vec<&str> = command.split_whitespace().collect();
-                    if let Some(name_idx) = parts.iter().position(|&r| r.to_lowercase() == "name") {
-                        if let Some(val_idx) = parts.iter().position(|&r| r.to_lowercase() == "value") {
-                            let param_name = parts[name_idx+1..val_idx].join("_").to_lowercase()
-                                .replace("enablepositionalcap", "enable_positional_cap")
-                                .replace("enablelazyeval", "enable_lazy_eval")
-                                .replace("lazyevalmingamephase", "lazy_eval_min_game_phase")
-                                .replace("lazyevalmarginsearch", "lazy_eval_margin_search")
-                                .replace("lazyevalmarginqs", "lazy_eval_margin_qs")
-                                .replace("positionalcapdamping", "positional_cap_damping")
-                                .replace("enablefutilitypruning", "enable_futility_pruning")
-                                .replace("enableqstt", "enable_qs_tt")
-                                .replace("futilitymaxdepth", "futility_max_depth")
-                                .replace("futilitymarginbase", "futility_margin_base")
-                                .replace("futilitymarginslope", "futility_margin_slope");
-                            let val_str = parts[val_idx+1..].join(" ");
-
-                            if param_name == "aggressiveness" {
-                                if val_str.to_lowercase().contains("high") {
-                                    active_config.set_aggressiveness(crate::config::Aggressiveness::HighAggressive);
-                                } else if val_str.to_lowercase().contains("aggressive") {
-                                    active_config.set_aggressiveness(crate::config::Aggressiveness::Aggressive);
-                                } else {
-                                    active_config.set_aggressiveness(crate::config::Aggressiveness::Normal);
-                                }
-                            } else if param_name == "enable_lazy_eval" {
-                                active_config.enable_lazy_eval = val_str.to_lowercase() == "true";
-                            } else if param_name == "usennue" || param_name == "use_nnue" {
-                                active_config.use_nnue = val_str.to_lowercase() == "true";
-                            } else if param_name == "nnuemodelpath" || param_name == "nnue_model_path" {
-                                active_config.nnue_model_path = val_str.clone();
-                            } else if param_name == "enable_positional_cap" {
-                                active_config.enable_positional_cap = val_str.to_lowercase() == "true";
-                            } else if param_name == "move_overhead" {
-                                if let Ok(overhead) = val_str.parse::<u64>() { active_config.move_overhead = overhead; }
-                            } else {
-                                match param_name.as_str() {
-                                    "nmp_depth_threshold" => if let Ok(v) = val_str.parse::<i32>() { active_config.nmp_depth_threshold = v; },
-                                    "nmp_reduction" => if let Ok(v) = val_str.parse::<i32>() { active_config.nmp_reduction = v; },
-                                    "nmp_verification_threshold" => if let Ok(v) = val_str.parse::<i32>() { active_config.nmp_verification_threshold = v; },
-                                    "nmp_dynamic_divisor" => if let Ok(v) = val_str.parse::<i32>() { active_config.nmp_dynamic_divisor = v; },
-                                    "lmr_move_threshold" => if let Ok(v) = val_str.parse::<i32>() { active_config.lmr_move_threshold = v; },
-                                    "lmr_divisor" | "lmr_divisor_scaled" => if let Ok(v) = val_str.parse::<i32>() { active_config.lmr_divisor = v; active_config.recalculate_lmr_table(); },
-                                    "killer_move_1_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.killer_move_1_rank_bonus = v; },
-                                    "killer_move_2_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.killer_move_2_rank_bonus = v; },
-                                    "counter_move_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.counter_move_rank_bonus = v; },
-                                    "is_hashed_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.is_hashed_rank_bonus = v; },
-                                    "give_check_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.give_check_rank_bonus = v; },
-                                    "is_pv_node_rank_bonus" => if let Ok(v) = val_str.parse::<i32>() { active_config.is_pv_node_rank_bonus = v; },
-                                    "give_promotion_rank_bonus_queen" => if let Ok(v) = val_str.parse::<i32>() { active_config.give_promotion_rank_bonus_queen = v; },
-                                    "give_promotion_rank_bonus_knight" => if let Ok(v) = val_str.parse::<i32>() { active_config.give_promotion_rank_bonus_knight = v; },
-                                    "history_max_threshold" => if let Ok(v) = val_str.parse::<u32>() { active_config.history_max_threshold = v; },
-                                    "your_turn_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.your_turn_bonus = v; },
-                                    "aggressiveness" => match val_str.as_str() {
-                                        "Normal" => active_config.set_aggressiveness(crate::config::Aggressiveness::Normal),
-                                        "Aggressive" => active_config.set_aggressiveness(crate::config::Aggressiveness::Aggressive),
-                                        "HighAggressive" => active_config.set_aggressiveness(crate::config::Aggressiveness::HighAggressive),
-                                        _ => {}
-                                    },
-                                    "enablepositionalcap" | "enable_positional_cap" => {
-                                        active_config.enable_positional_cap = val_str == "true";
-                                    },
-                                    "positionalcapdamping" | "positional_cap_damping" => {
-                                        if let Ok(v) = val_str.parse::<i16>() { active_config.positional_cap_damping = v; }
-                                    },
-                                    "moveoverhead" | "move_overhead" => {
-                                        if let Ok(v) = val_str.parse::<u64>() { active_config.move_overhead = v; }
-                                    },
-                                     "kingopenfilemalus" | "king_open_file_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_open_file_malus = v; },
-                                     "kinghalfopenfilemalus" | "king_half_open_file_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_half_open_file_malus = v; },
-                                     "kingringdefendervalue" | "king_ring_defender_value" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_ring_defender_value = v; },
-                                     "threatminorattacksrook" | "threat_minor_attacks_rook" => if let Ok(v) = val_str.parse::<i16>() { active_config.threat_minor_attacks_rook = v; },
-                                     "threatminorattacksqueen" | "threat_minor_attacks_queen" => if let Ok(v) = val_str.parse::<i16>() { active_config.threat_minor_attacks_queen = v; },
-                                     "threatrookattacksqueen" | "threat_rook_attacks_queen" => if let Ok(v) = val_str.parse::<i16>() { active_config.threat_rook_attacks_queen = v; },
-                                     "logpath" | "log_path" => { active_config.log_path = std::sync::Arc::from(val_str.as_str()); },
-                                     "bookfile" | "book_file" => { active_config.book_file = val_str.to_string(); book.clear_polyglot_cache(); },
-                                     "cachebookinram" | "cache_book_in_ram" => {
-                                         active_config.cache_book_in_ram = val_str.to_lowercase() == "true";
-                                         if !active_config.cache_book_in_ram {
-                                             book.clear_polyglot_cache();
-                                         }
-                                     },
-                                     "ownbook" | "own_book" | "usebook" | "use_book" => { active_config.use_book = val_str.to_lowercase() == "true"; },
-                                    "pawn_structure" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_structure = v; },
-                                    "pawn_supports_knight_outpost" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_supports_knight_outpost = v; },
-                                    "pawn_centered" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_centered = v; },
-                                    "pawn_undeveloped_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_undeveloped_malus = v; },
-                                    "pawn_on_last_rank_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_on_last_rank_bonus = v; },
-                                    "pawn_on_before_last_rank_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_on_before_last_rank_bonus = v; },
-                                    "pawn_on_before_before_last_rank_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_on_before_before_last_rank_bonus = v; },
-                                    "pawn_defends_bishop" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_defends_bishop = v; },
-                                    "pawn_double_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_double_malus = v; },
-                                    "pawn_isolated_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_isolated_malus = v; },
-                                    "pawn_backward_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_backward_malus = v; },
-                                    "protected_passed_pawn_middlegame" => if let Ok(v) = val_str.parse::<i16>() { active_config.protected_passed_pawn_middlegame = v; },
-                                    "protected_passed_pawn_endgame" => if let Ok(v) = val_str.parse::<i16>() { active_config.protected_passed_pawn_endgame = v; },
-                                    "undeveloped_knight_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.undeveloped_knight_malus = v; },
-                                    "knight_on_rim_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_on_rim_malus = v; },
-                                    "knight_centered" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_centered = v; },
-                                    "knight_blockes_pawn" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_blockes_pawn = v; },
-                                    "knight_mobility_factor" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_mobility_factor = v; },
-                                    "undeveloped_bishop_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.undeveloped_bishop_malus = v; },
-                                    "bishop_pair_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_pair_bonus = v; },
-                                    "bishop_trapped_at_rim_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_trapped_at_rim_malus = v; },
-                                    "bishop_mobility_factor" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_mobility_factor = v; },
-                                    "rook_open_file" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_open_file = v; },
-                                    "rook_half_open_file" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_half_open_file = v; },
-                                    "rook_doubled_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_doubled_bonus = v; },
-                                    "rook_behind_passed_pawn_middlegame" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_behind_passed_pawn_middlegame = v; },
-                                    "rook_behind_passed_pawn_endgame" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_behind_passed_pawn_endgame = v; },
-                                    "rook_on_seventh" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_on_seventh = v; },
-                                    "rook_mobility_factor" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_mobility_factor = v; },
-                                    "queen_mobility_factor" | "queenmobilityfactor" => if let Ok(v) = val_str.parse::<i16>() { active_config.queen_mobility_factor = v; },
-                                    "king_passer_dist_weight" | "kingpasserdistweight" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_passer_dist_weight = v; },
-                                    "undeveloped_king_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.undeveloped_king_malus = v; },
-                                    "king_ring_attack_knight" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_ring_attack_knight = v; },
-                                    "king_ring_attack_bishop" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_ring_attack_bishop = v; },
-                                    "king_ring_attack_rook" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_ring_attack_rook = v; },
-                                    "king_ring_attack_queen" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_ring_attack_queen = v; },
-                                    "king_opposition_bonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_opposition_bonus = v; },
-                                    "king_pawn_shield" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_pawn_shield = v; },
-                                    "king_piece_shield" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_piece_shield = v; },
-                                    "king_pawn_shield_kingside" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_pawn_shield_kingside = v; },
-                                    "king_pawn_shield_queenside" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_pawn_shield_queenside = v; },
-                                    "king_piece_shield_kingside" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_piece_shield_kingside = v; },
-                                    "king_piece_shield_queenside" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_piece_shield_queenside = v; },
-                                    "connected_passed_pawn_mg" => if let Ok(v) = val_str.parse::<i16>() { active_config.connected_passed_pawn_mg = v; },
-                                    "connected_passed_pawn_eg" => if let Ok(v) = val_str.parse::<i16>() { active_config.connected_passed_pawn_eg = v; },
-                                    "knight_outpost_true_mg" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_outpost_true_mg = v; },
-                                    "knight_outpost_true_eg" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_outpost_true_eg = v; },
-                                    "bishop_outpost_true_mg" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_outpost_true_mg = v; },
-                                    "bishop_outpost_true_eg" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_outpost_true_eg = v; },
-                                     "opposite_bishops_draw_scale" => if let Ok(v) = val_str.parse::<i16>() { active_config.opposite_bishops_draw_scale = v; },
-                                     "enable_endgame_mopup" | "enableendgamemopup" => { active_config.enable_endgame_mopup = val_str.to_lowercase() == "true"; },
-                                     "mopup_center_weight" | "mopupcenterweight" => if let Ok(v) = val_str.parse::<i16>() { active_config.mopup_center_weight = v; },
-                                     "mopup_proximity_weight" | "mopupproximityweight" => if let Ok(v) = val_str.parse::<i16>() { active_config.mopup_proximity_weight = v; },
-                                     "mopup_eval_threshold" | "mopupevalthreshold" => if let Ok(v) = val_str.parse::<i16>() { active_config.mopup_eval_threshold = v; },
-                                     "mopup_max_game_phase" | "mopupmaxgamephase" => if let Ok(v) = val_str.parse::<i16>() { active_config.mopup_max_game_phase = v; },
-                                     "rook_behind_enemy_passed_pawn_mg" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_behind_enemy_passed_pawn_mg = v; },
-                                    "rook_behind_enemy_passed_pawn_eg" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_behind_enemy_passed_pawn_eg = v; },
-                                    "king_trapp_at_baseline_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_trapp_at_baseline_malus = v; },
-                                    "king_in_check_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_in_check_malus = v; },
-                                    "king_in_double_check_malus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_in_double_check_malus = v; },
-                                    "pawn_attacks_opponent_fig" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_attacks_opponent_fig = v; },
-                                    "pawn_attacks_opponent_fig_with_tempo" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_attacks_opponent_fig_with_tempo = v; },
-                                    "queen_in_attack" => if let Ok(v) = val_str.parse::<i16>() { active_config.queen_in_attack = v; },
-                                    "queen_in_attack_with_tempo" => if let Ok(v) = val_str.parse::<i16>() { active_config.queen_in_attack_with_tempo = v; },
-                                    "knight_attacks_bishop" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_attacks_bishop = v; },
-                                    "knight_attacks_rook" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_attacks_rook = v; },
-                                    "knight_attacks_bishop_tempo" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_attacks_bishop_tempo = v; },
-                                    "knight_attacks_rook_tempo" => if let Ok(v) = val_str.parse::<i16>() { active_config.knight_attacks_rook_tempo = v; },
-                                    "delta_pruning_margin" => if let Ok(v) = val_str.parse::<i16>() { active_config.delta_pruning_margin = v; },
-                                    "lazy_eval_margin_search" | "lazyevalmarginsearch" => if let Ok(v) = val_str.parse::<i16>() { active_config.lazy_eval_margin_search = v; },
-                                    "lazy_eval_margin_qs" | "lazyevalmarginqs" => if let Ok(v) = val_str.parse::<i16>() { active_config.lazy_eval_margin_qs = v; },
-                                    "lazy_eval_min_game_phase" => if let Ok(v) = val_str.parse::<u32>() { active_config.lazy_eval_min_game_phase = v; },
-                                    "king_danger_weight_1" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_danger_weight_1 = v; },
-                                    "king_danger_weight_2" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_danger_weight_2 = v; },
-                                    "king_danger_weight_3" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_danger_weight_3 = v; },
-                                    "king_danger_weight_4" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_danger_weight_4 = v; },
-                                    "king_danger_weight_5" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_danger_weight_5 = v; },
-                                    "enablefutilitypruning" | "enable_futility_pruning" => { active_config.enable_futility_pruning = val_str.to_lowercase() == "true"; },
-                                    "enableqstt" | "enable_qs_tt" => { active_config.enable_qs_tt = val_str.to_lowercase() == "true"; },
-                                    "futilitymaxdepth" | "futility_max_depth" => if let Ok(v) = val_str.parse::<i32>() { active_config.futility_max_depth = v; },
-                                    "futilitymarginbase" | "futility_margin_base" => if let Ok(v) = val_str.parse::<i16>() { active_config.futility_margin_base = v; },
-                                     "king_open_file_heavy_threat_malus" | "kingopenfileheavythreatmalus" => if let Ok(v) = val_str.parse::<i16>() { active_config.king_open_file_heavy_threat_malus = v; },
-                                     "rook_open_file_attacks_king" | "rookopenfileattacksking" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_open_file_attacks_king = v; },
-                                     "rook_open_file_attacks_queen" | "rookopenfileattacksqueen" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_open_file_attacks_queen = v; },
-                                     "pawn_phalanx_mg" | "pawnphalanxmg" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_phalanx_mg = v; },
-                                     "pawn_phalanx_eg" | "pawnphalanxeg" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_phalanx_eg = v; },
-                                     "bishop_diagonal_attacks_king" | "bishopdiagonalattacksking" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_diagonal_attacks_king = v; },
-                                     "bishop_diagonal_attacks_queen" | "bishopdiagonalattacksqueen" => if let Ok(v) = val_str.parse::<i16>() { active_config.bishop_diagonal_attacks_queen = v; },
-                                     "rook_on_seventh_king_cutoff" | "rookonseventhkingcutoff" => if let Ok(v) = val_str.parse::<i16>() { active_config.rook_on_seventh_king_cutoff = v; },
-                                     "rooks_doubled_on_seventh" | "rooksdoubledonseventh" => if let Ok(v) = val_str.parse::<i16>() { active_config.rooks_doubled_on_seventh = v; },
-                                     "passed_pawn_blockaded_malus" | "passedpawnblockadedmalus" => if let Ok(v) = val_str.parse::<i16>() { active_config.passed_pawn_blockaded_malus = v; },
-                                     "candidate_passed_pawn_bonus" | "candidatepassedpawnbonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.candidate_passed_pawn_bonus = v; },
-                                     "pawn_storm_bonus" | "pawnstormbonus" => if let Ok(v) = val_str.parse::<i16>() { active_config.pawn_storm_bonus = v; },
-                                     "futilitymarginslope" | "futility_margin_slope" => if let Ok(v) = val_str.parse::<i16>() { active_config.futility_margin_slope = v; },
-                                    _ => {}
+                    if let Some(name_idx) = parts.iter().position(|&r| r.to_lowercase() == "name")
+                        && let Some(val_idx) = parts.iter().position(|&r| r.to_lowercase() == "value") {
+                        let param_name = parts[name_idx+1..val_idx].join(" ");
+                        let val_str = parts[val_idx+1..].join(" ");
+
+                        match params::set_option(&mut active_config, &param_name, &val_str) {
+                            Ok(param) => {
+                                if param.field == "params_file" {
+                                    match params::load_params_file(&mut active_config, &val_str) {
+                                        Ok((applied, rejected)) => {
+                                            for msg in rejected {
+                                                stdout.write(&format!("info string {}", msg));
+                                            }
+                                            stdout.write(&format!("info string loaded {} parameters from {}", applied.len(), val_str));
+                                            for param in applied {
+                                                apply_param_effects(param, &active_config, service, &engine_state, &mut game, &mut book);
+                                            }
+                                        },
+                                        Err(msg) => stdout.write(&format!("info string {}", msg)),
+                                    }
                                 }
-                            }
-                            logger.send(format!("Received option: {} = {}\n", param_name, val_str)).ok();
+                                apply_param_effects(param, &active_config, service, &engine_state, &mut game, &mut book);
+                                logger.send(format!("Received option: {} = {}\n", param.field, (param.get)(&active_config))).ok();
+                            },
+                            Err(msg) => stdout.write(&format!("info string {}", msg)),
                         }
                     }
                 }
 
+                else if let Some(path) = command.strip_prefix("dumpparams") {
+                    let path = path.trim();
+                    if path.is_empty() {
+                        for (field, value) in params::dump(&active_config) {
+                            stdout.write(&format!("{} {}", field, value));
+                        }
+                    } else if let Err(msg) = params::write_params_file(&active_config, path) {
+                        stdout.write(&format!("info string {}", msg));
+                    } else {
+                        stdout.write(&format!("info string wrote parameters to {}", path));
+                    }
+                }
+
                 else if command.starts_with("board") {
                     let fen = command[6..].to_string();
                     game = UciGame::new(service.fen.set_fen(&fen));
+                    game.board.chess960 = active_config.chess960;
                 }
 
                 else if let Some(moves_str) = command.strip_prefix("moves") {
                     if command.len() <= 5 {
                         continue;
                     }
-                    let moves_iter = moves_str.split_whitespace();
-                    for mv in moves_iter {
-                        game.do_move(mv);
-                    }                   
+                    // the position stays at the last legal move, e.g. for moves sent from a stale position
+                    for mv in moves_str.split_whitespace() {
+                        if let Err(msg) = game.do_move(mv, &service.move_gen, &active_config) {
+                            stdout.write(&format!("info string {}, ignoring the remaining moves", msg));
+                            break;
+                        }
+                    }
+                }
+
+                else if let Some(result) = command.strip_prefix("result") {
+                    match result.trim() {
+                        "1-0" => learn_book(&mut book, 1.0, &active_config, service, &logger),
+                        "0-1" => learn_book(&mut book, 0.0, &active_config, service, &logger),
+                        "1/2-1/2" => learn_book(&mut book, 0.5, &active_config, service, &logger),
+                        other => stdout.write(&format!("info string invalid result {}, expected 1-0, 0-1 or 1/2-1/2", other)),
+                    }
+                }
+
+                else if command == "eval" {
+                    stdout.write(&service.eval.trace_eval(&game.board, &active_config, &service.move_gen).to_table());
                 }
 
                 else if command == "infinite" {
                     engine_state.stop_flag.store(false, Ordering::SeqCst);
+                    engine_state.root_move_nodes.lock().unwrap().clear();
+
+                    let mut search_config = active_config.clone();
+                    search_config.contempt = active_config.effective_contempt(true);
 
                     let mut best_result: Option<SearchResult> = None;
                     for depth in 2..100 {
@@ -247,7 +152,7 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
 
                         let is_white = game.board.white_to_move;
                         let mut stats = Stats::default();
-                        let search_result = service.search.get_moves(&mut game.board, depth, is_white, &mut stats, &active_config, service, &engine_state, std::time::Instant::now(), None);
+                        let search_result = service.search.get_moves(&mut game.board, depth, is_white, &mut stats, &search_config, service, &engine_state, std::time::Instant::now(), None);
 
                         if search_result.completed {
                             best_result = Some(search_result.clone());
@@ -287,7 +192,29 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                     }
                     if let Some(res) = best_result {
                         stdout.write(&format!("bestmove {}", res.get_best_move_algebraic()));
-                        game.do_move(&res.get_best_move_algebraic());
+                        play_engine_move(&mut game, &res.get_best_move_algebraic(), service, &active_config);
+                    }
+                }
+
+                else if let Some(args) = command.strip_prefix("go perft") {
+                    let mut words = args.split_whitespace();
+                    let depth = words.next().and_then(|depth| depth.parse().ok());
+                    let (mut bulk, mut hash_mb) = (false, 0);
+                    while let Some(word) = words.next() {
+                        match word {
+                            "bulk" => bulk = true,
+                            "hash" => hash_mb = words.next().and_then(|value| value.parse().ok()).unwrap_or(0),
+                            _ => {},
+                        }
+                    }
+                    match depth {
+                        Some(depth) => {
+                            let mut perft = Perft::new(&service.move_gen, &active_config, bulk, hash_mb);
+                            for line in divide_report(&mut perft, &game.board, depth) {
+                                stdout.write(&line);
+                            }
+                        },
+                        None => stdout.write("info string usage: go perft <depth> [bulk] [hash <mb>]"),
                     }
                 }
 
@@ -298,7 +225,18 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                     
                     let white = game.white_to_move();        
                     let game_fen = service.fen.get_fen(&game.board);
-                    let book_move = book.get_book_move(&game.board, &game_fen, &active_config, Some(&logger));
+                    // the books only know standard chess, their castling notation would not match a Chess960 board
+                    let mut book_move = if active_config.chess960 {
+                        String::new()
+                    } else {
+                        book.get_book_move(&game.board, &active_config, Some(&logger))
+                    };
+                    if !book_move.is_empty()
+                        && let Err(msg) = check_book_move(&book_move, &mut game, &active_config, service, &engine_state) {
+                        stdout.write(&format!("info string {}", msg));
+                        book.discard_learn_move(&game.board);
+                        book_move.clear();
+                    }
                     let time_info = uci_parser.parse_go(command.as_str());
 
                     if book_move.is_empty() {
@@ -323,32 +261,50 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
 
                         if valid_moves.len == 0 {
                             logger.send("No valid moves found at root! Game over.".to_string()).ok();
-                            stdout.write("bestmove 0000");
+                            stdout.write(&format!("bestmove {}", NULL_MOVE));
                             continue;
                         }
 
                         if valid_moves.len == 1 {
                             let mv_str = valid_moves.moves[0].to_algebraic();
                             stdout.write(&format!("bestmove {}", mv_str));
-                            game.do_move(&mv_str);
+                            play_engine_move(&mut game, &mv_str, service, &active_config);
                             logger.send(format!("Only one legal move found. Playing bestmove: {}", mv_str)).ok();
                             continue;
                         }
 
-                        let my_thinking_time = if time_info.time_mode == TimeMode::None || time_info.time_mode == TimeMode::Depth {
-                            i32::MAX as u64
+                        let mut time_manager = if time_info.time_mode == TimeMode::None || time_info.time_mode == TimeMode::Depth {
+                            None
                         } else {
-                            calculate_thinking_time(&time_info, white, game.board.move_count, &active_config)
+                            Some(TimeManager::new(calculate_time_limits(&time_info, white, game.board.move_count, &active_config)))
                         };
 
-                        logger.send(format!("My thinking time is: {}", my_thinking_time)).ok();
+                        if let Some(tm) = &time_manager {
+                            logger.send(format!("My thinking time is: soft {} hard {}", tm.limits().soft, tm.limits().hard)).ok();
+                        } else {
+                            logger.send("My thinking time is: unlimited".to_string()).ok();
+                        }
 
                         engine_state.pv_nodes.lock().unwrap().clear();
                         engine_state.pv_nodes_len.store(0, Ordering::SeqCst);
+                        engine_state.root_move_nodes.lock().unwrap().clear();
+
+                        let mut search_config = active_config.clone();
+                        search_config.contempt = active_config.effective_contempt(false);
+                        if time_manager.as_ref().is_some_and(|tm| tm.limits().fixed) {
+                            search_config.root_time_extension = 100;
+                        }
+                        let skill = Skill::from_config(&active_config);
+                        if let Some(skill) = &skill {
+                            skill.apply(&mut search_config);
+                            logger.send(format!("Playing at skill level {:.1} (depth {}, nodes {})",
+                                skill.level, skill.max_depth, skill.max_nodes)).ok();
+                        }
 
                         let go_start_time = std::time::Instant::now();
                         let mut best_result: Option<SearchResult> = None;
-                        let max_depth = active_config.max_depth;
+                        let max_depth = search_config.max_depth;
+                        let mut total_nodes: u64 = 0;
 
 
                         for depth in 2..=max_depth {
@@ -356,7 +312,17 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                                 break;
                             }
 
+                            if let Some(tm) = &time_manager {
+                                let elapsed = go_start_time.elapsed().as_millis() as u64;
+                                if best_result.is_some() && !tm.should_start_iteration(elapsed, &active_config) {
+                                    logger.send(format!("Skip level {} after {}ms (scaled soft limit {}ms)",
+                                        depth, elapsed, tm.scaled_soft_limit(&active_config))).ok();
+                                    break;
+                                }
+                            }
+
                             logger.send(format!("Start search on level {}", depth)).expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
+                            let iteration_start_time = std::time::Instant::now();
 
                             let mut stats = Stats::default();
                             let is_white = game.board.white_to_move;
@@ -366,13 +332,15 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                                 depth,
                                 is_white,
                                 &mut stats,
-                                &active_config,
+                                &search_config,
                                 service,
                                 &engine_state,
                                 go_start_time,
-                                Some(my_thinking_time as i32),
+                                Some(time_manager.as_ref().map_or(i32::MAX, |tm| tm.search_target(&active_config))),
                             );
 
+                            total_nodes += stats.calculated_nodes as u64;
+
                             if search_result.completed {
                                 best_result = Some(search_result.clone());
                                 service.stdout.write(&service.uci_parser.get_info_str(&search_result, &stats));
@@ -405,6 +373,7 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                                     nps,
                                     search_result.get_best_move_row()
                                 )).ok();
+                                logger.send(format!("Root move nodes: {}", search_result.get_root_move_nodes_str())).ok();
 
                                 let mut pv_guard = engine_state.pv_nodes.lock().unwrap();
                                 pv_guard.clear();
@@ -415,6 +384,15 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                                     old_board.do_move(&turn);
                                 }
                                 engine_state.pv_nodes_len.store(search_result.calculated_depth, Ordering::SeqCst);
+
+                                if let Some(tm) = time_manager.as_mut() {
+                                    tm.push_iteration(IterationInfo {
+                                        best_move: search_result.variants.first().and_then(|v| v.best_move),
+                                        score: cp,
+                                        time_ms: iteration_start_time.elapsed().as_millis() as u64,
+                                        best_move_node_share: search_result.get_best_move_node_share(),
+                                    });
+                                }
                             }
 
                             if time_info.time_mode == TimeMode::Depth && depth >= time_info.depth {
@@ -427,14 +405,26 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                                     break;
                                 }
                             }
+
+                            if skill.is_some_and(|skill| total_nodes >= skill.max_nodes) {
+                                logger.send(format!("Skill node limit reached after {} nodes", total_nodes)).ok();
+                                break;
+                            }
                         }
 
                         if let Some(res) = best_result {
-                            stdout.write(&format!("bestmove {}", res.get_best_move_algebraic()));
-                            game.do_move(&res.get_best_move_algebraic());
+                            last_score = Some(res.get_eval());
+                            let best_move = match &skill {
+                                Some(skill) => skill.pick_move(&res, active_config.skill_seed, game.board.cached_hash)
+                                    .map(|turn| turn.to_algebraic())
+                                    .unwrap_or_else(|| res.get_best_move_algebraic()),
+                                None => res.get_best_move_algebraic(),
+                            };
+                            stdout.write(&format!("bestmove {}", best_move));
+                            play_engine_move(&mut game, &best_move, service, &active_config);
                             logger.send(format!(
                                 "final move: bestmove {} (total time: {}ms)",
-                                res.get_best_move_algebraic(),
+                                best_move,
                                 go_start_time.elapsed().as_millis()
                             )).ok();
 
@@ -459,15 +449,15 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
                             if let Some(first_move) = valid_moves.as_slice().first() {
                                 let mv_str = first_move.to_algebraic();
                                 stdout.write(&format!("bestmove {}", mv_str));
-                                game.do_move(&mv_str);
+                                play_engine_move(&mut game, &mv_str, service, &active_config);
                             } else {
-                                stdout.write("bestmove 0000");
+                                stdout.write(&format!("bestmove {}", NULL_MOVE));
                             }
                         }
                     } else {
                         logger.send(format!("found Book move: {} for position {}", book_move, game_fen))
                             .expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
-                        game.do_move(&book_move);
+                        play_engine_move(&mut game, &book_move, service, &active_config);
                         stdout.write(&format!("bestmove {}", book_move));
                     }
                 }
@@ -475,6 +465,32 @@ pub fn game_loop(engine_state: Arc<EngineState>, config: &Config, rx_game_comman
 }
 
 
+/// Plays the engine's move in the game, a finished game has no move to play. A move that is not
+/// legal leaves the game at the last consistent position.
+fn play_engine_move(game: &mut UciGame, notation: &str, service: &Service, config: &Config) {
+    if notation == NULL_MOVE {
+        return;
+    }
+    if let Err(msg) = game.do_move(notation, &service.move_gen, config) {
+        service.stdout.write(&format!("info string engine {}, keeping the position", msg));
+    }
+}
+
+/// Soft limit is the regular thinking time, the hard limit allows extensions but stays within a share of the clock.
+fn calculate_time_limits(time_info: &TimeInfo, white: bool, move_count: i32, config: &Config) -> TimeLimits {
+    let soft = calculate_thinking_time(time_info, white, move_count, config);
+    let remaining = (if white { time_info.wtime } else { time_info.btime }).saturating_sub(config.move_overhead as i32).max(0) as u64;
+
+    let hard = match time_info.time_mode {
+        TimeMode::Movetime => soft,
+        _ => {
+            let extended = soft * config.tm_hard_limit_factor / 100;
+            extended.min(remaining * config.tm_max_remaining_percent / 100).max(soft)
+        }
+    };
+    TimeLimits { soft, hard, fixed: time_info.time_mode == TimeMode::Movetime }
+}
+
 fn calculate_thinking_time(time_info: &TimeInfo, white: bool, move_count: i32, config: &Config) -> u64 {
     let mut my_time = if white { time_info.wtime } else { time_info.btime };
     my_time = my_time.saturating_sub(config.move_overhead as i32);
@@ -521,11 +537,104 @@ fn calculate_thinking_time(time_info: &TimeInfo, white: bool, move_count: i32, c
 }
 
 
+/// Verifies a book move by a search of `book_check_depth` with exact scores for all root moves.
+/// Illegal moves and moves more than `book_check_margin` below the best move are rejected,
+/// without a check depth only the legality is verified.
+fn check_book_move(book_move: &str, game: &mut UciGame, config: &Config, service: &Service, engine_state: &Arc<EngineState>) -> Result<(), String> {
+    let mut move_config = config.clone();
+    move_config.use_underpromotions = true;
+    if !service.move_gen.legal_moves(&mut game.board, &move_config).iter().any(|turn| turn.to_algebraic() == book_move) {
+        return Err(format!("book move {} rejected, not legal in this position", book_move));
+    }
+    if config.book_check_depth == 0 {
+        return Ok(());
+    }
+
+    let mut search_config = config.clone();
+    search_config.multi_pv = search_config.multi_pv.max(2);
+    let white = game.board.white_to_move;
+    let depth = config.book_check_depth;
+    let search_result = service.search.get_moves(&mut game.board, depth, white, &mut Stats::default(), &search_config, service, engine_state, std::time::Instant::now(), None);
+
+    let sign = if white { 1 } else { -1 };
+    let best = search_result.root_move_scores.iter().map(|(_, score)| *score as i32 * sign).max();
+    let book_score = search_result.root_move_scores.iter()
+        .find(|(turn, _)| turn.to_algebraic() == book_move)
+        .map(|(_, score)| *score as i32 * sign);
+    match (best, book_score) {
+        (Some(best), Some(score)) if best - score > config.book_check_margin => {
+            Err(format!("book move {} rejected, {} cp below the best move at depth {}", book_move, best - score, depth))
+        },
+        _ => Ok(()),
+    }
+}
+
+
+/// White score of the game that just ended, from its final position or else the last search score.
+fn finished_game_score(game: &mut UciGame, last_score: Option<i16>, service: &Service, config: &Config) -> Option<f32> {
+    match game.board.game_status {
+        GameStatus::Draw => return Some(0.5),
+        GameStatus::WhiteWin => return Some(1.0),
+        GameStatus::BlackWin => return Some(0.0),
+        GameStatus::Normal => {},
+    }
+    if service.move_gen.legal_moves(&mut game.board, config).is_empty() {
+        let mated = service.move_gen.is_in_check(&game.board);
+        return Some(if !mated { 0.5 } else if game.board.white_to_move { 0.0 } else { 1.0 });
+    }
+    last_score.map(|score| match score {
+        score if score >= LEARN_DECISIVE_SCORE => 1.0,
+        score if score <= -LEARN_DECISIVE_SCORE => 0.0,
+        _ => 0.5,
+    })
+}
+
+
+fn learn_book(book: &mut Book, white_score: f32, config: &Config, service: &Service, logger: &std::sync::mpsc::Sender<String>) {
+    match book.learn(white_score, config) {
+        Ok(0) => {},
+        Ok(updated) => {
+            logger.send(format!("Book learning: updated {} entries of {} with score {}", updated, config.book_file, white_score)).ok();
+        },
+        Err(e) => service.stdout.write(&format!("info string could not update book {}: {}", config.book_file, e)),
+    }
+}
+
+
+/// Effects of a changed parameter outside of the config.
+fn apply_param_effects(param: &Param, config: &Config, service: &Service, engine_state: &EngineState, game: &mut UciGame, book: &mut Book) {
+    match param.field {
+        "syzygy_path" => {
+            let tables = service.tablebases.init(&config.syzygy_path);
+            if tables > 0 {
+                service.stdout.write(&format!("info string found {} tablebases up to {} pieces", tables, service.tablebases.max_pieces()));
+            }
+        },
+        "log_path" if !config.log_path.is_empty() => crate::threads::open_log_file(engine_state, &config.log_path),
+        "chess960" => game.board.chess960 = config.chess960,
+        "book_file" => book.clear_polyglot_cache(),
+        "cache_book_in_ram" if !config.cache_book_in_ram => book.clear_polyglot_cache(),
+        "max_zobrist_hash_entries" => {
+            *engine_state.zobrist_table.write().unwrap() = Arc::new(zobrist::ZobristTable::with_capacity(config.max_zobrist_hash_entries));
+            engine_state.log_sender.send(format!("Hash size set to {} entries", config.max_zobrist_hash_entries))
+                .expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
+        },
+        "search_threads" => engine_state.log_sender.send(format!("Single-threaded engine. Ignoring setoption threads to {}", config.search_threads))
+            .expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE),
+        _ => {}
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use crate::model::{TimeInfo, TimeMode};
-    use super::calculate_thinking_time;
+    use super::{calculate_thinking_time, calculate_time_limits, check_book_move, play_engine_move};
+    use crate::time_manager::TimeManager;
     use crate::Config;
+    use crate::model::{EngineState, UciGame};
+    use crate::service::Service;
+    use std::sync::Arc;
 
     #[test]
     fn calculate_thinking_time_test() {
@@ -556,5 +665,86 @@ mod tests {
         assert_eq!(400, thinking_time);
     }
 
+    #[test]
+    fn calculate_time_limits_test() {
+        let config = Config::new();
+
+        let time_info = TimeInfo{
+            wtime: 20000, btime: 10000, winc: 0, binc: 0, moves_to_go: 0, time_mode: TimeMode::HourGlas, depth: 0
+        };
+        let limits = calculate_time_limits(&time_info, true, 10, &config);
+        assert_eq!(600, limits.soft);
+        assert_eq!(1800, limits.hard);
 
-}
\ No newline at end of file
+        // sudden death with little time left: hard limit is capped by the remaining clock
+        let time_info = TimeInfo{
+            wtime: 1000, btime: 1000, winc: 500, binc: 500, moves_to_go: 0, time_mode: TimeMode::HourGlas, depth: 0
+        };
+        let limits = calculate_time_limits(&time_info, true, 60, &config);
+        assert_eq!(550, limits.soft);
+        assert_eq!(550, limits.hard);
+
+        let time_info = TimeInfo{
+            wtime: 3000, btime: 3000, winc: 0, binc: 0, moves_to_go: 0, time_mode: TimeMode::Movetime, depth: 0
+        };
+        let limits = calculate_time_limits(&time_info, false, 0, &config);
+        assert_eq!(limits.soft, limits.hard);
+        assert!(limits.fixed);
+        // the whole movetime is the search target, no room is left for a root time extension
+        let time_manager = TimeManager::new(limits);
+        assert_eq!(limits.hard as i32, time_manager.search_target(&config));
+        assert!(!calculate_time_limits(&TimeInfo{
+            wtime: 3000, btime: 3000, winc: 0, binc: 0, moves_to_go: 0, time_mode: TimeMode::HourGlas, depth: 0
+        }, false, 0, &config).fixed);
+    }
+
+    #[test]
+    fn check_book_move_test() {
+        let service = Service::new();
+        let (tx_log, _rx_log) = std::sync::mpsc::channel();
+        let engine_state = Arc::new(EngineState {
+            stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            debug_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            zobrist_table: std::sync::RwLock::new(Arc::new(crate::zobrist::ZobristTable::with_capacity(100_000))),
+            pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
+            logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
+            log_sender: tx_log,
+        });
+        let mut config = Config::for_tests();
+        config.book_check_depth = 3;
+        config.book_check_margin = 100;
+
+        // 1.e4 e5 2.Qh5, Ke7 walks into Qxe5#
+        let mut game = UciGame::new(service.fen.set_fen("rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 1 2"));
+        assert!(check_book_move("b8c6", &mut game, &config, &service, &engine_state).is_ok());
+        assert!(check_book_move("e8e7", &mut game, &config, &service, &engine_state).unwrap_err().contains("below the best move"));
+        assert!(check_book_move("e8e6", &mut game, &config, &service, &engine_state).unwrap_err().contains("not legal"));
+    }
+
+    #[test]
+    fn play_engine_move_on_finished_game_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let engine_state = EngineState::offline(1000);
+        let mut game = UciGame::new(service.fen.set_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1"));
+        game.do_move("d1d8", &service.move_gen, &config).unwrap();
+        let fen = service.fen.get_fen(&game.board);
+
+        let mut stats = crate::model::Stats::default();
+        let result = service.search.get_moves(&mut game.board, 1, false, &mut stats, &config, &service, &engine_state, std::time::Instant::now(), None);
+        assert_eq!("0000", result.get_best_move_algebraic());
+        play_engine_move(&mut game, &result.get_best_move_algebraic(), &service, &config);
+        assert_eq!(fen, service.fen.get_fen(&game.board));
+        assert_eq!("d1d8", game.made_moves_str);
+
+        // a move failing the validation is reported and skipped
+        let mut game = UciGame::new(service.fen.set_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1"));
+        let fen = service.fen.get_fen(&game.board);
+        play_engine_move(&mut game, "d1d9", &service, &config);
+        play_engine_move(&mut game, "e2e4", &service, &config);
+        assert_eq!(fen, service.fen.get_fen(&game.board));
+        assert!(game.made_moves_str.is_empty());
+    }
+}
@@ -10,6 +10,7 @@ mod search_service;
 mod service;
 mod move_gen_service;
 mod book;
+mod internal_book;
 mod uci_parser_service;
 mod zobrist;
 pub mod pawn_hash;
@@ -17,8 +18,21 @@ mod stdout_wrapper;
 mod threads;
 mod game_handler;
 mod time_check;
+mod time_manager;
 mod magic;
 mod pst;
+mod skill;
+mod bitbase;
+mod endgame;
+mod syzygy;
+mod texel;
+mod params;
+mod pgn;
+mod epd;
+mod selfplay;
+mod datagen;
+mod perft;
+mod makebook;
 pub mod polyglot;
 
 
@@ -38,6 +52,57 @@ use model::RIP_COULDN_JOIN_THREAD;
 
 fn main() {
     crate::magic::init();
+    crate::bitbase::init();
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("texel") {
+        crate::texel::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("internalbook") {
+        crate::internal_book::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("makebook") {
+        crate::makebook::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("epd") {
+        crate::epd::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("selfplay") {
+        crate::selfplay::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("datagen") {
+        crate::datagen::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("perft") {
+        crate::perft::run(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("pgn") {
+        crate::pgn::run(&args[2..]);
+        return;
+    }
+
+    let mut config = Config::new();
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        let Some(path) = args.get(index + 1) else {
+            eprintln!("usage: suprah --config <file>");
+            std::process::exit(2);
+        };
+        match crate::params::load_params_file(&mut config, path) {
+            Ok((_, rejected)) => rejected.iter().for_each(|msg| eprintln!("{}: {}", path, msg)),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                std::process::exit(2);
+            }
+        }
+        config.params_file = path.clone();
+    }
 
 
 
@@ -49,10 +114,11 @@ fn main() {
     let engine_state = Arc::new(crate::model::EngineState {
         stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
         debug_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-        zobrist_table: std::sync::RwLock::new(Arc::new(crate::zobrist::ZobristTable::with_capacity(Config::new().max_zobrist_hash_entries))),
+        zobrist_table: std::sync::RwLock::new(Arc::new(crate::zobrist::ZobristTable::with_capacity(config.max_zobrist_hash_entries))),
 
         pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
         pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+        root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
         logger: Arc::new(std::sync::RwLock::new(initial_logger)),
         log_sender: tx_log_buffer.clone(),
     });
@@ -60,32 +126,36 @@ fn main() {
     // Set up std reader thread
     // Read std in and send to uci command processor
     let std_in_sender = tx_std_in.clone();
+    let std_in_config = config.clone();
     let std_in_thread = thread::spawn(move || {
-        std_reader(std_in_sender, &Config::new());
+        std_reader(std_in_sender, &std_in_config);
     });
 
     // Set up uci command thread
     // Receive uci commands and send internal command to game handler or other threads
     let engine_state_command_processor = engine_state.clone();
     let tx_game_command_clone = tx_game_command.clone();
+    let command_processor_config = config.clone();
     let _uci_command_processor = thread::spawn(move || {
-        uci_command_processor(engine_state_command_processor, &Config::new(), rx_std_in, tx_game_command_clone);
+        uci_command_processor(engine_state_command_processor, &command_processor_config, rx_std_in, tx_game_command_clone);
     });
 
     // Set up game loop thread
     // receives command by uci_command_processor
     let engine_state_game_loop = engine_state.clone();
-    let _game_handler = thread::spawn(move || {
-        game_loop(engine_state_game_loop, &Config::new(), rx_game_command);
+    let game_loop_config = config.clone();
+    let game_handler = thread::spawn(move || {
+        game_loop(engine_state_game_loop, &game_loop_config, rx_game_command);
     });
 
     // Set up logger threads
     let engine_state_log_buffer = engine_state.clone();
     let _logger_buffer = thread::spawn(move || {
-        logger_buffer_thread(engine_state_log_buffer, &Config::new(), rx_log_buffer);
+        logger_buffer_thread(engine_state_log_buffer, &config, rx_log_buffer);
     });
 
     std_in_thread.join().expect(RIP_COULDN_JOIN_THREAD);
+    game_handler.join().expect(RIP_COULDN_JOIN_THREAD);
 }
 
 
@@ -121,6 +191,7 @@ mod tests {
 
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(initial_logger)),
             log_sender: tx_log_buffer.clone(),
         });
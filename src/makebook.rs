@@ -0,0 +1,262 @@
+//! Builds a PolyGlot opening book from PGN game collections.
+//!
+//! Every game is replayed up to the ply limit and each move is counted for the position it was
+//! played in, keyed by `polyglot_key`. A move enters the book with the weight 2 * wins + draws of
+//! the side that played it, after the game, rating and result filters.
+
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::fen_service::FenService;
+use crate::move_gen_service::MoveGenService;
+use crate::pgn::{parse_pgn, san_to_turn, PgnGame};
+use crate::polyglot::{polyglot_key, PolyglotBook, PolyglotEntry};
+
+/// Which games contribute the moves of a side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResultFilter {
+    All,
+    /// Only moves of the side that won or drew the game.
+    NotLost,
+    /// Only moves of the side that won the game.
+    Won,
+}
+
+pub struct MakebookOptions {
+    pub pgn_files: Vec<String>,
+    pub out: String,
+    pub max_ply: usize,
+    pub min_games: u32,
+    pub min_elo: u32,
+    pub results: ResultFilter,
+}
+
+impl MakebookOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = MakebookOptions {
+            pgn_files: Vec::new(),
+            out: "book.bin".to_string(),
+            max_ply: 30,
+            min_games: 3,
+            min_elo: 0,
+            results: ResultFilter::All,
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--out" => options.out = value()?.clone(),
+                "--max-ply" => options.max_ply = value()?.parse().map_err(|_| "invalid --max-ply".to_string())?,
+                "--min-games" => options.min_games = value()?.parse().map_err(|_| "invalid --min-games".to_string())?,
+                "--min-elo" => options.min_elo = value()?.parse().map_err(|_| "invalid --min-elo".to_string())?,
+                "--results" => options.results = match value()?.as_str() {
+                    "all" => ResultFilter::All,
+                    "not-lost" => ResultFilter::NotLost,
+                    "won" => ResultFilter::Won,
+                    other => return Err(format!("invalid --results {}, expected all, not-lost or won", other)),
+                },
+                _ if !arg.starts_with("--") => options.pgn_files.push(arg.clone()),
+                _ => return Err(format!("unknown argument {}", arg)),
+            }
+        }
+        if options.pgn_files.is_empty() {
+            return Err("usage: suprah makebook <pgn>... [--out <file>] [--max-ply <n>] [--min-games <n>] [--min-elo <n>] [--results all|not-lost|won]".to_string());
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct MoveStats {
+    games: u32,
+    wins: u32,
+    draws: u32,
+}
+
+pub struct BookBuilder {
+    max_ply: usize,
+    min_elo: u32,
+    results: ResultFilter,
+    stats: HashMap<(u64, u16), MoveStats>,
+    config: Config,
+    move_gen: MoveGenService,
+    pub games_used: usize,
+    pub games_skipped: usize,
+}
+
+impl BookBuilder {
+    pub fn new(options: &MakebookOptions) -> Self {
+        crate::magic::init();
+        BookBuilder {
+            max_ply: options.max_ply,
+            min_elo: options.min_elo,
+            results: options.results,
+            stats: HashMap::new(),
+            config: Config::new(),
+            move_gen: MoveGenService::new(),
+            games_used: 0,
+            games_skipped: 0,
+        }
+    }
+
+    /// Score of white (1, 0.5, 0) or None for unfinished games.
+    fn white_score(game: &PgnGame) -> Option<f32> {
+        match game.result.as_str() {
+            "1-0" => Some(1.0),
+            "0-1" => Some(0.0),
+            "1/2-1/2" => Some(0.5),
+            _ => None,
+        }
+    }
+
+    fn elo(game: &PgnGame, white: bool) -> u32 {
+        game.tag(if white { "WhiteElo" } else { "BlackElo" }).and_then(|elo| elo.parse().ok()).unwrap_or(0)
+    }
+
+    /// Counts the moves of a game, stops at the ply limit or the first unreadable move.
+    pub fn add_game(&mut self, game: &PgnGame) {
+        let Some(white_score) = Self::white_score(game) else {
+            self.games_skipped += 1;
+            return;
+        };
+        if game.start_fen().split_whitespace().count() < 4 {
+            self.games_skipped += 1;
+            return;
+        }
+        self.games_used += 1;
+
+        let mut board = FenService.set_fen(game.start_fen());
+        for mv in game.moves.iter().take(self.max_ply) {
+            let Some(turn) = san_to_turn(&mut board, &mv.san, &self.move_gen, &self.config) else {
+                break;
+            };
+            let white = board.white_to_move;
+            let score = if white { white_score } else { 1.0 - white_score };
+            let counted = Self::elo(game, white) >= self.min_elo && match self.results {
+                ResultFilter::All => true,
+                ResultFilter::NotLost => score > 0.0,
+                ResultFilter::Won => score == 1.0,
+            };
+            if counted {
+                let stats = self.stats.entry((polyglot_key(&board), PolyglotEntry::encode_move(&board, &turn))).or_default();
+                stats.games += 1;
+                stats.wins += (score == 1.0) as u32;
+                stats.draws += (score == 0.5) as u32;
+            }
+            board.do_move(&turn);
+        }
+    }
+
+    /// Book of all moves played in at least `min_games` games with a non zero weight. The weights
+    /// of a position are scaled down together if one exceeds the 16 bit range.
+    pub fn build(&self, min_games: u32) -> PolyglotBook {
+        let mut by_key: HashMap<u64, Vec<(u16, u32)>> = HashMap::new();
+        for (&(key, mov), stats) in &self.stats {
+            let weight = 2 * stats.wins + stats.draws;
+            if stats.games >= min_games && weight > 0 {
+                by_key.entry(key).or_default().push((mov, weight));
+            }
+        }
+        let mut entries = Vec::new();
+        for (key, moves) in by_key {
+            let max = moves.iter().map(|(_, weight)| *weight).max().unwrap_or(0);
+            for (mov, weight) in moves {
+                let weight = if max > u16::MAX as u32 { (weight as u64 * u16::MAX as u64 / max as u64).max(1) as u16 } else { weight as u16 };
+                entries.push(PolyglotEntry { key, mov, weight, learn: 0 });
+            }
+        }
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.mov.cmp(&b.mov)));
+        PolyglotBook::from_entries(entries)
+    }
+}
+
+/// Entry point of `suprah makebook ...`.
+pub fn run(args: &[String]) {
+    let options = match MakebookOptions::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut builder = BookBuilder::new(&options);
+    for path in &options.pgn_files {
+        let content = std::fs::read(path).unwrap_or_else(|e| {
+            eprintln!("could not read {}: {}", path, e);
+            std::process::exit(2);
+        });
+        for game in parse_pgn(&String::from_utf8_lossy(&content)) {
+            builder.add_game(&game);
+        }
+        println!("read {}", path);
+    }
+
+    let book = builder.build(options.min_games);
+    if let Err(e) = book.write(&options.out) {
+        eprintln!("could not write {}: {}", options.out, e);
+        std::process::exit(2);
+    }
+    println!("{} games used, {} skipped, wrote {} entries to {}", builder.games_used, builder.games_skipped, book.entries().len(), options.out);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    fn options(args: &str) -> MakebookOptions {
+        MakebookOptions::parse(&args.split_whitespace().map(String::from).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn makebook_weights_and_filters_test() {
+        let pgn = "[WhiteElo \"2500\"]\n[BlackElo \"2000\"]\n\n1. e4 e5 2. Nf3 1-0\n\n\
+                   [WhiteElo \"2500\"]\n[BlackElo \"2000\"]\n\n1. e4 c5 1/2-1/2\n\n\
+                   [WhiteElo \"2500\"]\n[BlackElo \"2000\"]\n\n1. d4 d5 0-1\n\n\
+                   1. e4 e5 *";
+        let games = parse_pgn(pgn);
+
+        let mut builder = BookBuilder::new(&options("games.pgn --max-ply 2"));
+        games.iter().for_each(|game| builder.add_game(game));
+        assert_eq!((3, 1), (builder.games_used, builder.games_skipped));
+
+        let service = Service::new();
+        let start = service.fen.set_init_board();
+        let book = builder.build(1);
+        // e4: one win and one draw, d4 lost and has no weight, Nf3 is beyond the ply limit, e5 lost
+        let moves: Vec<(String, u16)> = book.find_entries(&start).iter().map(|e| (e.to_algebraic(), e.weight)).collect();
+        assert_eq!(vec![("e2e4".to_string(), 3)], moves);
+        assert_eq!(3, book.entries().len());
+        assert_eq!(1, builder.build(2).entries().len());
+
+        // black moves only count for players rated 2100 and more
+        let mut builder = BookBuilder::new(&options("games.pgn --min-elo 2100"));
+        games.iter().for_each(|game| builder.add_game(game));
+        assert!(builder.build(1).entries().iter().all(|e| ["e2e4", "g1f3"].contains(&e.to_algebraic().as_str())));
+
+        let mut builder = BookBuilder::new(&options("games.pgn --results won"));
+        games.iter().for_each(|game| builder.add_game(game));
+        assert_eq!(3, builder.build(1).entries().len());
+    }
+
+    #[test]
+    fn makebook_writes_readable_book_test() {
+        let pgn = "1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O 1-0\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O 1-0";
+        let mut builder = BookBuilder::new(&options("games.pgn"));
+        parse_pgn(pgn).iter().for_each(|game| builder.add_game(game));
+
+        let path = std::env::temp_dir().join(format!("makebook_test_{}.bin", std::process::id()));
+        builder.build(1).write(&path).unwrap();
+        let book = PolyglotBook::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let service = Service::new();
+        let board = service.fen.set_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 6 4");
+        assert_eq!("e1g1", book.get_random_book_move(&board));
+        // only the moves of the winning side have a weight
+        assert_eq!(4, book.entries().len());
+        assert!(book.entries().windows(2).all(|pair| pair[0].key <= pair[1].key));
+    }
+}
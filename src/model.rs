@@ -2,7 +2,10 @@ use std::sync::atomic::AtomicBool;
 use std::collections::{HashMap, VecDeque};
 
 use crate::zobrist;
-use crate::{notation_util::NotationUtil, zobrist::ZobristTable};
+use crate::zobrist::ZobristTable;
+use crate::config::Config;
+use crate::fen_service::FenService;
+use crate::move_gen_service::MoveGenService;
 
 pub const INIT_BOARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
@@ -21,10 +24,39 @@ pub struct EngineState {
 
     pub pv_nodes: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<u64, Turn>>>,
     pub pv_nodes_len: std::sync::Arc<std::sync::atomic::AtomicI32>,
+    /// Node counts per root move of the last completed iteration, best move first.
+    pub root_move_nodes: std::sync::Arc<std::sync::Mutex<Vec<(Turn, u64)>>>,
     pub logger: std::sync::Arc<std::sync::RwLock<LoggerFn>>,
     pub log_sender: std::sync::mpsc::Sender<String>,
 }
 
+impl EngineState {
+    /// State for searches outside of the UCI loop (epd, selfplay, ...). Log messages are dropped.
+    pub fn offline(zobrist_entries: usize) -> std::sync::Arc<Self> {
+        let (log_sender, log_receiver) = std::sync::mpsc::channel::<String>();
+        std::thread::spawn(move || log_receiver.iter().for_each(drop));
+        std::sync::Arc::new(EngineState {
+            stop_flag: std::sync::Arc::new(AtomicBool::new(false)),
+            debug_flag: std::sync::Arc::new(AtomicBool::new(false)),
+            zobrist_table: std::sync::RwLock::new(std::sync::Arc::new(ZobristTable::with_capacity(zobrist_entries))),
+            pv_nodes: std::sync::Arc::new(std::sync::Mutex::new(HashMap::new())),
+            pv_nodes_len: std::sync::Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
+            logger: std::sync::Arc::new(std::sync::RwLock::new(std::sync::Arc::new(|_| {}))),
+            log_sender,
+        })
+    }
+
+    /// Forgets everything learned about earlier positions, as for a new game.
+    pub fn clear(&self) {
+        self.stop_flag.store(false, std::sync::atomic::Ordering::SeqCst);
+        self.pv_nodes.lock().expect(RIP_COULDN_LOCK_MUTEX).clear();
+        self.pv_nodes_len.store(0, std::sync::atomic::Ordering::SeqCst);
+        self.root_move_nodes.lock().expect(RIP_COULDN_LOCK_MUTEX).clear();
+        self.zobrist_table.read().unwrap().clear();
+    }
+}
+
 pub struct SearchContext<'a> {
     pub zobrist_table: &'a ZobristTable,
 
@@ -69,15 +101,26 @@ impl UciGame {
         }
     }
 
-    pub fn do_move(&mut self, notation_move: &str) {
-        self.board.do_move(&NotationUtil::get_turn_from_notation(notation_move));
-        
+    /// Plays a move in UCI notation if it is legal in the current position, the board stays
+    /// unchanged otherwise. The move is taken from the generated legal moves, so capture and
+    /// promotion are filled in as the move generator does.
+    pub fn do_move(&mut self, notation_move: &str, move_gen: &MoveGenService, config: &Config) -> Result<Turn, String> {
+        let notation = notation_move.to_ascii_lowercase();
+        // underpromotions are legal even when the search skips them
+        let mut move_config = config.clone();
+        move_config.use_underpromotions = true;
+        let turn = move_gen.legal_moves(&mut self.board, &move_config).into_iter()
+            .find(|turn| turn.to_algebraic() == notation)
+            .ok_or_else(|| format!("illegal move {} in position {}", notation_move, FenService.get_fen(&self.board)))?;
+        self.board.do_move(&turn);
+
         if self.made_moves_str.is_empty() {
             self.made_moves_str.push_str(notation_move);
         } else {
             self.made_moves_str.push(' ');
             self.made_moves_str.push_str(notation_move);
         }
+        Ok(turn)
     }
 
     pub fn white_to_move(&self) -> bool  {
@@ -95,6 +138,15 @@ pub enum TimeMode {
     None,
 }
 
+/// Limit of a search run outside of the UCI loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchLimit {
+    Movetime(u64),
+    Depth(i32),
+    /// Checked after each iteration, the last one may exceed it.
+    Nodes(u64),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct TimeInfo {
     pub wtime: i32,
@@ -120,6 +172,12 @@ pub const BLACK_BISHOP: usize = 9;
 pub const BLACK_QUEEN: usize = 10;
 pub const BLACK_KING: usize = 11;
 
+/// Material key increment of one piece of the given bitboard index.
+#[inline(always)]
+pub const fn material_key_unit(bb_idx: usize) -> u64 {
+    1u64 << (4 * bb_idx)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Turn {
     pub from: u8,
@@ -285,25 +343,31 @@ pub struct MoveInformation {
     pub castle_information: CastleInformation,
     pub hash: u64,
     pub pawn_key: u64,
+    pub material_key: u64,
     pub en_passante: i8,
     pub capture: u8,
     pub moved_piece: u8,
     pub old_pst_mg: i16,
     pub old_pst_eg: i16,
+    pub halfmove_clock: i32,
+    pub castling: Option<CastlingMove>,
 }
 
 impl MoveInformation {
     // Constructor
-    pub fn new(castle_information: CastleInformation, hash: u64, pawn_key: u64, en_passante: i8, capture: u8, moved_piece: u8, old_pst_mg: i16, old_pst_eg: i16) -> Self {
+    pub fn new(castle_information: CastleInformation, hash: u64, pawn_key: u64, material_key: u64, en_passante: i8, capture: u8, moved_piece: u8, old_pst_mg: i16, old_pst_eg: i16, halfmove_clock: i32, castling: Option<CastlingMove>) -> Self {
         MoveInformation {
             castle_information,
             hash,
             pawn_key,
+            material_key,
             en_passante,
             capture,
             moved_piece,
             old_pst_mg,
             old_pst_eg,
+            halfmove_clock,
+            castling,
         }
     }
 }
@@ -315,6 +379,25 @@ pub struct CastleInformation {
     pub white_possible_to_castle_short: bool,
     pub black_possible_to_castle_long: bool,
     pub black_possible_to_castle_short: bool,
+    /// Rook origins in the order white long, white short, black long, black short.
+    pub rook_squares: [u8; 4],
+}
+
+pub const CASTLE_WHITE_LONG: usize = 0;
+pub const CASTLE_WHITE_SHORT: usize = 1;
+pub const CASTLE_BLACK_LONG: usize = 2;
+pub const CASTLE_BLACK_SHORT: usize = 3;
+/// Rook origins of the standard start position, indexed like `CastleInformation::rook_squares`.
+pub const STANDARD_CASTLE_ROOK_SQUARES: [u8; 4] = [0, 7, 56, 63];
+
+/// King and rook squares of a castling move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CastlingMove {
+    pub right: usize,
+    pub king_from: u8,
+    pub king_to: u8,
+    pub rook_from: u8,
+    pub rook_to: u8,
 }
 
 impl CastleInformation {
@@ -332,13 +415,21 @@ pub struct Board {
     pub white_possible_to_castle_short: bool,
     pub black_possible_to_castle_long: bool,
     pub black_possible_to_castle_short: bool,
+    /// Rook origins in the order white long, white short, black long, black short.
+    pub castle_rook_squares: [u8; 4],
+    /// Castling moves are encoded as king captures own rook instead of a two square king move.
+    pub chess960: bool,
     pub field_for_en_passante: i8,  // -1 if no en passant possible, 0..63
     pub white_to_move: bool,
     pub move_count: i32,
+    /// Halfmoves since the last capture or pawn move, for the fifty-move rule.
+    pub halfmove_clock: i32,
     pub game_status: GameStatus,
     pub move_repetition_map: HashMap<u64, i32>,
     pub cached_hash: u64,
     pub pawn_key: u64,
+    /// Piece counts, 4 bits per bitboard index, see `material_key_unit`.
+    pub material_key: u64,
     pub pst_mg: i16,
     pub pst_eg: i16,
     pub _white_king_on_board: bool,
@@ -412,13 +503,17 @@ impl Board {
             white_possible_to_castle_short,
             black_possible_to_castle_long,
             black_possible_to_castle_short,
+            castle_rook_squares: STANDARD_CASTLE_ROOK_SQUARES,
+            chess960: false,
             field_for_en_passante,
             white_to_move,
             move_count,
+            halfmove_clock: 0,
             game_status: GameStatus::Normal,
             move_repetition_map: HashMap::new(),
             cached_hash: 0,
             pawn_key: 0,
+            material_key: 0,
             pst_mg,
             pst_eg,
             _white_king_on_board,
@@ -426,9 +521,17 @@ impl Board {
         };
         board.cached_hash = zobrist::gen_hash(&board);
         board.pawn_key = zobrist::gen_pawn_hash(&board);
+        board.material_key = board.gen_material_key();
         board
     }
 
+    /// Material key of the piece counts on the board.
+    pub fn gen_material_key(&self) -> u64 {
+        self.bitboards.iter().enumerate()
+            .map(|(bb_idx, bb)| bb.count_ones() as u64 * material_key_unit(bb_idx))
+            .sum()
+    }
+
     #[inline(always)]
     pub fn piece_to_bb_idx(piece: u8) -> usize {
         match piece {
@@ -483,9 +586,14 @@ impl Board {
         let old_pst_mg = self.pst_mg;
         let old_pst_eg = self.pst_eg;
         let old_pawn_key = self.pawn_key;
+        let old_material_key = self.material_key;
+        let old_halfmove_clock = self.halfmove_clock;
 
-        let mut actual_capture = turn.capture;
-        if actual_capture == 0 {
+        let castling = self.get_castling(from, to);
+        let castle_rights = self.castle_rights_after_move(from, to);
+
+        let mut actual_capture = if castling.is_some() { 0 } else { turn.capture };
+        if actual_capture == 0 && castling.is_none() {
             let piece_at_to = self.get_piece_at(to);
             if piece_at_to != 0 && (10..=15).contains(&piece_at_to) != self.white_to_move {
                 actual_capture = piece_at_to;
@@ -505,129 +613,71 @@ impl Board {
 
         let moved_bb_idx = Board::piece_to_bb_idx(moved_piece);
 
-        // Incremental PST updates
-        self.pst_mg -= crate::pst::PST_MG[moved_bb_idx][from as usize];
-        self.pst_eg -= crate::pst::PST_EG[moved_bb_idx][from as usize];
-
-        if turn.is_promotion() {
-            let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
-            self.pst_mg += crate::pst::PST_MG[promo_bb_idx][to as usize];
-            self.pst_eg += crate::pst::PST_EG[promo_bb_idx][to as usize];
-        } else {
-            self.pst_mg += crate::pst::PST_MG[moved_bb_idx][to as usize];
-            self.pst_eg += crate::pst::PST_EG[moved_bb_idx][to as usize];
-        }
-
-        // Update mailbox
-        self.mailbox[from as usize] = 0;
-        if turn.is_promotion() {
-            self.mailbox[to as usize] = turn.promotion;
+        if let Some(castling) = castling {
+            self.move_castling_pieces(moved_piece, castling);
         } else {
-            self.mailbox[to as usize] = moved_piece;
-        }
+            // Incremental PST updates
+            self.pst_mg -= crate::pst::PST_MG[moved_bb_idx][from as usize];
+            self.pst_eg -= crate::pst::PST_EG[moved_bb_idx][from as usize];
+
+            if turn.is_promotion() {
+                let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
+                self.pst_mg += crate::pst::PST_MG[promo_bb_idx][to as usize];
+                self.pst_eg += crate::pst::PST_EG[promo_bb_idx][to as usize];
+            } else {
+                self.pst_mg += crate::pst::PST_MG[moved_bb_idx][to as usize];
+                self.pst_eg += crate::pst::PST_EG[moved_bb_idx][to as usize];
+            }
 
-        // Move the piece
-        if turn.is_promotion() {
-            self.bitboards[moved_bb_idx] ^= from_mask;
-            let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
-            self.bitboards[promo_bb_idx] ^= to_mask;
-        } else {
-            self.bitboards[moved_bb_idx] ^= from_mask | to_mask;
-        }
+            // Update mailbox
+            self.mailbox[from as usize] = 0;
+            if turn.is_promotion() {
+                self.mailbox[to as usize] = turn.promotion;
+            } else {
+                self.mailbox[to as usize] = moved_piece;
+            }
 
-        // Handle capture
-        if actual_capture != 0 {
-            // Check if it was an en passant capture
-            let is_en_passant = (moved_piece == 10 || moved_piece == 20) && (to as i8 == old_field_for_en_passante);
-            let capture_bb_idx = Board::piece_to_bb_idx(actual_capture);
-            if is_en_passant {
-                let victim_sq = if self.white_to_move { to - 8 } else { to + 8 };
-                self.pst_mg -= crate::pst::PST_MG[capture_bb_idx][victim_sq as usize];
-                self.pst_eg -= crate::pst::PST_EG[capture_bb_idx][victim_sq as usize];
-                
-                if self.white_to_move {
-                    self.bitboards[BLACK_PAWN] &= !(1u64 << victim_sq);
-                    self.mailbox[victim_sq as usize] = 0;
-                } else {
-                    self.bitboards[WHITE_PAWN] &= !(1u64 << victim_sq);
-                    self.mailbox[victim_sq as usize] = 0;
-                }
+            // Move the piece
+            if turn.is_promotion() {
+                self.bitboards[moved_bb_idx] ^= from_mask;
+                let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
+                self.bitboards[promo_bb_idx] ^= to_mask;
             } else {
-                self.pst_mg -= crate::pst::PST_MG[capture_bb_idx][to as usize];
-                self.pst_eg -= crate::pst::PST_EG[capture_bb_idx][to as usize];
-                self.bitboards[capture_bb_idx] &= !to_mask;
+                self.bitboards[moved_bb_idx] ^= from_mask | to_mask;
             }
-        }
 
-        // Handling castling for white and black (rook movements)
-        if moved_piece == 15 || moved_piece == 25 {
-            let is_castling = (to as i8 - from as i8).abs() == 2;
-            if is_castling {
-                match to {
-                    6 => { // White short
-                        self.pst_mg -= crate::pst::PST_MG[WHITE_ROOK][7];
-                        self.pst_eg -= crate::pst::PST_EG[WHITE_ROOK][7];
-                        self.pst_mg += crate::pst::PST_MG[WHITE_ROOK][5];
-                        self.pst_eg += crate::pst::PST_EG[WHITE_ROOK][5];
-                        self.bitboards[WHITE_ROOK] ^= (1u64 << 7) | (1u64 << 5);
-                        self.mailbox[7] = 0;
-                        self.mailbox[5] = 11;
-                    }
-                    2 => { // White long
-                        self.pst_mg -= crate::pst::PST_MG[WHITE_ROOK][0];
-                        self.pst_eg -= crate::pst::PST_EG[WHITE_ROOK][0];
-                        self.pst_mg += crate::pst::PST_MG[WHITE_ROOK][3];
-                        self.pst_eg += crate::pst::PST_EG[WHITE_ROOK][3];
-                        self.bitboards[WHITE_ROOK] ^= (1u64 << 0) | (1u64 << 3);
-                        self.mailbox[0] = 0;
-                        self.mailbox[3] = 11;
-                    }
-                    62 => { // Black short
-                        self.pst_mg -= crate::pst::PST_MG[BLACK_ROOK][63];
-                        self.pst_eg -= crate::pst::PST_EG[BLACK_ROOK][63];
-                        self.pst_mg += crate::pst::PST_MG[BLACK_ROOK][61];
-                        self.pst_eg += crate::pst::PST_EG[BLACK_ROOK][61];
-                        self.bitboards[BLACK_ROOK] ^= (1u64 << 63) | (1u64 << 61);
-                        self.mailbox[63] = 0;
-                        self.mailbox[61] = 21;
-                    }
-                    58 => { // Black long
-                        self.pst_mg -= crate::pst::PST_MG[BLACK_ROOK][56];
-                        self.pst_eg -= crate::pst::PST_EG[BLACK_ROOK][56];
-                        self.pst_mg += crate::pst::PST_MG[BLACK_ROOK][59];
-                        self.pst_eg += crate::pst::PST_EG[BLACK_ROOK][59];
-                        self.bitboards[BLACK_ROOK] ^= (1u64 << 56) | (1u64 << 59);
-                        self.mailbox[56] = 0;
-                        self.mailbox[59] = 21;
+            // Handle capture
+            if actual_capture != 0 {
+                // Check if it was an en passant capture
+                let is_en_passant = (moved_piece == 10 || moved_piece == 20) && (to as i8 == old_field_for_en_passante);
+                let capture_bb_idx = Board::piece_to_bb_idx(actual_capture);
+                if is_en_passant {
+                    let victim_sq = if self.white_to_move { to - 8 } else { to + 8 };
+                    self.pst_mg -= crate::pst::PST_MG[capture_bb_idx][victim_sq as usize];
+                    self.pst_eg -= crate::pst::PST_EG[capture_bb_idx][victim_sq as usize];
+                
+                    if self.white_to_move {
+                        self.bitboards[BLACK_PAWN] &= !(1u64 << victim_sq);
+                        self.mailbox[victim_sq as usize] = 0;
+                    } else {
+                        self.bitboards[WHITE_PAWN] &= !(1u64 << victim_sq);
+                        self.mailbox[victim_sq as usize] = 0;
                     }
-                    _ => {}
+                } else {
+                    self.pst_mg -= crate::pst::PST_MG[capture_bb_idx][to as usize];
+                    self.pst_eg -= crate::pst::PST_EG[capture_bb_idx][to as usize];
+                    self.bitboards[capture_bb_idx] &= !to_mask;
                 }
             }
         }
 
         // Update castling rights
-        match from {
-            56 => self.black_possible_to_castle_long = false,
-            63 => self.black_possible_to_castle_short = false,
-            60 => {
-                self.black_possible_to_castle_long = false;
-                self.black_possible_to_castle_short = false;
-            }
-            0 => self.white_possible_to_castle_long = false,
-            7 => self.white_possible_to_castle_short = false,
-            4 => {
-                self.white_possible_to_castle_long = false;
-                self.white_possible_to_castle_short = false;
-            }
-            _ => {}
-        }
-        match to {
-            56 => self.black_possible_to_castle_long = false,
-            63 => self.black_possible_to_castle_short = false,
-            0 => self.white_possible_to_castle_long = false,
-            7 => self.white_possible_to_castle_short = false,
-            _ => {}
-        }
+        [
+            self.white_possible_to_castle_long,
+            self.white_possible_to_castle_short,
+            self.black_possible_to_castle_long,
+            self.black_possible_to_castle_short,
+        ] = castle_rights;
 
         // Handling en passante target square
         self.field_for_en_passante = -1;
@@ -664,6 +714,16 @@ impl Board {
                 self.game_status = GameStatus::Draw;
             }
         }
+
+        // Fifty-move rule
+        if moved_piece == 10 || moved_piece == 20 || actual_capture != 0 {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+            if self.halfmove_clock >= 100 {
+                self.game_status = GameStatus::Draw;
+            }
+        }
         // Update pawn key
         let is_pawn = moved_piece == 10 || moved_piece == 20;
         if is_pawn {
@@ -683,7 +743,16 @@ impl Board {
             self.pawn_key ^= zobrist::get_zobrist_val(capture_sq as usize, capture_bb_idx);
         }
 
-        MoveInformation::new(old_castle_information, old_cached_hash, old_pawn_key, old_field_for_en_passante, actual_capture, moved_piece, old_pst_mg, old_pst_eg)
+        // Update material key
+        if actual_capture != 0 {
+            self.material_key -= material_key_unit(Board::piece_to_bb_idx(actual_capture));
+        }
+        if turn.is_promotion() {
+            self.material_key -= material_key_unit(moved_bb_idx);
+            self.material_key += material_key_unit(Board::piece_to_bb_idx(turn.promotion));
+        }
+
+        MoveInformation::new(old_castle_information, old_cached_hash, old_pawn_key, old_material_key, old_field_for_en_passante, actual_capture, moved_piece, old_pst_mg, old_pst_eg, old_halfmove_clock, castling)
     }
 
 
@@ -691,8 +760,10 @@ impl Board {
         let new_hash = self.cached_hash;
         self.cached_hash = move_information.hash;
         self.pawn_key = move_information.pawn_key;
+        self.material_key = move_information.material_key;
         self.pst_mg = move_information.old_pst_mg;
         self.pst_eg = move_information.old_pst_eg;
+        self.halfmove_clock = move_information.halfmove_clock;
 
         let from = turn.from;
         let to = turn.to;
@@ -716,65 +787,39 @@ impl Board {
 
         let moved_bb_idx = Board::piece_to_bb_idx(moved_piece);
 
-        // Update mailbox
-        self.mailbox[from as usize] = moved_piece;
-        self.mailbox[to as usize] = 0;
-
-        // Undo move
-        if turn.is_promotion() {
-            let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
-            self.bitboards[promo_bb_idx] ^= to_mask;
-            self.bitboards[moved_bb_idx] ^= from_mask;
+        if let Some(castling) = move_information.castling {
+            self.unmove_castling_pieces(moved_piece, castling);
         } else {
-            self.bitboards[moved_bb_idx] ^= from_mask | to_mask;
-        }
-
-        // Handle capture undo
-        if move_information.capture != 0 {
-            let is_en_passant = (moved_piece == 10 || moved_piece == 20) && (to as i8 == move_information.en_passante);
-            if is_en_passant {
-                if !self.white_to_move { // White played the EP capture
-                    let victim_sq = to - 8;
-                    self.bitboards[BLACK_PAWN] |= 1u64 << victim_sq;
-                    self.mailbox[victim_sq as usize] = move_information.capture;
-                } else { // Black played the EP capture
-                    let victim_sq = to + 8;
-                    self.bitboards[WHITE_PAWN] |= 1u64 << victim_sq;
-                    self.mailbox[victim_sq as usize] = move_information.capture;
-                }
+            // Update mailbox
+            self.mailbox[from as usize] = moved_piece;
+            self.mailbox[to as usize] = 0;
+
+            // Undo move
+            if turn.is_promotion() {
+                let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
+                self.bitboards[promo_bb_idx] ^= to_mask;
+                self.bitboards[moved_bb_idx] ^= from_mask;
             } else {
-                let capture_bb_idx = Board::piece_to_bb_idx(move_information.capture);
-                self.bitboards[capture_bb_idx] |= to_mask;
-                self.mailbox[to as usize] = move_information.capture;
+                self.bitboards[moved_bb_idx] ^= from_mask | to_mask;
             }
-        }
 
-        // Handle castling undo (rook movements)
-        if moved_piece == 15 || moved_piece == 25 {
-            let is_castling = (to as i8 - from as i8).abs() == 2;
-            if is_castling {
-                match to {
-                    6 => { // White short
-                        self.bitboards[WHITE_ROOK] ^= (1u64 << 7) | (1u64 << 5);
-                        self.mailbox[7] = 11;
-                        self.mailbox[5] = 0;
+            // Handle capture undo
+            if move_information.capture != 0 {
+                let is_en_passant = (moved_piece == 10 || moved_piece == 20) && (to as i8 == move_information.en_passante);
+                if is_en_passant {
+                    if !self.white_to_move { // White played the EP capture
+                        let victim_sq = to - 8;
+                        self.bitboards[BLACK_PAWN] |= 1u64 << victim_sq;
+                        self.mailbox[victim_sq as usize] = move_information.capture;
+                    } else { // Black played the EP capture
+                        let victim_sq = to + 8;
+                        self.bitboards[WHITE_PAWN] |= 1u64 << victim_sq;
+                        self.mailbox[victim_sq as usize] = move_information.capture;
                     }
-                    2 => { // White long
-                        self.bitboards[WHITE_ROOK] ^= (1u64 << 0) | (1u64 << 3);
-                        self.mailbox[0] = 11;
-                        self.mailbox[3] = 0;
-                    }
-                    62 => { // Black short
-                        self.bitboards[BLACK_ROOK] ^= (1u64 << 63) | (1u64 << 61);
-                        self.mailbox[63] = 21;
-                        self.mailbox[61] = 0;
-                    }
-                    58 => { // Black long
-                        self.bitboards[BLACK_ROOK] ^= (1u64 << 56) | (1u64 << 59);
-                        self.mailbox[56] = 21;
-                        self.mailbox[59] = 0;
-                    }
-                    _ => {}
+                } else {
+                    let capture_bb_idx = Board::piece_to_bb_idx(move_information.capture);
+                    self.bitboards[capture_bb_idx] |= to_mask;
+                    self.mailbox[to as usize] = move_information.capture;
                 }
             }
         }
@@ -784,6 +829,7 @@ impl Board {
         self.white_possible_to_castle_short = castle_information.white_possible_to_castle_short;
         self.black_possible_to_castle_long = castle_information.black_possible_to_castle_long;
         self.black_possible_to_castle_short = castle_information.black_possible_to_castle_short;
+        self.castle_rook_squares = castle_information.rook_squares;
         self.field_for_en_passante = move_information.en_passante;
 
         // Decrement move count if it was white's move (meaning we are undoing black's move, so white_to_move will become true)
@@ -816,7 +862,96 @@ impl Board {
             white_possible_to_castle_short: self.white_possible_to_castle_short,
             black_possible_to_castle_long: self.black_possible_to_castle_long,
             black_possible_to_castle_short: self.black_possible_to_castle_short,
+            rook_squares: self.castle_rook_squares,
+        }
+    }
+
+    /// Returns the castling move if the king on `from` castles with a move to `to`. Standard chess encodes
+    /// castling as a two square king move, Chess960 as king captures own rook.
+    pub fn get_castling(&self, from: u8, to: u8) -> Option<CastlingMove> {
+        let king = self.get_piece_at(from);
+        if king != 15 && king != 25 {
+            return None;
+        }
+        let white = king == 15;
+        let base = if white { 0 } else { 56 };
+        if from / 8 != base / 8 || to / 8 != base / 8 {
+            return None;
+        }
+        let short = to > from;
+        let right = match (white, short) {
+            (true, false) => CASTLE_WHITE_LONG,
+            (true, true) => CASTLE_WHITE_SHORT,
+            (false, false) => CASTLE_BLACK_LONG,
+            (false, true) => CASTLE_BLACK_SHORT,
+        };
+        let rook_from = if self.chess960 {
+            let own_rook = if white { 11 } else { 21 };
+            if self.get_piece_at(to) != own_rook || self.castle_rook_squares[right] != to {
+                return None;
+            }
+            to
+        } else {
+            if (to as i8 - from as i8).abs() != 2 {
+                return None;
+            }
+            self.castle_rook_squares[right]
+        };
+        Some(CastlingMove {
+            right,
+            king_from: from,
+            king_to: base + if short { 6 } else { 2 },
+            rook_from,
+            rook_to: base + if short { 5 } else { 3 },
+        })
+    }
+
+    /// Castling rights after a move from `from` to `to`, in the order of `CastleInformation::rook_squares`.
+    pub fn castle_rights_after_move(&self, from: u8, to: u8) -> [bool; 4] {
+        let mut rights = [
+            self.white_possible_to_castle_long,
+            self.white_possible_to_castle_short,
+            self.black_possible_to_castle_long,
+            self.black_possible_to_castle_short,
+        ];
+        match self.get_piece_at(from) {
+            15 => { rights[CASTLE_WHITE_LONG] = false; rights[CASTLE_WHITE_SHORT] = false; }
+            25 => { rights[CASTLE_BLACK_LONG] = false; rights[CASTLE_BLACK_SHORT] = false; }
+            _ => {}
+        }
+        for (right, &rook_square) in self.castle_rook_squares.iter().enumerate() {
+            if from == rook_square || to == rook_square {
+                rights[right] = false;
+            }
         }
+        rights
+    }
+
+    /// Places king and rook of a castling move, king and rook targets may overlap their origins.
+    fn move_castling_pieces(&mut self, king: u8, castling: CastlingMove) {
+        let rook = if king == 15 { 11 } else { 21 };
+        let king_bb_idx = Board::piece_to_bb_idx(king);
+        let rook_bb_idx = Board::piece_to_bb_idx(rook);
+        self.pst_mg += crate::pst::PST_MG[king_bb_idx][castling.king_to as usize] - crate::pst::PST_MG[king_bb_idx][castling.king_from as usize]
+            + crate::pst::PST_MG[rook_bb_idx][castling.rook_to as usize] - crate::pst::PST_MG[rook_bb_idx][castling.rook_from as usize];
+        self.pst_eg += crate::pst::PST_EG[king_bb_idx][castling.king_to as usize] - crate::pst::PST_EG[king_bb_idx][castling.king_from as usize]
+            + crate::pst::PST_EG[rook_bb_idx][castling.rook_to as usize] - crate::pst::PST_EG[rook_bb_idx][castling.rook_from as usize];
+        self.bitboards[king_bb_idx] ^= (1u64 << castling.king_from) ^ (1u64 << castling.king_to);
+        self.bitboards[rook_bb_idx] ^= (1u64 << castling.rook_from) ^ (1u64 << castling.rook_to);
+        self.mailbox[castling.king_from as usize] = 0;
+        self.mailbox[castling.rook_from as usize] = 0;
+        self.mailbox[castling.king_to as usize] = king;
+        self.mailbox[castling.rook_to as usize] = rook;
+    }
+
+    fn unmove_castling_pieces(&mut self, king: u8, castling: CastlingMove) {
+        let rook = if king == 15 { 11 } else { 21 };
+        self.bitboards[Board::piece_to_bb_idx(king)] ^= (1u64 << castling.king_from) ^ (1u64 << castling.king_to);
+        self.bitboards[Board::piece_to_bb_idx(rook)] ^= (1u64 << castling.rook_from) ^ (1u64 << castling.rook_to);
+        self.mailbox[castling.king_to as usize] = 0;
+        self.mailbox[castling.rook_to as usize] = 0;
+        self.mailbox[castling.king_from as usize] = king;
+        self.mailbox[castling.rook_from as usize] = rook;
     }
 
     /// gives an indicator wich depth to the search can be applied. 100 is maximum
@@ -860,12 +995,16 @@ impl PartialEq for Board {
             self.white_possible_to_castle_short == other.white_possible_to_castle_short &&
             self.black_possible_to_castle_long == other.black_possible_to_castle_long &&
             self.black_possible_to_castle_short == other.black_possible_to_castle_short &&
+            self.castle_rook_squares == other.castle_rook_squares &&
+            self.chess960 == other.chess960 &&
             self.field_for_en_passante == other.field_for_en_passante &&
             self.white_to_move == other.white_to_move &&
             self.move_count == other.move_count &&
+            self.halfmove_clock == other.halfmove_clock &&
             self.game_status == other.game_status &&
             self.bitboards == other.bitboards &&
             self.pawn_key == other.pawn_key &&
+            self.material_key == other.material_key &&
             self.move_repetition_map == other.move_repetition_map
     }
 }
@@ -880,6 +1019,7 @@ pub struct Stats {
     pub eval_nodes: usize,
     pub calc_time_ms: usize,
     pub zobrist_hit: usize,
+    pub tb_hits: usize,
     pub cuts: i32,
     pub capture_share: i32,
     pub nodes_per_ms: i32,
@@ -897,6 +1037,7 @@ impl Stats {
             eval_nodes: 0,
             calc_time_ms: 0,
             zobrist_hit: 0,
+            tb_hits: 0,
             cuts: 0,
             capture_share: 0,
             nodes_per_ms: 0,
@@ -950,6 +1091,7 @@ impl Stats {
         self.eval_nodes = 0;
         self.calc_time_ms = 0;
         self.zobrist_hit = 0;
+        self.tb_hits = 0;
         self.logging = Vec::default();
     }
 }
@@ -964,6 +1106,10 @@ pub struct SearchResult {
     pub is_pv_search_result: bool,
     pub best_score: i16,
     pub second_best_score: i16,
+    /// Nodes spent per root move, best move first and the rest by descending effort.
+    pub root_move_nodes: Vec<(Turn, u64)>,
+    /// White-relative score per root move; exact only for multi_pv > 1, otherwise bounds.
+    pub root_move_scores: Vec<(Turn, i16)>,
 }
 
 #[derive(Debug, Clone)]
@@ -985,6 +1131,8 @@ impl SearchResult {
             is_pv_search_result: false,
             best_score: 0,
             second_best_score: 0,
+            root_move_nodes: Vec::new(),
+            root_move_scores: Vec::new(),
         }
     }
 
@@ -1041,6 +1189,25 @@ impl SearchResult {
         });
     }
 
+    /// Percentage of root nodes spent on the best move, None if no node counts are present.
+    pub fn get_best_move_node_share(&self) -> Option<u64> {
+        let total: u64 = self.root_move_nodes.iter().map(|(_, nodes)| nodes).sum();
+        let best_move = self.variants.first().and_then(|v| v.best_move)?;
+        let best_nodes = self.root_move_nodes.iter().find(|(turn, _)| *turn == best_move).map(|(_, nodes)| *nodes)?;
+        if total == 0 {
+            return None;
+        }
+        Some(best_nodes * 100 / total)
+    }
+
+    pub fn get_root_move_nodes_str(&self) -> String {
+        let total: u64 = self.root_move_nodes.iter().map(|(_, nodes)| nodes).sum::<u64>().max(1);
+        self.root_move_nodes.iter()
+            .map(|(turn, nodes)| format!("{} {} ({}%)", turn.to_algebraic(), nodes, nodes * 100 / total))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
     pub fn get_best_move_algebraic(&self) -> String {
         self.variants.first()
             .and_then(|variant| variant.best_move.as_ref())
@@ -1080,8 +1247,9 @@ impl SearchResult {
 #[cfg(test)]
 mod tests {
     use crate::notation_util::NotationUtil;
+    use crate::config::Config;
     use crate::service::Service;
-    use super::UciGame;
+    use super::{GameStatus, UciGame};
 
 
     #[test]
@@ -1346,6 +1514,28 @@ mod tests {
         assert_eq!(board.move_repetition_map.len(), 0);
     }
 
+    #[test]
+    fn fifty_move_rule_test() {
+        let fen_service = Service::new().fen;
+        let mut board = fen_service.set_fen("4k3/4p3/8/8/8/8/4P3/4K1N1 w - - 99 80");
+        assert_eq!(99, board.halfmove_clock);
+
+        let turn = &NotationUtil::get_turn_from_notation("g1f3");
+        let mi = board.do_move(turn);
+        assert_eq!(100, board.halfmove_clock);
+        assert!(board.game_status == GameStatus::Draw);
+        board.undo_move(turn, mi);
+        assert_eq!(99, board.halfmove_clock);
+        assert!(board.game_status == GameStatus::Normal);
+
+        let turn = &NotationUtil::get_turn_from_notation("e2e4");
+        let mi = board.do_move(turn);
+        assert_eq!(0, board.halfmove_clock);
+        assert!(board.game_status == GameStatus::Normal);
+        board.undo_move(turn, mi);
+        assert_eq!(99, board.halfmove_clock);
+    }
+
     #[test]
     fn zobrist_castling_do_undo_move_hash_test() {
         let fen_service = Service::new().fen;
@@ -1365,6 +1555,46 @@ mod tests {
         assert_eq!(org_hash, crate::zobrist::gen_hash(&board));
     }
 
+    #[test]
+    fn chess960_castling_test() {
+        let fen_service = Service::new().fen;
+        let cases = [
+            // short castling, king and rook pass each other
+            ("rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1", "b1h1", "rk5r/8/8/8/8/8/8/R4RK1 b kq - 1 1"),
+            // long castling, king moves to the right
+            ("rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1", "b1a1", "rk5r/8/8/8/8/8/8/2KR3R b kq - 1 1"),
+            // king already on its target square
+            ("1r4kr/8/8/8/8/8/8/1R4KR b HBhb - 0 1", "g8h8", "1r3rk1/8/8/8/8/8/8/1R4KR w KQ - 1 2"),
+            // rook already on its target square
+            ("4k3/8/8/8/8/8/8/3RK3 w D - 0 1", "e1d1", "4k3/8/8/8/8/8/8/2KR4 b - - 1 1"),
+        ];
+        for (fen, notation, expected_fen) in cases {
+            let mut board = fen_service.set_fen(fen);
+            board.chess960 = true;
+            let board_copy = board.clone();
+            let turn = &NotationUtil::get_turn_from_notation(notation);
+            assert!(board.get_castling(turn.from, turn.to).is_some());
+
+            let mi = board.do_move(turn);
+            assert_eq!(expected_fen, fen_service.get_fen(&board));
+            assert_eq!(crate::zobrist::gen_hash(&board), board.cached_hash);
+
+            board.undo_move(turn, mi);
+            assert_eq!(board_copy, board);
+            assert_eq!(board_copy.mailbox, board.mailbox);
+            assert_eq!(crate::zobrist::gen_hash(&board), board.cached_hash);
+        }
+
+        // Shredder-FEN and X-FEN describe the same rights
+        let shredder = fen_service.set_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1");
+        assert_eq!(crate::model::INIT_BOARD_FEN, fen_service.get_fen(&shredder));
+
+        // an inner rook is written with its file
+        let board = fen_service.set_fen("1r2k1rr/8/8/8/8/8/8/1R2K1RR w Gg - 0 1");
+        assert_eq!([0, 6, 56, 62], board.castle_rook_squares);
+        assert_eq!("1r2k1rr/8/8/8/8/8/8/1R2K1RR w Gg - 0 1", fen_service.get_fen(&board));
+    }
+
     #[test]
     fn zobrist_en_passant_do_undo_move_hash_test() {
         let fen_service = Service::new().fen;
@@ -1398,6 +1628,7 @@ mod tests {
     #[test]
     fn uci_game_test() {
         let service = Service::new();
+        let config = Config::for_tests();
 
         let mut game = UciGame::new(service.fen.set_init_board());
 
@@ -1405,31 +1636,55 @@ mod tests {
         assert_eq!("", game.made_moves_str);
         assert_eq!(1, game.board.move_count);
 
-        game.do_move("e2e4");
+        game.do_move("e2e4", &service.move_gen, &config).unwrap();
         assert_eq!(false, game.white_to_move());
         assert_eq!("e2e4", game.made_moves_str);
         
-        game.do_move("e7e5");
+        game.do_move("e7e5", &service.move_gen, &config).unwrap();
         assert_eq!(true, game.white_to_move());
         assert_eq!(2, game.board.move_count);
 
-        game.do_move("d2d3");
+        game.do_move("d2d3", &service.move_gen, &config).unwrap();
         assert_eq!(false, game.white_to_move());
         assert_eq!(2, game.board.move_count);
         assert_eq!("e2e4 e7e5 d2d3", game.made_moves_str);
     }
 
+    #[test]
+    fn uci_game_rejects_illegal_moves_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let mut game = UciGame::new(service.fen.set_init_board());
+        game.do_move("e2e4", &service.move_gen, &config).unwrap();
+
+        // a stale move of white and garbled moves leave the position unchanged
+        let fen = service.fen.get_fen(&game.board);
+        assert!(game.do_move("d2d4", &service.move_gen, &config).is_err());
+        assert!(game.do_move("e7e5x", &service.move_gen, &config).is_err());
+        assert!(game.do_move("", &service.move_gen, &config).is_err());
+        assert_eq!(fen, service.fen.get_fen(&game.board));
+        assert_eq!("e2e4", game.made_moves_str);
+
+        let mut game = UciGame::new(service.fen.set_fen("r3k3/1P6/8/8/8/8/7r/4K3 w - - 0 1"));
+        assert!(game.do_move("e1e2", &service.move_gen, &config).is_err());
+        let mut config = config.clone();
+        config.use_underpromotions = false;
+        let turn = game.do_move("b7a8N", &service.move_gen, &config).unwrap();
+        assert_eq!((21, 12), (turn.capture, game.board.get_piece_at(56)));
+    }
+
     #[test]
     fn undo_capture_move_test_white() {
-        let fen_service = Service::new().fen;
+        let service = Service::new();
+        let config = Config::for_tests();
 
-        let board = fen_service.set_init_board();
+        let board = service.fen.set_init_board();
         let mut game = UciGame::new(board);
-        game.do_move("e2e4");
-        game.do_move("d7d5");
-        game.do_move("b1c3");
-        game.do_move("d5d4");
-        game.do_move("e4e5");
+        game.do_move("e2e4", &service.move_gen, &config).unwrap();
+        game.do_move("d7d5", &service.move_gen, &config).unwrap();
+        game.do_move("b1c3", &service.move_gen, &config).unwrap();
+        game.do_move("d5d4", &service.move_gen, &config).unwrap();
+        game.do_move("e4e5", &service.move_gen, &config).unwrap();
         
         let mut capture_move = NotationUtil::get_turn_from_notation("d4c3");
         capture_move.capture = 12;
@@ -4,7 +4,8 @@ use once_cell::sync::Lazy;
 use crate::zobrist;
 use crate::config::Config;
 use crate::model::{
-    Board, GameStatus, Stats, Turn, SearchContext,
+    Board, CastlingMove, GameStatus, Stats, Turn, SearchContext,
+    CASTLE_WHITE_LONG, CASTLE_WHITE_SHORT, CASTLE_BLACK_LONG, CASTLE_BLACK_SHORT,
     WHITE_PAWN, WHITE_ROOK, WHITE_KNIGHT, WHITE_BISHOP, WHITE_QUEEN, WHITE_KING,
     BLACK_PAWN, BLACK_ROOK, BLACK_KNIGHT, BLACK_BISHOP, BLACK_QUEEN, BLACK_KING,
 };
@@ -117,6 +118,29 @@ impl MoveGenService {
         self.get_valid_moves_from_move_list(&move_list, board, stats, config, false, context, do_move_ordering, force_skip_validation, valid_moves);
     }
 
+    /// Legal moves of a position outside of a search, in generation order.
+    pub fn legal_moves(&self, board: &mut Board, config: &Config) -> Vec<Turn> {
+        let zobrist_table = zobrist::ZobristTable::with_capacity(1);
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let pv_nodes = std::sync::Mutex::new(std::collections::HashMap::new());
+        let history_table = [[0u32; 64]; 64];
+        let context = SearchContext {
+            zobrist_table: &zobrist_table,
+            stop_flag: &stop_flag,
+            pv_nodes: &pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &history_table,
+            counter_move: None,
+            start_time: std::time::Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+        let mut move_list = crate::model::MoveList::new();
+        self.generate_valid_moves_list(board, &mut Stats::new(), config, &context, false, false, &mut move_list);
+        move_list.as_slice().to_vec()
+    }
+
     fn get_valid_moves_from_move_list(
         &self,
         move_list: &crate::model::MoveRawList,
@@ -130,7 +154,6 @@ impl MoveGenService {
         valid_moves: &mut crate::model::MoveList,
     ) {
         let white_turn = board.white_to_move;
-        let king_value = if white_turn { 15 } else { 25 };
 
         // get pv node
         let mut pv_node = None;
@@ -158,7 +181,8 @@ impl MoveGenService {
             let idx0 = move_list.moves[i];
             let idx1 = move_list.moves[i + 1];
 
-            let capture = board.get_piece_at(idx1);
+            let castling = if only_captures { None } else { board.get_castling(idx0, idx1) };
+            let capture = if castling.is_some() { 0 } else { board.get_piece_at(idx1) };
             if capture == 0 && only_captures {
                 continue;
             }
@@ -193,8 +217,7 @@ impl MoveGenService {
             }
 
             // Check for castling
-            let moved_piece = board.get_piece_at(idx0);
-            if !only_captures && (moved_piece == king_value && (idx1 as i32 - idx0 as i32).abs() == 2) && !self.is_valid_castling(board, white_turn, idx1 as i32) {
+            if let Some(castling) = castling && !self.is_valid_castling(board, white_turn, castling) {
                 continue;
             }
 
@@ -428,38 +451,66 @@ impl MoveGenService {
         }
     }
 
-    fn is_valid_castling(&self, board: &Board, white_turn: bool, target: i32) -> bool {
-        let check_squares: &[u8] = if white_turn {
-            if target == 6 { &[5, 6] } else { &[3, 2] }
-        } else if target == 62 { &[61, 62] } else { &[59, 58] };
-
-        if self.is_in_check(board) {
+    fn is_valid_castling(&self, board: &Board, white_turn: bool, castling: CastlingMove) -> bool {
+        let rights = [
+            board.white_possible_to_castle_long,
+            board.white_possible_to_castle_short,
+            board.black_possible_to_castle_long,
+            board.black_possible_to_castle_short,
+        ];
+        if !rights[castling.right] || self.is_in_check(board) {
             return false;
         }
 
-        for &square in check_squares {
+        // squares the king passes, including its target
+        let (low, high) = if castling.king_to > castling.king_from {
+            (castling.king_from + 1, castling.king_to)
+        } else {
+            (castling.king_to, castling.king_from.saturating_sub(1))
+        };
+        for square in low..=high {
+            if square == castling.king_from {
+                continue;
+            }
             let attackers = self.get_attackers_mask(board, white_turn, square, board.occupied);
             if attackers != 0 {
                 return false;
             }
         }
+        true
+    }
 
-        if white_turn {
-            if target == 6 && !board.white_possible_to_castle_short {
-                return false;
-            }
-            if target == 2 && !board.white_possible_to_castle_long {
-                return false;
-            }
+    /// Castling moves of the king on `king_sq`, encoded as two square king move or as king captures rook in Chess960.
+    fn push_castling_moves(&self, board: &Board, white: bool, king_sq: u8, moves: &mut crate::model::MoveRawList) {
+        let (base, rights) = if white {
+            (0u8, [(CASTLE_WHITE_LONG, board.white_possible_to_castle_long), (CASTLE_WHITE_SHORT, board.white_possible_to_castle_short)])
         } else {
-            if target == 62 && !board.black_possible_to_castle_short {
-                return false;
+            (56u8, [(CASTLE_BLACK_LONG, board.black_possible_to_castle_long), (CASTLE_BLACK_SHORT, board.black_possible_to_castle_short)])
+        };
+        if king_sq / 8 != base / 8 {
+            return;
+        }
+        let rook_bb = board.bitboards[if white { WHITE_ROOK } else { BLACK_ROOK }];
+        for (right, possible) in rights {
+            let rook_from = board.castle_rook_squares[right];
+            if !possible || rook_bb & (1u64 << rook_from) == 0 {
+                continue;
             }
-            if target == 58 && !board.black_possible_to_castle_long {
-                return false;
+            let short = right == CASTLE_WHITE_SHORT || right == CASTLE_BLACK_SHORT;
+            let king_to = base + if short { 6 } else { 2 };
+            let rook_to = base + if short { 5 } else { 3 };
+
+            // every square king and rook travel over must be empty, apart from king and rook themselves
+            let span = |a: u8, b: u8| -> u64 {
+                let (lo, hi) = (a.min(b), a.max(b));
+                (u64::MAX >> (63 - hi)) & (u64::MAX << lo)
+            };
+            let path = (span(king_sq, king_to) | span(rook_from, rook_to)) & !(1u64 << king_sq) & !(1u64 << rook_from);
+            if board.occupied & path == 0 {
+                moves.push(king_sq as i32);
+                moves.push(if board.chess960 { rook_from as i32 } else { king_to as i32 });
             }
         }
-        true
     }
 
     fn get_promotion_move(&self, board: &Board, white_turn: bool, idx0: i32, idx1: i32) -> Option<Turn> {
@@ -650,39 +701,7 @@ impl MoveGenService {
                     }
 
                     if !only_captures {
-                        if white {
-                            if sq == 4 {
-                                if board.white_possible_to_castle_short
-                                    && (occupied & ((1u64 << 5) | (1u64 << 6))) == 0
-                                    && (board.bitboards[WHITE_ROOK] & (1u64 << 7)) != 0
-                                {
-                                    moves.push(4);
-                                    moves.push(6);
-                                }
-                                if board.white_possible_to_castle_long
-                                    && (occupied & ((1u64 << 1) | (1u64 << 2) | (1u64 << 3))) == 0
-                                    && (board.bitboards[WHITE_ROOK] & (1u64 << 0)) != 0
-                                {
-                                    moves.push(4);
-                                    moves.push(2);
-                                }
-                            }
-                        } else if sq == 60 {
-                            if board.black_possible_to_castle_short
-                                && (occupied & ((1u64 << 61) | (1u64 << 62))) == 0
-                                && (board.bitboards[BLACK_ROOK] & (1u64 << 63)) != 0
-                            {
-                                moves.push(60);
-                                moves.push(62);
-                            }
-                            if board.black_possible_to_castle_long
-                                && (occupied & ((1u64 << 57) | (1u64 << 58) | (1u64 << 59))) == 0
-                                && (board.bitboards[BLACK_ROOK] & (1u64 << 56)) != 0
-                            {
-                                moves.push(60);
-                                moves.push(58);
-                            }
-                        }
+                        self.push_castling_moves(board, white, sq, moves);
                     }
                 }
                 _ => {}
@@ -1585,6 +1604,46 @@ mod tests {
         assert_eq!(nodes, 4085603);
     }
 
+    #[test]
+    fn perft_chess960_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let zobrist_table = ZobristTable::with_capacity(1);
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let pv_nodes = std::sync::Mutex::new(std::collections::HashMap::new());
+        let history_table = [[0u32; 64]; 64];
+        let context = SearchContext {
+            zobrist_table: &zobrist_table,
+            stop_flag: &stop_flag,
+            pv_nodes: &pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &history_table,
+            counter_move: None,
+            start_time: std::time::Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+
+        let positions = [
+            ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189]),
+            ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002]),
+            ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10471]),
+            ("qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", [22, 593, 13440]),
+            ("1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9", [28, 1120, 31058]),
+        ];
+        for (fen, expected) in positions {
+            let mut board = service.fen.set_fen(fen);
+            board.chess960 = true;
+            let board_copy = board.clone();
+            for (depth, nodes) in expected.iter().enumerate() {
+                assert_eq!(perft_fast(&service.move_gen, &mut board, depth + 1, &config, &context), *nodes, "perft({}) of {}", depth + 1, fen);
+            }
+            assert_eq!(board, board_copy);
+            assert_eq!(zobrist::gen_hash(&board), board.cached_hash);
+        }
+    }
+
     #[test]
     fn underpromotions_config_test() {
         let service = Service::new();
@@ -1718,6 +1777,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_material_key_consistency() {
+        let service = Service::new();
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+
+        fn check_material_key_recursive(board: &mut Board, service: &Service, depth: i32) {
+            assert_eq!(board.material_key, board.gen_material_key(), "Material key mismatch at FEN: {}", service.fen.get_fen(board));
+
+            if depth == 0 {
+                return;
+            }
+
+            let moves = generate_valid_moves_list(board);
+            for m in &moves {
+                let move_info = board.do_move(m);
+                check_material_key_recursive(board, service, depth - 1);
+                board.undo_move(m, move_info);
+                assert_eq!(board.material_key, board.gen_material_key(), "Material key mismatch after undo of move {}", m.to_algebraic());
+            }
+        }
+
+        for fen in fens {
+            let mut board = service.fen.set_fen(fen);
+            check_material_key_recursive(&mut board, &service, 2);
+        }
+    }
+
     #[test]
     fn test_generate_moves_list_bitboard_consistency() {
         let service = Service::new();
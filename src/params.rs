@@ -0,0 +1,731 @@
+//! Registry of the engine parameters settable through `setoption`.
+//!
+//! Every entry maps a `Config` field to its UCI type, range and accessors. The registry prints the
+//! `uci` option list, applies and validates `setoption` and dumps the active values. Option names
+//! match the UCI name or the `Config` field name, ignoring case, spaces and underscores.
+//!
+//! Parameter files are JSON (flat, `tuning/parameters.json` or `spsa_state.json` with `theta`) or
+//! TOML-style `name = value` lines, `dumpparams <file>` writes the flat JSON form.
+
+use std::fs::File;
+use std::io::Write;
+
+use crate::config::{Aggressiveness, BookSelection, Config, IirMode};
+
+/// `Hash` is set in MB of transposition table entries.
+const HASH_ENTRY_BYTES: usize = std::mem::size_of::<crate::zobrist::AtomicEntry>();
+
+pub enum ParamType {
+    Spin { min: i64, max: i64 },
+    Check,
+    Combo(&'static [&'static str]),
+    String,
+}
+
+pub struct Param {
+    /// Name of the `Config` field, also used in parameter files.
+    pub field: &'static str,
+    /// Name in the `uci` option list, parameters without one are settable but not advertised.
+    pub uci_name: Option<&'static str>,
+    /// Further accepted names, kept for older tuning setups.
+    pub aliases: &'static [&'static str],
+    pub param_type: ParamType,
+    pub get: fn(&Config) -> String,
+    /// Only called with values accepted by `Param::validate`.
+    pub set: fn(&mut Config, &str),
+    /// Bounds of the texel tuner, `None` for parameters it leaves alone.
+    pub tuning: Option<(i16, i16)>,
+}
+
+macro_rules! spin {
+    ($field:ident, $uci_name:expr, $min:expr, $max:expr, tune($tune_min:expr, $tune_max:expr)) => {
+        Param { tuning: Some(($tune_min, $tune_max)), ..spin!($field, $uci_name, $min, $max) }
+    };
+    ($field:ident, $uci_name:expr, $min:expr, $max:expr) => {
+        Param {
+            field: stringify!($field),
+            uci_name: $uci_name,
+            aliases: &[],
+            param_type: ParamType::Spin { min: $min, max: $max },
+            get: |config| config.$field.to_string(),
+            set: |config, value| config.$field = value.parse().expect("RIP validated spin value"),
+            tuning: None,
+        }
+    };
+}
+
+macro_rules! check {
+    ($field:ident, $uci_name:expr) => {
+        Param {
+            field: stringify!($field),
+            uci_name: $uci_name,
+            aliases: &[],
+            param_type: ParamType::Check,
+            get: |config| config.$field.to_string(),
+            set: |config, value| config.$field = value == "true",
+            tuning: None,
+        }
+    };
+}
+
+macro_rules! string {
+    ($field:ident, $uci_name:expr) => {
+        Param {
+            field: stringify!($field),
+            uci_name: $uci_name,
+            aliases: &[],
+            param_type: ParamType::String,
+            get: |config| config.$field.to_string(),
+            set: |config, value| config.$field = value.to_string(),
+            tuning: None,
+        }
+    };
+}
+
+pub const PARAMS: &[Param] = &[
+    spin!(move_overhead, Some("Move Overhead"), 0, 5000),
+    Param {
+        field: "max_zobrist_hash_entries",
+        uci_name: Some("Hash"),
+        aliases: &[],
+        param_type: ParamType::Spin { min: 1, max: 1024 },
+        get: |config| (config.max_zobrist_hash_entries * HASH_ENTRY_BYTES / (1024 * 1024)).to_string(),
+        set: |config, value| {
+            config.max_zobrist_hash_entries = value.parse::<usize>().expect("RIP validated spin value") * 1024 * 1024 / HASH_ENTRY_BYTES;
+        },
+        tuning: None,
+    },
+    spin!(search_threads, Some("Threads"), 1, 8),
+    string!(syzygy_path, Some("SyzygyPath")),
+    string!(book_file, Some("BookFile")),
+    string!(params_file, Some("ParamsFile")),
+    check!(use_book, Some("OwnBook")),
+    check!(cache_book_in_ram, Some("CacheBookInRam")),
+    Param {
+        field: "book_selection",
+        uci_name: Some("BookSelection"),
+        aliases: &[],
+        param_type: ParamType::Combo(&["Best", "Weighted", "Uniform", "Temperature"]),
+        get: |config| format!("{:?}", config.book_selection),
+        set: |config, value| config.book_selection = match value {
+            "Best" => BookSelection::Best,
+            "Uniform" => BookSelection::Uniform,
+            "Temperature" => BookSelection::Temperature,
+            _ => BookSelection::Weighted,
+        },
+        tuning: None,
+    },
+    spin!(book_temperature, Some("BookTemperature"), 1, 1000),
+    spin!(book_depth, Some("BookDepth"), 0, 1000),
+    spin!(book_min_weight, Some("BookMinWeight"), 0, 65535),
+    spin!(book_seed, Some("BookSeed"), 0, 2147483647),
+    check!(book_learning, Some("BookLearning")),
+    spin!(book_check_depth, Some("BookCheckDepth"), 0, 20),
+    spin!(book_check_margin, Some("BookCheckMargin"), 0, 1000),
+    Param {
+        field: "aggressiveness",
+        uci_name: Some("Aggressiveness"),
+        aliases: &[],
+        param_type: ParamType::Combo(&["Normal", "Aggressive", "HighAggressive"]),
+        get: |config| format!("{:?}", config.aggressiveness),
+        set: |config, value| config.set_aggressiveness(match value {
+            "Aggressive" => Aggressiveness::Aggressive,
+            "HighAggressive" => Aggressiveness::HighAggressive,
+            _ => Aggressiveness::Normal,
+        }),
+        tuning: None,
+    },
+    check!(enable_positional_cap, Some("EnablePositionalCap")),
+    spin!(positional_cap_damping, Some("PositionalCapDamping"), 1, 100),
+    spin!(king_open_file_malus, Some("KingOpenFileMalus"), 0, 500, tune(18, 74)),
+    spin!(king_half_open_file_malus, Some("KingHalfOpenFileMalus"), 0, 500, tune(10, 40)),
+    spin!(king_ring_defender_value, Some("KingRingDefenderValue"), 0, 10, tune(0, 10)),
+    spin!(threat_minor_attacks_rook, Some("ThreatMinorAttacksRook"), 0, 200, tune(6, 26)),
+    spin!(threat_minor_attacks_queen, Some("ThreatMinorAttacksQueen"), 0, 200, tune(12, 48)),
+    spin!(threat_rook_attacks_queen, Some("ThreatRookAttacksQueen"), 0, 200, tune(10, 40)),
+    Param {
+        field: "log_path",
+        uci_name: Some("LogPath"),
+        aliases: &[],
+        param_type: ParamType::String,
+        get: |config| config.log_path.to_string(),
+        set: |config, value| config.log_path = std::sync::Arc::from(value),
+        tuning: None,
+    },
+    spin!(connected_passed_pawn_mg, Some("ConnectedPassedPawnMg"), -500, 500, tune(7, 30)),
+    spin!(connected_passed_pawn_eg, Some("ConnectedPassedPawnEg"), -500, 500, tune(14, 58)),
+    spin!(knight_outpost_true_mg, Some("KnightOutpostTrueMg"), -500, 500, tune(14, 58)),
+    spin!(knight_outpost_true_eg, Some("KnightOutpostTrueEg"), -500, 500, tune(7, 30)),
+    spin!(bishop_outpost_true_mg, Some("BishopOutpostTrueMg"), -500, 500, tune(10, 42)),
+    spin!(bishop_outpost_true_eg, Some("BishopOutpostTrueEg"), -500, 500, tune(5, 22)),
+    spin!(king_pawn_shield_kingside, Some("KingPawnShieldKingside"), -500, 500, tune(18, 74)),
+    spin!(king_pawn_shield_queenside, Some("KingPawnShieldQueenside"), -500, 500, tune(12, 50)),
+    spin!(king_piece_shield_kingside, Some("KingPieceShieldKingside"), -500, 500, tune(7, 30)),
+    spin!(king_piece_shield_queenside, Some("KingPieceShieldQueenside"), -500, 500, tune(0, 20)),
+    spin!(opposite_bishops_draw_scale, Some("OppositeBishopsDrawScale"), 0, 100, tune(25, 100)),
+    spin!(rook_behind_enemy_passed_pawn_mg, Some("RookBehindEnemyPassedPawnMg"), -500, 500, tune(0, 20)),
+    spin!(rook_behind_enemy_passed_pawn_eg, Some("RookBehindEnemyPassedPawnEg"), -500, 500, tune(12, 48)),
+    check!(enable_lazy_eval, Some("EnableLazyEval")),
+    spin!(lazy_eval_min_game_phase, Some("LazyEvalMinGamePhase"), 0, 256),
+    spin!(lazy_eval_margin_search, Some("LazyEvalMarginSearch"), 10, 1000),
+    spin!(lazy_eval_margin_qs, Some("LazyEvalMarginQs"), 10, 1000),
+    check!(enable_futility_pruning, Some("EnableFutilityPruning")),
+    spin!(futility_max_depth, Some("FutilityMaxDepth"), 1, 10),
+    spin!(futility_margin_base, Some("FutilityMarginBase"), 0, 500),
+    spin!(futility_margin_slope, Some("FutilityMarginSlope"), 0, 300),
+    spin!(contempt, Some("Contempt"), -200, 200),
+    check!(analysis_contempt, Some("AnalysisContempt")),
+    check!(uci_analyse_mode, Some("UCI_AnalyseMode")),
+    check!(chess960, Some("UCI_Chess960")),
+    check!(limit_strength, Some("UCI_LimitStrength")),
+    spin!(uci_elo, Some("UCI_Elo"), crate::skill::MIN_ELO as i64, crate::skill::MAX_ELO as i64),
+    spin!(skill_level, Some("Skill Level"), 0, 20),
+    spin!(skill_seed, Some("SkillSeed"), 0, 2147483647),
+    Param {
+        field: "iir_mode",
+        uci_name: Some("IirMode"),
+        aliases: &[],
+        param_type: ParamType::Combo(&["Off", "Reduction", "Deepening"]),
+        get: |config| format!("{:?}", config.iir_mode),
+        set: |config, value| config.iir_mode = match value {
+            "Off" => IirMode::Off,
+            "Deepening" => IirMode::Deepening,
+            _ => IirMode::Reduction,
+        },
+        tuning: None,
+    },
+    spin!(iir_depth_threshold, Some("IirDepthThreshold"), 1, 20),
+    spin!(iid_reduction, Some("IidReduction"), 1, 10),
+    check!(use_nnue, Some("UseNNUE")),
+    string!(nnue_model_path, Some("NnueModelPath")),
+    spin!(nmp_depth_threshold, None, 1, 20),
+    spin!(nmp_reduction, None, 0, 10),
+    spin!(nmp_verification_threshold, None, 0, 50),
+    spin!(nmp_dynamic_divisor, None, 0, 50),
+    spin!(lmr_move_threshold, None, 0, 64),
+    spin!(lmr_history_good_threshold, None, 0, 9000),
+    spin!(lmr_history_bad_threshold, None, 0, 9000),
+    spin!(aspiration_delta, None, 5, 200),
+    spin!(aspiration_growth, None, 2, 10),
+    spin!(root_time_extension_threshold, None, 50, 100),
+    spin!(root_time_extension, None, 100, 200),
+    check!(enable_dynamic_time, None),
+    spin!(tm_hard_limit_factor, None, 100, 1000),
+    spin!(tm_max_remaining_percent, None, 1, 100),
+    spin!(tm_instability_window, None, 1, 20),
+    spin!(tm_best_move_change_extension, None, 0, 200),
+    spin!(tm_score_drop_threshold, None, 0, 1000),
+    spin!(tm_score_drop_extension, None, 0, 200),
+    spin!(tm_stable_iterations, None, 1, 20),
+    spin!(tm_stable_scale, None, 10, 100),
+    spin!(tm_node_dominance_percent, None, 50, 100),
+    spin!(tm_dominant_scale, None, 10, 100),
+    spin!(min_thinking_time, None, 0, 5000),
+    spin!(max_depth, None, 1, 99),
+    spin!(multi_pv, None, 1, 64),
+    spin!(truncate_bad_moves, None, 1, 256),
+    spin!(eval_noise, None, 0, 1000),
+    spin!(smp_thread_eval_noise, None, 0, 1000),
+    spin!(see_rank_penalty, None, 1000, 1000000),
+    Param {
+        field: "lmr_divisor",
+        uci_name: None,
+        aliases: &["lmr_divisor_scaled"],
+        param_type: ParamType::Spin { min: 50, max: 1000 },
+        get: |config| config.lmr_divisor.to_string(),
+        set: |config, value| {
+            config.lmr_divisor = value.parse().expect("RIP validated spin value");
+            config.recalculate_lmr_table();
+        },
+        tuning: None,
+    },
+    spin!(killer_move_1_rank_bonus, None, 0, 1000000),
+    spin!(killer_move_2_rank_bonus, None, 0, 1000000),
+    spin!(counter_move_rank_bonus, None, 0, 1000000),
+    spin!(is_hashed_rank_bonus, None, 0, 1000),
+    spin!(give_check_rank_bonus, None, 0, 1000),
+    spin!(is_pv_node_rank_bonus, None, 0, 1000),
+    spin!(give_promotion_rank_bonus_queen, None, 0, 1000),
+    spin!(give_promotion_rank_bonus_knight, None, 0, 1000),
+    spin!(history_max_threshold, None, 0, 1000000),
+    spin!(delta_pruning_margin, None, 0, 2000),
+    check!(enable_qs_tt, None),
+    check!(use_zobrist, None),
+    check!(use_pv_nodes, None),
+    check!(pre_sort_moves, None),
+    check!(use_underpromotions, None),
+    check!(skip_strong_validation, None),
+    check!(enable_pvs, None),
+    check!(enable_lmr, None),
+    check!(enable_nmp, None),
+    check!(enable_rfp, None),
+    check!(enable_aspiration, None),
+    check!(enable_root_node_ordering, None),
+    check!(enable_delta_pruning, None),
+    check!(enable_counter_moves, None),
+    check!(enable_history_malus, None),
+    check!(enable_endgame_mopup, None),
+    spin!(mopup_center_weight, None, 0, 1000, tune(0, 20)),
+    spin!(mopup_proximity_weight, None, 0, 1000, tune(0, 30)),
+    spin!(mopup_eval_threshold, None, 0, 2000),
+    spin!(mopup_max_game_phase, None, 0, 256),
+    check!(enable_kpk_bitbase, None),
+    check!(enable_endgame_evaluators, None),
+    spin!(your_turn_bonus, None, -1000, 1000, tune(9, 38)),
+    spin!(pawn_structure, None, -1000, 1000, tune(0, 10)),
+    spin!(pawn_supports_knight_outpost, None, -1000, 1000, tune(0, 20)),
+    spin!(pawn_centered, None, -1000, 1000, tune(6, 26)),
+    spin!(pawn_undeveloped_malus, None, -1000, 1000, tune(7, 30)),
+    spin!(pawn_on_last_rank_bonus, None, -1000, 1000, tune(91, 366)),
+    spin!(pawn_on_before_last_rank_bonus, None, -1000, 1000, tune(52, 208)),
+    spin!(pawn_on_before_before_last_rank_bonus, None, -1000, 1000, tune(23, 94)),
+    spin!(pawn_defends_bishop, None, -1000, 1000, tune(11, 46)),
+    spin!(pawn_double_malus, None, -1000, 1000, tune(7, 28)),
+    spin!(pawn_isolated_malus, None, -1000, 1000, tune(0, 18)),
+    spin!(pawn_backward_malus, None, -1000, 1000, tune(5, 22)),
+    spin!(protected_passed_pawn_middlegame, None, -1000, 1000, tune(6, 24)),
+    spin!(protected_passed_pawn_endgame, None, -1000, 1000, tune(13, 54)),
+    spin!(undeveloped_knight_malus, None, -1000, 1000, tune(15, 62)),
+    spin!(knight_on_rim_malus, None, -1000, 1000, tune(8, 34)),
+    spin!(knight_centered, None, -1000, 1000, tune(12, 48)),
+    spin!(knight_blockes_pawn, None, -1000, 1000, tune(14, 56)),
+    spin!(knight_mobility_factor, None, -1000, 1000, tune(0, 10)),
+    spin!(undeveloped_bishop_malus, None, -1000, 1000, tune(15, 62)),
+    spin!(bishop_pair_bonus, None, -1000, 1000, tune(24, 96)),
+    spin!(bishop_trapped_at_rim_malus, None, -1000, 1000, tune(29, 116)),
+    spin!(bishop_mobility_factor, None, -1000, 1000, tune(0, 10)),
+    spin!(rook_open_file, None, -1000, 1000, tune(13, 54)),
+    spin!(rook_half_open_file, None, -1000, 1000, tune(10, 42)),
+    spin!(rook_doubled_bonus, None, -1000, 1000, tune(12, 50)),
+    spin!(rook_behind_passed_pawn_middlegame, None, -1000, 1000, tune(6, 26)),
+    spin!(rook_behind_passed_pawn_endgame, None, -1000, 1000, tune(19, 76)),
+    spin!(rook_on_seventh, None, -1000, 1000, tune(16, 66)),
+    spin!(rook_mobility_factor, None, -1000, 1000, tune(0, 10)),
+    spin!(queen_mobility_factor, None, -1000, 1000, tune(0, 10)),
+    spin!(king_passer_dist_weight, None, -1000, 1000, tune(0, 24)),
+    spin!(undeveloped_king_malus, None, -1000, 1000, tune(27, 108)),
+    spin!(king_ring_attack_knight, None, -1000, 1000, tune(0, 10)),
+    spin!(king_ring_attack_bishop, None, -1000, 1000, tune(0, 10)),
+    spin!(king_ring_attack_rook, None, -1000, 1000, tune(0, 10)),
+    spin!(king_ring_attack_queen, None, -1000, 1000, tune(0, 10)),
+    spin!(king_opposition_bonus, None, -1000, 1000, tune(6, 24)),
+    spin!(king_pawn_shield, None, -1000, 1000, tune(18, 74)),
+    spin!(king_piece_shield, None, -1000, 1000, tune(8, 34)),
+    spin!(king_trapp_at_baseline_malus, None, -1000, 1000, tune(35, 142)),
+    spin!(king_in_check_malus, None, -1000, 1000, tune(67, 270)),
+    spin!(king_in_double_check_malus, None, -1000, 1000, tune(168, 674)),
+    spin!(pawn_attacks_opponent_fig, None, -1000, 1000, tune(17, 70)),
+    spin!(pawn_attacks_opponent_fig_with_tempo, None, -1000, 1000, tune(9, 36)),
+    spin!(queen_in_attack, None, -1000, 1000, tune(26, 106)),
+    spin!(queen_in_attack_with_tempo, None, -1000, 1000, tune(14, 58)),
+    spin!(knight_attacks_bishop, None, -1000, 1000, tune(0, 10)),
+    spin!(knight_attacks_rook, None, -1000, 1000, tune(8, 32)),
+    spin!(knight_attacks_bishop_tempo, None, -1000, 1000, tune(0, 18)),
+    spin!(knight_attacks_rook_tempo, None, -1000, 1000, tune(6, 26)),
+    spin!(king_danger_weight_1, None, -1000, 1000, tune(0, 20)),
+    spin!(king_danger_weight_2, None, -1000, 1000, tune(25, 100)),
+    spin!(king_danger_weight_3, None, -1000, 1000, tune(50, 200)),
+    spin!(king_danger_weight_4, None, -1000, 1000, tune(75, 300)),
+    spin!(king_danger_weight_5, None, -1000, 1000, tune(100, 400)),
+    spin!(king_open_file_heavy_threat_malus, None, -1000, 1000, tune(0, 30)),
+    spin!(rook_open_file_attacks_king, None, -1000, 1000, tune(0, 30)),
+    spin!(rook_open_file_attacks_queen, None, -1000, 1000, tune(0, 20)),
+    spin!(pawn_phalanx_mg, None, -1000, 1000, tune(0, 16)),
+    spin!(pawn_phalanx_eg, None, -1000, 1000, tune(0, 10)),
+    spin!(bishop_diagonal_attacks_king, None, -1000, 1000, tune(0, 30)),
+    spin!(bishop_diagonal_attacks_queen, None, -1000, 1000, tune(0, 20)),
+    spin!(rook_on_seventh_king_cutoff, None, -1000, 1000, tune(0, 40)),
+    spin!(rooks_doubled_on_seventh, None, -1000, 1000, tune(0, 50)),
+    spin!(passed_pawn_blockaded_malus, None, -1000, 1000, tune(0, 30)),
+    spin!(candidate_passed_pawn_bonus, None, -1000, 1000, tune(0, 16)),
+    spin!(pawn_storm_bonus, None, -1000, 1000, tune(0, 12)),
+];
+
+/// Option name without case, spaces and underscores, so "Skill Level", "skill_level" and "SkillLevel" are equal.
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| *c != '_' && !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+impl Param {
+    pub fn matches(&self, name: &str) -> bool {
+        let name = normalize(name);
+        normalize(self.field) == name
+            || self.uci_name.is_some_and(|uci_name| normalize(uci_name) == name)
+            || self.aliases.iter().any(|alias| normalize(alias) == name)
+    }
+
+    /// Canonical form of a value, or why it is not accepted.
+    pub fn validate(&self, value: &str) -> Result<String, String> {
+        let value = value.trim();
+        match &self.param_type {
+            ParamType::Spin { min, max } => match value.parse::<i64>() {
+                Ok(v) if v >= *min && v <= *max => Ok(v.to_string()),
+                Ok(v) => Err(format!("value {} of {} out of range {}..{}", v, self.field, min, max)),
+                Err(_) => Err(format!("invalid value {} for spin option {}", value, self.field)),
+            },
+            ParamType::Check => match value.to_lowercase().as_str() {
+                "true" | "false" => Ok(value.to_lowercase()),
+                _ => Err(format!("invalid value {} for check option {}", value, self.field)),
+            },
+            ParamType::Combo(vars) => vars.iter().find(|var| var.eq_ignore_ascii_case(value)).map(|var| var.to_string())
+                .ok_or(format!("invalid value {} for combo option {}, expected one of {}", value, self.field, vars.join(", "))),
+            ParamType::String => Ok(if value == "<empty>" { String::new() } else { value.to_string() }),
+        }
+    }
+
+    /// Line of the `uci` option list with the default from the config, None for unadvertised parameters.
+    pub fn uci_option(&self, config: &Config) -> Option<String> {
+        let uci_name = self.uci_name?;
+        let default = (self.get)(config);
+        Some(match &self.param_type {
+            ParamType::Spin { min, max } => format!("option name {} type spin default {} min {} max {}", uci_name, default, min, max),
+            ParamType::Check => format!("option name {} type check default {}", uci_name, default),
+            ParamType::Combo(vars) => format!("option name {} type combo default {} var {}", uci_name, default, vars.join(" var ")),
+            ParamType::String => format!("option name {} type string default {}", uci_name, if default.is_empty() { "<empty>" } else { &default }),
+        })
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|param| param.matches(name))
+}
+
+/// Validates and applies a `setoption`, returns the changed parameter.
+pub fn set_option(config: &mut Config, name: &str, value: &str) -> Result<&'static Param, String> {
+    let param = find(name).ok_or(format!("unknown option {}", name))?;
+    let value = param.validate(value)?;
+    (param.set)(config, &value);
+    Ok(param)
+}
+
+/// The `uci` option lines of all advertised parameters.
+pub fn uci_options(config: &Config) -> Vec<String> {
+    PARAMS.iter().filter_map(|param| param.uci_option(config)).collect()
+}
+
+/// Field name and value of every parameter.
+pub fn dump(config: &Config) -> Vec<(&'static str, String)> {
+    PARAMS.iter().map(|param| (param.field, (param.get)(config))).collect()
+}
+
+/// Minimal JSON tree, scalars keep their text (strings unescaped).
+enum Json {
+    Object(Vec<(String, Json)>),
+    Array,
+    Scalar(String),
+}
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            other => Err(format!("expected '{}' but found {:?}", expected, other)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut result = String::new();
+        loop {
+            match self.chars.next().ok_or("unterminated string")? {
+                '"' => return Ok(result),
+                '\\' => match self.chars.next().ok_or("unterminated string")? {
+                    'n' => result.push('\n'),
+                    't' => result.push('\t'),
+                    'u' => {
+                        let code: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let code = u32::from_str_radix(&code, 16).map_err(|_| format!("invalid escape \\u{}", code))?;
+                        result.push(char::from_u32(code).unwrap_or('?'));
+                    },
+                    c => result.push(c),
+                },
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&'}').is_some() {
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    members.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => continue,
+                        Some('}') => return Ok(Json::Object(members)),
+                        other => return Err(format!("expected ',' or '}}' but found {:?}", other)),
+                    }
+                }
+            },
+            Some('[') => {
+                self.chars.next();
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&']').is_some() {
+                    return Ok(Json::Array);
+                }
+                loop {
+                    self.value()?;
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => continue,
+                        Some(']') => return Ok(Json::Array),
+                        other => return Err(format!("expected ',' or ']' but found {:?}", other)),
+                    }
+                }
+            },
+            Some('"') => Ok(Json::Scalar(self.string()?)),
+            Some(_) => {
+                let mut literal = String::new();
+                while let Some(c) = self.chars.next_if(|c| !c.is_whitespace() && !",}]".contains(*c)) {
+                    literal.push(c);
+                }
+                Ok(Json::Scalar(literal))
+            },
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+}
+
+/// Name and value entries of a parameter file.
+pub fn parse_params(content: &str) -> Result<Vec<(String, String)>, String> {
+    if !content.trim_start().starts_with('{') {
+        return Ok(content.lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('['))
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().trim_matches('"').to_string()))
+            .collect());
+    }
+
+    let mut parser = JsonParser { chars: content.chars().peekable() };
+    let Json::Object(mut members) = parser.value()? else {
+        return Err("expected a JSON object".to_string());
+    };
+    // spsa_state.json keeps the parameters in "theta" next to the tuner state
+    if let Some(index) = members.iter().position(|(name, value)| name == "theta" && matches!(value, Json::Object(_))) {
+        let Json::Object(theta) = members.swap_remove(index).1 else { unreachable!() };
+        members = theta;
+    }
+    Ok(members.into_iter().filter_map(|(name, value)| match value {
+        Json::Scalar(value) => Some((name, value)),
+        // tuning/parameters.json: {"value": v, "min": a, "max": b}
+        Json::Object(fields) => fields.into_iter().find_map(|(field, value)| match value {
+            Json::Scalar(value) if field == "value" => Some((name.clone(), value)),
+            _ => None,
+        }),
+        Json::Array => None,
+    }).collect())
+}
+
+/// Applies a parameter file to the config, returns the applied parameters and a message per
+/// rejected entry. Fractional values of spin parameters (SPSA theta) are rounded.
+pub fn load_params_file(config: &mut Config, path: &str) -> Result<(Vec<&'static Param>, Vec<String>), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    let mut applied = Vec::new();
+    let mut rejected = Vec::new();
+    for (name, value) in parse_params(&content)? {
+        let param = find(&name);
+        // a parameter file does not chain further parameter files
+        if param.is_some_and(|param| param.field == "params_file") {
+            continue;
+        }
+        let value = match (param.map(|param| &param.param_type), value.parse::<f64>()) {
+            (Some(ParamType::Spin { .. }), Ok(v)) if value.parse::<i64>().is_err() => (v.round() as i64).to_string(),
+            _ => value,
+        };
+        match set_option(config, &name, &value) {
+            Ok(param) => applied.push(param),
+            Err(msg) => rejected.push(msg),
+        }
+    }
+    Ok((applied, rejected))
+}
+
+/// Writes all parameters as flat JSON, readable by `load_params_file`.
+pub fn write_params_file(config: &Config, path: &str) -> Result<(), String> {
+    let body: Vec<String> = PARAMS.iter().filter(|param| param.field != "params_file").map(|param| {
+        let value = (param.get)(config);
+        match param.param_type {
+            ParamType::Spin { .. } | ParamType::Check => format!("    \"{}\": {}", param.field, value),
+            _ => format!("    \"{}\": \"{}\"", param.field, value.replace('\\', "\\\\").replace('"', "\\\"")),
+        }
+    }).collect();
+    let mut file = File::create(path).map_err(|e| format!("could not create {}: {}", path, e))?;
+    writeln!(file, "{{\n{}\n}}", body.join(",\n")).map_err(|e| format!("could not write {}: {}", path, e))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_consistency_test() {
+        let config = Config::new();
+        for (i, param) in PARAMS.iter().enumerate() {
+            let default = (param.get)(&config);
+            assert_eq!(Ok(default.clone()), param.validate(&default), "{}", param.field);
+            for other in &PARAMS[i + 1..] {
+                assert!(!other.matches(param.field), "{} shadows {}", param.field, other.field);
+                for name in param.uci_name.iter().chain(param.aliases) {
+                    assert!(!other.matches(name), "{} shadows {}", name, other.field);
+                }
+            }
+        }
+    }
+
+    /// `Config` fields that are no parameters.
+    const UNREGISTERED_FIELDS: &[&str] = &[
+        // derived from other fields
+        "version", "lmr_table",
+        // debugging output
+        "in_debug", "_print_commands", "print_eval_per_figure", "log_to_console", "print_info_string_during_search",
+        // unused
+        "game_loop", "search_depth",
+        // the pawn hash table is allocated once per service
+        "max_pawn_hash_entries",
+        // single search mode
+        "quiescence_search_mode",
+        // fractional
+        "max_eval_mult",
+    ];
+
+    #[test]
+    fn config_coverage_test() {
+        let source = include_str!("config.rs");
+        let body = &source[source.find("pub struct Config {").unwrap()..];
+        let body = &body[..body.find("\n}").unwrap()];
+        let fields: Vec<&str> = body.lines().skip(1)
+            .filter_map(|line| line.trim().strip_prefix("pub ")?.split_once(':').map(|(name, _)| name))
+            .collect();
+        assert!(fields.len() > PARAMS.len());
+        for field in fields {
+            let registered = PARAMS.iter().any(|param| param.field == field);
+            assert!(registered != UNREGISTERED_FIELDS.contains(&field), "{} is neither registered nor excluded, or both", field);
+        }
+    }
+
+    #[test]
+    fn tuning_bounds_test() {
+        let config = Config::new();
+        for param in PARAMS.iter().filter(|param| param.tuning.is_some()) {
+            let (tune_min, tune_max) = param.tuning.unwrap();
+            let ParamType::Spin { min, max } = param.param_type else { panic!("{} is no spin", param.field) };
+            assert!(min <= tune_min as i64 && tune_max as i64 <= max, "{}", param.field);
+            let default: i16 = (param.get)(&config).parse().unwrap();
+            assert!(tune_min <= default && default <= tune_max, "{}", param.field);
+        }
+    }
+
+    #[test]
+    fn set_option_test() {
+        let mut config = Config::new();
+        assert_eq!("king_open_file_malus", set_option(&mut config, "KingOpenFileMalus", "44").unwrap().field);
+        assert_eq!(44, config.king_open_file_malus);
+        set_option(&mut config, "king_open_file_malus", "45").unwrap();
+        assert_eq!(45, config.king_open_file_malus);
+        set_option(&mut config, "Skill Level", "7").unwrap();
+        assert_eq!(7, config.skill_level);
+        set_option(&mut config, "UCI_Chess960", "True").unwrap();
+        assert!(config.chess960);
+        set_option(&mut config, "IirMode", "deepening").unwrap();
+        assert_eq!(IirMode::Deepening, config.iir_mode);
+        set_option(&mut config, "SyzygyPath", "<empty>").unwrap();
+        assert_eq!("", config.syzygy_path);
+        set_option(&mut config, "BookFile", "/tmp/my book.bin").unwrap();
+        assert_eq!("/tmp/my book.bin", config.book_file);
+        set_option(&mut config, "Hash", "64").unwrap();
+        assert_eq!(64 * 1024 * 1024 / 16, config.max_zobrist_hash_entries);
+        assert_eq!("64", (find("Hash").unwrap().get)(&config));
+        set_option(&mut config, "Threads", "1").unwrap();
+        assert_eq!(1, config.search_threads);
+        set_option(&mut config, "enable_lmr", "false").unwrap();
+        assert!(!config.enable_lmr);
+        set_option(&mut config, "tm_stable_scale", "60").unwrap();
+        assert_eq!(60, config.tm_stable_scale);
+
+        set_option(&mut config, "lmr_divisor_scaled", "300").unwrap();
+        assert_eq!(300, config.lmr_divisor);
+        let mut expected = config.clone();
+        expected.recalculate_lmr_table();
+        assert_eq!(expected.lmr_table, config.lmr_table);
+
+        // typos and invalid values are reported and leave the config unchanged
+        assert!(set_option(&mut config, "king_open_file_maluss", "10").is_err());
+        assert!(set_option(&mut config, "KingOpenFileMalus", "501").is_err());
+        assert!(set_option(&mut config, "KingOpenFileMalus", "ten").is_err());
+        assert!(set_option(&mut config, "EnableLazyEval", "yes").is_err());
+        assert!(set_option(&mut config, "Aggressiveness", "Wild").is_err());
+        assert_eq!(45, config.king_open_file_malus);
+    }
+
+    #[test]
+    fn uci_options_test() {
+        let config = Config::new();
+        let options = uci_options(&config);
+        assert!(options.contains(&format!("option name KingOpenFileMalus type spin default {} min 0 max 500", config.king_open_file_malus)));
+        assert!(options.contains(&"option name SyzygyPath type string default <empty>".to_string()));
+        assert!(options.contains(&"option name IirMode type combo default Off var Off var Reduction var Deepening".to_string()));
+        assert!(options.contains(&"option name UCI_Chess960 type check default false".to_string()));
+        assert!(dump(&config).contains(&("pawn_structure", config.pawn_structure.to_string())));
+    }
+
+    #[test]
+    fn parse_params_test() {
+        let parameters = "{\n    \"contempt\": {\n        \"value\": 12,\n        \"min\": -200,\n        \"max\": 200\n    },\n    \"pawn_structure\": {\"value\": 7, \"min\": 0, \"max\": 20}\n}";
+        assert_eq!(vec![("contempt".to_string(), "12".to_string()), ("pawn_structure".to_string(), "7".to_string())], parse_params(parameters).unwrap());
+
+        let spsa_state = "{\"k\": 43, \"theta\": {\"lmr_divisor\": 185.0, \"your_turn_bonus\": 19.3}, \"history\": [1, 2]}";
+        assert_eq!(vec![("lmr_divisor".to_string(), "185.0".to_string()), ("your_turn_bonus".to_string(), "19.3".to_string())], parse_params(spsa_state).unwrap());
+
+        let toml = "# tuned set\n[params]\nSkill Level = 5\nbook_file = \"books/main.bin\"\n";
+        assert_eq!(vec![("Skill Level".to_string(), "5".to_string()), ("book_file".to_string(), "books/main.bin".to_string())], parse_params(toml).unwrap());
+
+        assert!(parse_params("{\"contempt\": 1").is_err());
+    }
+
+    #[test]
+    fn params_file_roundtrip_test() {
+        let path = std::env::temp_dir().join(format!("params_test_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut config = Config::new();
+        set_option(&mut config, "your_turn_bonus", "33").unwrap();
+        set_option(&mut config, "BookFile", "books/\"quoted\".bin").unwrap();
+        set_option(&mut config, "IirMode", "Reduction").unwrap();
+        write_params_file(&config, path).unwrap();
+
+        let mut loaded = Config::new();
+        let (applied, rejected) = load_params_file(&mut loaded, path).unwrap();
+        std::fs::remove_file(path).ok();
+        assert!(rejected.is_empty(), "{:?}", rejected);
+        assert_eq!(PARAMS.len() - 1, applied.len());
+        assert_eq!(dump(&config), dump(&loaded));
+
+        std::fs::write(path, "{\"theta\": {\"your_turn_bonus\": 21.6, \"lmr_divisor\": 190.0, \"typo\": 1, \"contempt\": 900}}").unwrap();
+        let (applied, rejected) = load_params_file(&mut loaded, path).unwrap();
+        std::fs::remove_file(path).ok();
+        assert_eq!(2, applied.len());
+        assert_eq!(22, loaded.your_turn_bonus);
+        assert_eq!(190, loaded.lmr_divisor);
+        assert_eq!(2, rejected.len());
+    }
+}
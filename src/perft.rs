@@ -0,0 +1,264 @@
+//! Perft: counts the leaf nodes of the legal move tree to verify the move generator.
+//!
+//! Draws by repetition or the fifty-move rule do not end a line, every legal move is counted.
+//! Bulk counting returns the number of legal moves at depth 1 instead of playing them. The
+//! optional hash table caches subtree counts by position key and depth.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+use crate::config::Config;
+use crate::fen_service::FenService;
+use crate::model::{Board, GameStatus, MoveList, SearchContext, Stats, Turn};
+use crate::move_gen_service::MoveGenService;
+use crate::zobrist::ZobristTable;
+
+/// Standard perft positions with their node counts from depth 1 on.
+pub const PERFT_SUITE: [(&str, &str, &[u64]); 6] = [
+    ("startpos", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281, 4865609]),
+    ("kiwipete", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862, 4085603]),
+    ("position 3", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238, 674624]),
+    ("position 4", "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467, 422333]),
+    ("position 5", "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379, 2103487]),
+    ("position 6", "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[46, 2079, 89890, 3894594]),
+];
+
+/// Subtree counts by position key, one entry per slot, newer entries replace older ones.
+pub struct PerftTable {
+    entries: Vec<(u64, u64)>,
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb * 1024 * 1024 / 16).max(1);
+        PerftTable { entries: vec![(0, 0); len] }
+    }
+
+    fn slot(&self, key: u64, depth: usize) -> usize {
+        ((key ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) % self.entries.len() as u64) as usize
+    }
+
+    /// The lower 8 bits of the stored count hold the depth.
+    fn probe(&self, key: u64, depth: usize) -> Option<u64> {
+        let (entry_key, data) = self.entries[self.slot(key, depth)];
+        (entry_key == key && data & 0xFF == depth as u64).then_some(data >> 8)
+    }
+
+    fn store(&mut self, key: u64, depth: usize, nodes: u64) {
+        let slot = self.slot(key, depth);
+        self.entries[slot] = (key, nodes << 8 | depth as u64);
+    }
+}
+
+pub struct Perft<'a> {
+    move_gen: &'a MoveGenService,
+    config: Config,
+    bulk: bool,
+    table: Option<PerftTable>,
+    zobrist_table: ZobristTable,
+    stop_flag: AtomicBool,
+    pv_nodes: Mutex<HashMap<u64, Turn>>,
+    history_table: [[u32; 64]; 64],
+}
+
+impl<'a> Perft<'a> {
+    /// A hash size of 0 counts without the table.
+    pub fn new(move_gen: &'a MoveGenService, config: &Config, bulk: bool, hash_mb: usize) -> Self {
+        let mut config = config.clone();
+        config.use_underpromotions = true;
+        config.use_pv_nodes = false;
+        Perft {
+            move_gen,
+            config,
+            bulk,
+            table: (hash_mb > 0).then(|| PerftTable::new(hash_mb)),
+            zobrist_table: ZobristTable::with_capacity(1),
+            stop_flag: AtomicBool::new(false),
+            pv_nodes: Mutex::new(HashMap::new()),
+            history_table: [[0u32; 64]; 64],
+        }
+    }
+
+    /// Leaf nodes below the position at the depth.
+    pub fn count(&mut self, board: &Board, depth: usize) -> u64 {
+        self.divide(board, depth).iter().map(|(_, nodes)| nodes).sum::<u64>().max(if depth == 0 { 1 } else { 0 })
+    }
+
+    /// Leaf nodes below each legal move of the position, in generation order.
+    pub fn divide(&mut self, board: &Board, depth: usize) -> Vec<(Turn, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let mut board = board.clone();
+        board.game_status = GameStatus::Normal;
+        board.move_repetition_map.clear();
+        let context = SearchContext {
+            zobrist_table: &self.zobrist_table,
+            stop_flag: &self.stop_flag,
+            pv_nodes: &self.pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &self.history_table,
+            counter_move: None,
+            start_time: Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+        let mut stats = Stats::new();
+        let mut move_list = MoveList::new();
+        self.move_gen.generate_valid_moves_list(&mut board, &mut stats, &self.config, &context, false, false, &mut move_list);
+        move_list.as_slice().iter().map(|turn| {
+            let move_information = board.do_move(turn);
+            board.game_status = GameStatus::Normal;
+            let nodes = perft(self.move_gen, &mut board, depth - 1, &self.config, &context, &mut stats, self.bulk, self.table.as_mut());
+            board.undo_move(turn, move_information);
+            (*turn, nodes)
+        }).collect()
+    }
+}
+
+fn perft(move_gen: &MoveGenService, board: &mut Board, depth: usize, config: &Config, context: &SearchContext, stats: &mut Stats,
+         bulk: bool, mut table: Option<&mut PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if let Some(nodes) = table.as_ref().and_then(|table| table.probe(board.cached_hash, depth)) {
+        return nodes;
+    }
+
+    let mut move_list = MoveList::new();
+    move_gen.generate_valid_moves_list(board, stats, config, context, false, false, &mut move_list);
+    let nodes = if bulk && depth == 1 {
+        move_list.len as u64
+    } else {
+        let mut nodes = 0;
+        for turn in move_list.as_slice() {
+            let move_information = board.do_move(turn);
+            board.game_status = GameStatus::Normal;
+            nodes += perft(move_gen, board, depth - 1, config, context, stats, bulk, table.as_deref_mut());
+            board.undo_move(turn, move_information);
+        }
+        nodes
+    };
+
+    if let Some(table) = table {
+        table.store(board.cached_hash, depth, nodes);
+    }
+    nodes
+}
+
+/// Divide output: one `<move>: <nodes>` line per legal move, the total and the speed.
+pub fn divide_report(perft: &mut Perft, board: &Board, depth: usize) -> Vec<String> {
+    let start = Instant::now();
+    let divide = perft.divide(board, depth);
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    let nodes: u64 = if depth == 0 { 1 } else { divide.iter().map(|(_, nodes)| nodes).sum() };
+    let mut lines: Vec<String> = divide.iter().map(|(turn, nodes)| format!("{}: {}", turn.to_algebraic(), nodes)).collect();
+    lines.push(String::new());
+    lines.push(format!("Nodes searched: {}", nodes));
+    lines.push(format!("Time {} ms, NPS {}", elapsed_ms, nodes * 1000 / elapsed_ms.max(1)));
+    lines
+}
+
+/// Runs the standard positions up to `max_depth` and returns the number of failed counts.
+pub fn run_suite(move_gen: &MoveGenService, config: &Config, bulk: bool, hash_mb: usize, max_depth: usize) -> usize {
+    let mut failures = 0;
+    let mut perft = Perft::new(move_gen, config, bulk, hash_mb);
+    for (name, fen, expected) in PERFT_SUITE {
+        let board = FenService.set_fen(fen);
+        for (depth, expected) in expected.iter().enumerate().map(|(i, nodes)| (i + 1, *nodes)).take_while(|(depth, _)| *depth <= max_depth) {
+            let start = Instant::now();
+            let nodes = perft.count(&board, depth);
+            let elapsed_ms = start.elapsed().as_millis() as u64;
+            let status = if nodes == expected { "ok" } else { "FAILED" };
+            if nodes != expected {
+                failures += 1;
+            }
+            println!("{:<12} depth {} nodes {:>10} expected {:>10} {:>6} ms {:>10} nps  {}", name, depth, nodes, expected,
+                elapsed_ms, nodes * 1000 / elapsed_ms.max(1), status);
+        }
+    }
+    failures
+}
+
+/// Entry point of `suprah perft <fen>|startpos <depth> [--bulk] [--hash <mb>]` and
+/// `suprah perft suite [<max depth>] [--bulk] [--hash <mb>]`.
+pub fn run(args: &[String]) {
+    let usage = "usage: suprah perft <fen>|startpos <depth> [--bulk] [--hash <mb>]\n       suprah perft suite [<max depth>] [--bulk] [--hash <mb>]";
+    let fail = |msg: &str| -> ! {
+        eprintln!("{}", msg);
+        std::process::exit(2);
+    };
+    let (mut bulk, mut hash_mb) = (false, 0);
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--bulk" => bulk = true,
+            "--hash" => hash_mb = iter.next().and_then(|value| value.parse().ok()).unwrap_or_else(|| fail(usage)),
+            _ => words.extend(arg.split_whitespace()),
+        }
+    }
+
+    let move_gen = MoveGenService::new();
+    let config = Config::new();
+    if words.first() == Some(&"suite") {
+        let max_depth = words.get(1).map_or(Some(usize::MAX), |depth| depth.parse().ok()).unwrap_or_else(|| fail(usage));
+        let failures = run_suite(&move_gen, &config, bulk, hash_mb, max_depth);
+        println!("{}", if failures == 0 { "all counts ok".to_string() } else { format!("{} counts FAILED", failures) });
+        std::process::exit(if failures == 0 { 0 } else { 1 });
+    }
+
+    let Some((depth, position)) = words.split_last() else {
+        fail(usage);
+    };
+    let depth: usize = depth.parse().unwrap_or_else(|_| fail(usage));
+    let fen = match position.join(" ").as_str() {
+        "" => fail(usage),
+        "startpos" => crate::pgn::START_FEN.to_string(),
+        fen => fen.to_string(),
+    };
+    let board = FenService.set_fen(&fen);
+    let mut perft = Perft::new(&move_gen, &config, bulk, hash_mb);
+    for line in divide_report(&mut perft, &board, depth) {
+        println!("{}", line);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perft_modes_test() {
+        let move_gen = MoveGenService::new();
+        let config = Config::for_tests();
+        for (bulk, hash_mb) in [(false, 0), (true, 0), (true, 1)] {
+            let mut perft = Perft::new(&move_gen, &config, bulk, hash_mb);
+            for (name, fen, expected) in PERFT_SUITE {
+                let board = FenService.set_fen(fen);
+                assert_eq!(expected[2], perft.count(&board, 3), "{} bulk {} hash {}", name, bulk, hash_mb);
+            }
+        }
+    }
+
+    #[test]
+    fn divide_report_test() {
+        let move_gen = MoveGenService::new();
+        let mut perft = Perft::new(&move_gen, &Config::for_tests(), true, 0);
+        let board = FenService.set_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+        let lines = divide_report(&mut perft, &board, 2);
+        assert_eq!(14 + 3, lines.len());
+        assert!(lines[..14].iter().all(|line| line.split_once(": ").is_some_and(|(_, nodes)| nodes.parse::<u64>().is_ok())));
+        assert_eq!("Nodes searched: 191", lines[15]);
+        assert_eq!(1, perft.count(&board, 0));
+
+        // a repeated position does not end the line
+        let mut board = FenService.set_fen("8/8/8/8/8/8/8/k1K5 w - - 99 80");
+        board.move_repetition_map.insert(board.cached_hash, 2);
+        assert_eq!(perft.count(&FenService.set_fen("8/8/8/8/8/8/8/k1K5 w - - 0 1"), 3), perft.count(&board, 3));
+    }
+}
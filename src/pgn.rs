@@ -0,0 +1,654 @@
+//! PGN reading and writing.
+//!
+//! `parse_pgn` reads tag pairs and movetext with comments, NAGs and variations. `san_to_turn` and
+//! `turn_to_san` convert between SAN and `Turn` through the legal move generation, `write_pgn`
+//! formats games again.
+
+use crate::config::Config;
+use crate::fen_service::FenService;
+use crate::model::{Board, Turn};
+use crate::move_gen_service::MoveGenService;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// A move of a PGN movetext with its annotations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric annotation glyphs, "!" and "?" suffixes are read as $1 to $6.
+    pub nags: Vec<u8>,
+    /// Comment following the move.
+    pub comment: Option<String>,
+    /// Alternatives to this move, each starting at its position.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(san: &str) -> Self {
+        PgnMove { san: san.to_string(), ..Default::default() }
+    }
+}
+
+/// One game of a PGN file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// Comment before the first move.
+    pub comment: Option<String>,
+    /// Main line.
+    pub moves: Vec<PgnMove>,
+    /// Result token of the movetext ("1-0", "0-1", "1/2-1/2" or "*").
+    pub result: String,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Replaces the value of a tag or appends the tag.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Start position from the FEN tag, the initial position otherwise.
+    pub fn start_fen(&self) -> &str {
+        self.tag("FEN").unwrap_or(START_FEN)
+    }
+
+    /// Game of the moves played from the board, with the Seven Tag Roster and a FEN tag for
+    /// other start positions.
+    pub fn from_turns(board: &Board, turns: &[Turn], result: &str, move_gen: &MoveGenService, config: &Config) -> Self {
+        let mut game = PgnGame::default();
+        for (name, value) in [("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"), ("White", "?"), ("Black", "?"), ("Result", result)] {
+            game.set_tag(name, value);
+        }
+        let fen = FenService.get_fen(board);
+        if fen != START_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game.moves = moves_to_san(board, turns, move_gen, config).iter().map(|san| PgnMove::new(san)).collect();
+        game.result = result.to_string();
+        game
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.comment.is_none() && self.moves.is_empty() && self.result.is_empty()
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// Value of a quoted tag string, "\\" and "\"" escape backslash and quote.
+fn unescape_tag_value(quoted: &str) -> String {
+    let value = quoted.strip_prefix('"').and_then(|value| value.strip_suffix('"')).unwrap_or(quoted);
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Strips a move number, possibly glued to the move as in "12.Nf3", "12...Nf6" or "12.0-0".
+fn strip_move_number(token: &str) -> &str {
+    let digits = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    if digits.starts_with('.') {
+        digits.trim_start_matches('.')
+    } else {
+        token
+    }
+}
+
+/// Splits "!", "?", "!!", "??", "!?" and "?!" off a SAN token as NAG.
+fn split_suffix_annotation(token: &str) -> (&str, Option<u8>) {
+    let san = token.trim_end_matches(['!', '?']);
+    let nag = match &token[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    (san, nag)
+}
+
+/// Moves of the main line and of the open variations while reading a game.
+struct LineStack {
+    lines: Vec<Vec<PgnMove>>,
+}
+
+impl LineStack {
+    fn current(&mut self) -> &mut Vec<PgnMove> {
+        self.lines.last_mut().expect("RIP pgn line stack is never empty")
+    }
+
+    fn close_variation(&mut self) {
+        if self.lines.len() > 1 {
+            let variation = self.lines.pop().expect("RIP pgn line stack is never empty");
+            if let Some(mv) = self.current().last_mut() && !variation.is_empty() {
+                mv.variations.push(variation);
+            }
+        }
+    }
+
+    /// Main line of the game, unclosed variations are closed.
+    fn take_main_line(&mut self) -> Vec<PgnMove> {
+        while self.lines.len() > 1 {
+            self.close_variation();
+        }
+        std::mem::take(self.current())
+    }
+}
+
+fn finish_game(games: &mut Vec<PgnGame>, game: &mut PgnGame, lines: &mut LineStack) {
+    game.moves = lines.take_main_line();
+    games.push(std::mem::take(game));
+}
+
+/// Parses all games of a PGN text with comments, NAGs and variations. Comments at the start of a
+/// variation are dropped.
+pub fn parse_pgn(content: &str) -> Vec<PgnGame> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::default();
+    let mut lines = LineStack { lines: vec![Vec::new()] };
+    let mut chars = content.chars().peekable();
+    let mut at_line_start = true;
+
+    while let Some(c) = chars.next() {
+        let line_start = at_line_start;
+        at_line_start = c == '\n';
+        match c {
+            // escape line
+            '%' if line_start => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        at_line_start = true;
+                        break;
+                    }
+                }
+            },
+            '[' if lines.lines.len() == 1 => {
+                let tag: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                if !lines.current().is_empty() || !game.result.is_empty() {
+                    finish_game(&mut games, &mut game, &mut lines);
+                }
+                if let Some((name, value)) = tag.trim().split_once(char::is_whitespace) {
+                    game.tags.push((name.to_string(), unescape_tag_value(value.trim())));
+                }
+            },
+            '{' | ';' => {
+                let end = if c == '{' { '}' } else { '\n' };
+                let comment: String = chars.by_ref().take_while(|c| *c != end).collect();
+                at_line_start = end == '\n';
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                let is_main_line = lines.lines.len() == 1;
+                let target = match lines.current().last_mut() {
+                    Some(mv) => &mut mv.comment,
+                    None if is_main_line => &mut game.comment,
+                    None => continue,
+                };
+                *target = Some(match target.take() {
+                    Some(previous) => format!("{} {}", previous, comment),
+                    None => comment,
+                });
+            },
+            '(' => lines.lines.push(Vec::new()),
+            ')' => lines.close_variation(),
+            c if c.is_whitespace() => {},
+            c => {
+                let mut token = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}()[];".contains(*c)) {
+                    token.push(c);
+                }
+                if let Some(nag) = token.strip_prefix('$') {
+                    if let (Ok(nag), Some(mv)) = (nag.parse(), lines.current().last_mut()) {
+                        mv.nags.push(nag);
+                    }
+                    continue;
+                }
+                if is_result(&token) {
+                    if lines.lines.len() == 1 {
+                        game.result = token;
+                        finish_game(&mut games, &mut game, &mut lines);
+                    }
+                    continue;
+                }
+                let (san, nag) = split_suffix_annotation(strip_move_number(&token));
+                if !san.is_empty() {
+                    let mut mv = PgnMove::new(san);
+                    mv.nags.extend(nag);
+                    lines.current().push(mv);
+                }
+            },
+        }
+    }
+    game.moves = lines.take_main_line();
+    if !game.is_empty() {
+        games.push(game);
+    }
+    games
+}
+
+/// Plies played before the start position, from the side to move and fullmove fields of the FEN.
+fn start_ply(fen: &str) -> usize {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let fullmove = fields.get(5).and_then(|n| n.parse::<usize>().ok()).unwrap_or(1).max(1);
+    (fullmove - 1) * 2 + (fields.get(1) == Some(&"b")) as usize
+}
+
+fn movetext_tokens(moves: &[PgnMove], first_ply: usize, tokens: &mut Vec<String>) {
+    let mut needs_number = true;
+    for (i, mv) in moves.iter().enumerate() {
+        let ply = first_ply + i;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if needs_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(mv.san.clone());
+        tokens.extend(mv.nags.iter().map(|nag| format!("${}", nag)));
+        needs_number = false;
+
+        if let Some(comment) = &mv.comment {
+            tokens.extend(format!("{{{}}}", comment).split_whitespace().map(String::from));
+            needs_number = true;
+        }
+        for variation in &mv.variations {
+            let start = tokens.len();
+            movetext_tokens(variation, ply, tokens);
+            tokens[start].insert(0, '(');
+            tokens.last_mut().expect("RIP variation without moves").push(')');
+            needs_number = true;
+        }
+    }
+}
+
+/// Formats a game as PGN: the tags in their order, then the movetext wrapped at 80 columns.
+pub fn write_pgn(game: &PgnGame) -> String {
+    let mut pgn = String::new();
+    for (name, value) in &game.tags {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    if let Some(comment) = &game.comment {
+        tokens.extend(format!("{{{}}}", comment).split_whitespace().map(String::from));
+    }
+    movetext_tokens(&game.moves, start_ply(game.start_fen()), &mut tokens);
+    tokens.push(if game.result.is_empty() { "*".to_string() } else { game.result.clone() });
+
+    let mut line_len = 0;
+    for token in tokens {
+        if line_len > 0 && line_len + 1 + token.len() > 80 {
+            pgn.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            pgn.push(' ');
+            line_len += 1;
+        }
+        line_len += token.len();
+        pgn.push_str(&token);
+    }
+    pgn.push_str("\n\n");
+    pgn
+}
+
+fn piece_letter(piece: u8) -> char {
+    match piece % 10 {
+        1 => 'R',
+        2 => 'N',
+        3 => 'B',
+        4 => 'Q',
+        5 => 'K',
+        _ => 'P',
+    }
+}
+
+fn promotion_letter(promotion: u8) -> Option<char> {
+    match promotion % 10 {
+        1 => Some('R'),
+        2 => Some('N'),
+        3 => Some('B'),
+        4 => Some('Q'),
+        _ => None,
+    }
+}
+
+fn is_castling(board: &Board, turn: &Turn) -> bool {
+    let piece = board.get_piece_at(turn.from);
+    piece % 10 == 5 && (turn.from.abs_diff(turn.to) == 2 || board.get_piece_at(turn.to) == piece - 4)
+}
+
+/// Finds the legal move written in SAN, None if the move is illegal or ambiguous.
+pub fn san_to_turn(board: &mut Board, san: &str, move_gen: &MoveGenService, config: &Config) -> Option<Turn> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    // underpromotions are legal even when the search skips them
+    let mut move_config = config.clone();
+    move_config.use_underpromotions = true;
+    let legal = move_gen.legal_moves(board, &move_config);
+
+    let castle_short = matches!(san, "O-O" | "0-0");
+    if castle_short || matches!(san, "O-O-O" | "0-0-0") {
+        return legal.into_iter().find(|turn| is_castling(board, turn) && (turn.to > turn.from) == castle_short);
+    }
+
+    let (body, promotion) = match san.split_once('=') {
+        Some((body, promotion)) => (body, promotion.chars().next()),
+        None if san.len() > 2 && san.ends_with(['Q', 'R', 'B', 'N']) => (&san[..san.len() - 1], san.chars().last()),
+        None => (san, None),
+    };
+    if body.len() < 2 || !body.is_ascii() {
+        return None;
+    }
+    let piece = match body.chars().next() {
+        Some(c) if "NBRQK".contains(c) => c,
+        _ => 'P',
+    };
+    let target = &body[body.len() - 2..];
+    let file = target.as_bytes()[0].wrapping_sub(b'a');
+    let rank = target.as_bytes()[1].wrapping_sub(b'1');
+    if file > 7 || rank > 7 {
+        return None;
+    }
+    let to = rank * 8 + file;
+    let disambiguation: Vec<char> = body[if piece == 'P' { 0 } else { 1 }..body.len() - 2].chars().filter(|c| *c != 'x').collect();
+
+    let mut candidates = legal.into_iter().filter(|turn| {
+        turn.to == to
+            && piece_letter(board.get_piece_at(turn.from)) == piece
+            && promotion_letter(turn.promotion) == promotion
+            && !is_castling(board, turn)
+            && disambiguation.iter().all(|c| match c {
+                'a'..='h' => turn.from % 8 == *c as u8 - b'a',
+                '1'..='8' => turn.from / 8 == *c as u8 - b'1',
+                _ => false,
+            })
+    });
+    let turn = candidates.next()?;
+    candidates.next().is_none().then_some(turn)
+}
+
+fn square_name(square: u8) -> String {
+    format!("{}{}", (b'a' + square % 8) as char, (b'1' + square / 8) as char)
+}
+
+/// SAN of a legal move, with "+" or "#" when it gives check or mate.
+pub fn turn_to_san(board: &mut Board, turn: &Turn, move_gen: &MoveGenService, config: &Config) -> String {
+    let mut move_config = config.clone();
+    move_config.use_underpromotions = true;
+    let mut san = if is_castling(board, turn) {
+        if turn.to > turn.from { "O-O" } else { "O-O-O" }.to_string()
+    } else {
+        let piece = piece_letter(board.get_piece_at(turn.from));
+        let capture = board.get_piece_at(turn.to) != 0 || (piece == 'P' && turn.from % 8 != turn.to % 8);
+        let mut san = String::new();
+        if piece == 'P' {
+            if capture {
+                san.push((b'a' + turn.from % 8) as char);
+            }
+        } else {
+            san.push(piece);
+            let legal = move_gen.legal_moves(board, &move_config);
+            let rivals: Vec<&Turn> = legal.iter()
+                .filter(|t| t.to == turn.to && t.from != turn.from && piece_letter(board.get_piece_at(t.from)) == piece && !is_castling(board, t))
+                .collect();
+            if !rivals.is_empty() {
+                let from = square_name(turn.from);
+                if rivals.iter().all(|t| t.from % 8 != turn.from % 8) {
+                    san.push_str(&from[..1]);
+                } else if rivals.iter().all(|t| t.from / 8 != turn.from / 8) {
+                    san.push_str(&from[1..]);
+                } else {
+                    san.push_str(&from);
+                }
+            }
+        }
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&square_name(turn.to));
+        if let Some(promotion) = promotion_letter(turn.promotion) {
+            san.push('=');
+            san.push(promotion);
+        }
+        san
+    };
+
+    let move_information = board.do_move(turn);
+    if move_gen.is_in_check(board) {
+        san.push(if move_gen.legal_moves(board, &move_config).is_empty() { '#' } else { '+' });
+    }
+    board.undo_move(turn, move_information);
+    san
+}
+
+/// SAN of a sequence of legal moves played from the board.
+pub fn moves_to_san(board: &Board, turns: &[Turn], move_gen: &MoveGenService, config: &Config) -> Vec<String> {
+    let mut board = board.clone();
+    turns.iter().map(|turn| {
+        let san = turn_to_san(&mut board, turn, move_gen, config);
+        board.do_move(turn);
+        san
+    }).collect()
+}
+
+/// Replays the main line and rewrites its SAN as `turn_to_san` writes it. Fails at the first
+/// illegal or ambiguous move.
+pub fn normalize_game(game: &mut PgnGame, move_gen: &MoveGenService, config: &Config) -> Result<(), String> {
+    let mut board = FenService.set_fen(game.start_fen());
+    for (ply, mv) in game.moves.iter_mut().enumerate() {
+        let turn = san_to_turn(&mut board, &mv.san, move_gen, config)
+            .ok_or(format!("illegal or ambiguous move {} at ply {}", mv.san, ply + 1))?;
+        mv.san = turn_to_san(&mut board, &turn, move_gen, config);
+        board.do_move(&turn);
+    }
+    Ok(())
+}
+
+/// Entry point of `suprah pgn <pgn>... [--out <file>]`, writes the games with checked and
+/// normalized SAN. Games with unreadable moves are skipped.
+pub fn run(args: &[String]) {
+    let mut files = Vec::new();
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--out" => out = iter.next().cloned(),
+            _ => files.push(arg.clone()),
+        }
+    }
+    if files.is_empty() || args.last().map(String::as_str) == Some("--out") {
+        eprintln!("usage: suprah pgn <pgn>... [--out <file>]");
+        std::process::exit(2);
+    }
+
+    let move_gen = MoveGenService::new();
+    let config = Config::new();
+    let mut written = String::new();
+    let (mut games_written, mut games_skipped) = (0, 0);
+    for path in &files {
+        let content = std::fs::read(path).unwrap_or_else(|e| {
+            eprintln!("could not read {}: {}", path, e);
+            std::process::exit(2);
+        });
+        for (i, mut game) in parse_pgn(&String::from_utf8_lossy(&content)).into_iter().enumerate() {
+            match normalize_game(&mut game, &move_gen, &config) {
+                Ok(()) => {
+                    written.push_str(&write_pgn(&game));
+                    games_written += 1;
+                },
+                Err(msg) => {
+                    eprintln!("{} game {}: {}, skipped", path, i + 1, msg);
+                    games_skipped += 1;
+                },
+            }
+        }
+    }
+
+    match out {
+        Some(out) => {
+            if let Err(e) = std::fs::write(&out, written) {
+                eprintln!("could not write {}: {}", out, e);
+                std::process::exit(2);
+            }
+            eprintln!("{} games written to {}, {} skipped", games_written, out, games_skipped);
+        },
+        None => print!("{}", written),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    #[test]
+    fn parse_pgn_test() {
+        let pgn = "[Event \"Test\"]\n[White \"A\"]\n[WhiteElo \"2400\"]\n\n1. e4 {best by test} e5 2.Nf3 (2. f4 exf4) Nc6 $1 3. Bb5 a6; Ruy\n4...Nf6 1-0\n\n\
+                   % escaped line\n[Event \"Second\"]\n[FEN \"8/8/8/8/8/8/4k3/4K3 w - - 0 1\"]\n\n1. Kd1 *";
+        let games = parse_pgn(pgn);
+        assert_eq!(2, games.len());
+        assert_eq!(Some("2400"), games[0].tag("WhiteElo"));
+        let sans: Vec<&str> = games[0].moves.iter().map(|mv| mv.san.as_str()).collect();
+        assert_eq!(vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Nf6"], sans);
+        assert_eq!("1-0", games[0].result);
+        assert_eq!(START_FEN, games[0].start_fen());
+        assert_eq!("8/8/8/8/8/8/4k3/4K3 w - - 0 1", games[1].start_fen());
+        assert_eq!(vec![PgnMove::new("Kd1")], games[1].moves);
+        assert_eq!("*", games[1].result);
+
+        // castling written with zeros, with and without a glued move number
+        let games = parse_pgn("[FEN \"r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 11\"]\n\n11... 0-0 12.0-0-0?! Rf2 *");
+        let sans: Vec<&str> = games[0].moves.iter().map(|mv| mv.san.as_str()).collect();
+        assert_eq!(vec!["0-0", "0-0-0", "Rf2"], sans);
+        assert_eq!(vec![6], games[0].moves[1].nags);
+    }
+
+    #[test]
+    fn san_to_turn_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let move_gen = &service.move_gen;
+
+        let mut board = service.fen.set_init_board();
+        for (san, expected) in [("e4", "e2e4"), ("e5", "e7e5"), ("Nf3", "g1f3"), ("Nc6", "b8c6"), ("Bc4", "f1c4"), ("Nf6", "g8f6"), ("O-O", "e1g1")] {
+            let turn = san_to_turn(&mut board, san, move_gen, &config).unwrap_or_else(|| panic!("RIP no move for {}", san));
+            assert_eq!(expected, turn.to_algebraic());
+            board.do_move(&turn);
+        }
+        assert_eq!(None, san_to_turn(&mut board, "Ke6", move_gen, &config));
+
+        // disambiguation, captures, promotion and check suffixes
+        let mut board = service.fen.set_fen("1r5k/P7/8/8/8/R6R/8/R3K3 w Q - 0 1");
+        assert_eq!(None, san_to_turn(&mut board, "Rd3", move_gen, &config));
+        assert_eq!("a3d3", san_to_turn(&mut board, "Rad3", move_gen, &config).unwrap().to_algebraic());
+        assert_eq!("h3f3", san_to_turn(&mut board, "Rhf3", move_gen, &config).unwrap().to_algebraic());
+        assert_eq!("a1a2", san_to_turn(&mut board, "R1a2", move_gen, &config).unwrap().to_algebraic());
+        assert_eq!("a7b8q", san_to_turn(&mut board, "axb8=Q+", move_gen, &config).unwrap().to_algebraic());
+        assert_eq!("a7a8n", san_to_turn(&mut board, "a8N", move_gen, &config).unwrap().to_algebraic());
+        assert_eq!(None, san_to_turn(&mut board, "a8", move_gen, &config));
+        assert_eq!("e1c1", san_to_turn(&mut board, "O-O-O", move_gen, &config).unwrap().to_algebraic());
+    }
+
+    #[test]
+    fn parse_pgn_annotations_test() {
+        let pgn = "{Opening} 1. e4 e5!? 2. Nf3 $1 {main} {line} (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 ; Ruy next\n3. Bb5 1-0";
+        let game = &parse_pgn(pgn)[0];
+        assert_eq!(Some("Opening"), game.comment.as_deref());
+        assert_eq!(5, game.moves.len());
+        assert_eq!(("e5", vec![5]), (game.moves[1].san.as_str(), game.moves[1].nags.clone()));
+
+        let nf3 = &game.moves[2];
+        assert_eq!(vec![1], nf3.nags);
+        assert_eq!(Some("main line"), nf3.comment.as_deref());
+        assert_eq!(1, nf3.variations.len());
+        let variation = &nf3.variations[0];
+        assert_eq!(vec!["f4", "exf4", "Nf3"], variation.iter().map(|mv| mv.san.as_str()).collect::<Vec<_>>());
+        assert_eq!(vec![vec![PgnMove::new("d5")]], variation[1].variations);
+        assert_eq!(Some("Ruy next"), game.moves[3].comment.as_deref());
+    }
+
+    #[test]
+    fn turn_to_san_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let move_gen = &service.move_gen;
+        let san = |fen: &str, notation: &str| {
+            let mut board = service.fen.set_fen(fen);
+            let turn = move_gen.legal_moves(&mut board, &config).into_iter().find(|turn| turn.to_algebraic() == notation).unwrap();
+            turn_to_san(&mut board, &turn, move_gen, &config)
+        };
+
+        let board = "1r4k1/P7/8/8/8/R6R/8/R3K3 w Q - 0 1";
+        assert_eq!("Rad3", san(board, "a3d3"));
+        assert_eq!("R1a2", san(board, "a1a2"));
+        assert_eq!("Rh4", san(board, "h3h4"));
+        assert_eq!("axb8=Q+", san(board, "a7b8q"));
+        assert_eq!("a8=N", san(board, "a7a8n"));
+        assert_eq!("O-O-O", san(board, "e1c1"));
+        // three queens need the full square
+        assert_eq!("Qa1b2", san("8/7k/8/8/8/8/Q7/Q1Q3K1 w - - 0 1", "a1b2"));
+        assert_eq!("exd6", san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"));
+        assert_eq!("Qxf7#", san("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "f3f7"));
+
+        // every SAN written is read back as the same move
+        let mut board = service.fen.set_fen("r3k2r/pPpp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+        for turn in move_gen.legal_moves(&mut board, &config) {
+            let san = turn_to_san(&mut board, &turn, move_gen, &config);
+            assert_eq!(Some(turn.to_algebraic()), san_to_turn(&mut board, &san, move_gen, &config).map(|t| t.to_algebraic()), "{}", san);
+        }
+    }
+
+    #[test]
+    fn underpromotion_test() {
+        let service = Service::new();
+        // the tools run with the engine defaults, which skip underpromotions in the search
+        let config = Config::new();
+        assert!(!config.use_underpromotions);
+        let move_gen = &service.move_gen;
+
+        let mut board = service.fen.set_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!("a7a8r", san_to_turn(&mut board, "a8=R", move_gen, &config).unwrap().to_algebraic());
+        let turn = san_to_turn(&mut board, "a8=B", move_gen, &config).unwrap();
+        assert_eq!("a7a8b", turn.to_algebraic());
+        assert_eq!("a8=B", turn_to_san(&mut board, &turn, move_gen, &config));
+
+        let mut game = parse_pgn("[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n\n1. a8=R+ Kd7 *").remove(0);
+        normalize_game(&mut game, move_gen, &config).unwrap();
+        assert_eq!("a8=R+", game.moves[0].san);
+    }
+
+    #[test]
+    fn write_pgn_roundtrip_test() {
+        let service = Service::new();
+        let config = Config::for_tests();
+        let pgn = "[Event \"Test \\\"quoted\\\"\"]\n[FEN \"r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3\"]\n\n\
+                   3. bb5 a6 $2 {long comment that keeps going and going so that the movetext needs more than one line} \
+                   (3... Nf6 4. O-O) 4. Ba4 Nf6 5. O-O 1/2-1/2";
+        let mut game = parse_pgn(pgn).remove(0);
+        assert!(normalize_game(&mut game, &service.move_gen, &config).is_err());
+
+        game.moves[0].san = "Bb5".to_string();
+        normalize_game(&mut game, &service.move_gen, &config).unwrap();
+        let written = write_pgn(&game);
+        assert!(written.starts_with("[Event \"Test \\\"quoted\\\"\"]\n[FEN "));
+        assert!(written.contains("3. Bb5 a6 $2 {long comment"));
+        assert!(written.contains("(3... Nf6 4. O-O) 4. Ba4 Nf6 5. O-O 1/2-1/2\n\n"));
+        assert!(written.lines().all(|line| line.len() <= 80));
+        assert_eq!(vec![game], parse_pgn(&written));
+    }
+}
@@ -1,10 +1,12 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
 use once_cell::sync::Lazy;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, SeedableRng};
 
+use crate::config::{BookSelection, Config};
 use crate::model::Board;
 
 /// 64-bit random matrix used to generate PolyGlot Zobrist keys.
@@ -78,6 +80,38 @@ impl PolyglotEntry {
 
         format!("{}{}{}{}{}", from_char, from_num, to_char, to_num, promo_char)
     }
+
+    /// Encodes a move of the board as PolyGlot move integer, castling as king captures rook.
+    pub fn encode_move(board: &Board, turn: &crate::model::Turn) -> u16 {
+        let piece = board.get_piece_at(turn.from);
+        let to = if (piece == 15 || piece == 25) && turn.from.abs_diff(turn.to) == 2 {
+            if turn.to > turn.from { turn.from + 3 } else { turn.from - 4 }
+        } else {
+            turn.to
+        };
+        let promotion = match turn.promotion % 10 {
+            2 => 1,
+            3 => 2,
+            1 => 3,
+            4 => 4,
+            _ => 0,
+        };
+        (promotion << 12) | ((turn.from as u16) << 6) | to as u16
+    }
+
+    /// Move notation for the given board. PolyGlot encodes castling as king captures rook,
+    /// which standard boards expect as a two square king move.
+    pub fn to_algebraic_for_board(&self, board: &Board) -> String {
+        let to_sq = (self.mov & 0x3F) as u8;
+        let from_sq = ((self.mov >> 6) & 0x3F) as u8;
+        let king = board.get_piece_at(from_sq);
+        let own_rook = if king == 15 { 11 } else { 21 };
+        if !board.chess960 && (king == 15 || king == 25) && board.get_piece_at(to_sq) == own_rook {
+            let king_to = if to_sq > from_sq { from_sq + 2 } else { from_sq - 2 };
+            return crate::model::Turn::_new_to_from(from_sq, king_to).to_algebraic();
+        }
+        self.to_algebraic()
+    }
 }
 
 /// Maps Suprah internal piece representation to PolyGlot piece index (0..11).
@@ -187,25 +221,18 @@ impl PolyglotBook {
         let mut file = File::open(path)?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)?;
+        Ok(Self::from_bytes(&buffer))
+    }
 
-        let num_entries = buffer.len() / 16;
-        let mut entries = Vec::with_capacity(num_entries);
-
-        for chunk in buffer.chunks_exact(16) {
-            let key = u64::from_be_bytes(chunk[0..8].try_into().unwrap());
-            let mov = u16::from_be_bytes(chunk[8..10].try_into().unwrap());
-            let weight = u16::from_be_bytes(chunk[10..12].try_into().unwrap());
-            let learn = u32::from_be_bytes(chunk[12..16].try_into().unwrap());
-
-            entries.push(PolyglotEntry {
-                key,
-                mov,
-                weight,
-                learn,
-            });
-        }
-
-        Ok(Self { entries })
+    /// Reads the entries of PolyGlot `.bin` content, a trailing partial entry is ignored.
+    pub fn from_bytes(buffer: &[u8]) -> Self {
+        let entries = buffer.chunks_exact(16).map(|chunk| PolyglotEntry {
+            key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+            mov: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+            weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap()),
+            learn: u32::from_be_bytes(chunk[12..16].try_into().unwrap()),
+        }).collect();
+        Self { entries }
     }
 
     /// Creates an in-memory PolyGlotBook from a slice of entries.
@@ -213,6 +240,49 @@ impl PolyglotBook {
         Self { entries }
     }
 
+    pub fn entries(&self) -> &[PolyglotEntry] {
+        &self.entries
+    }
+
+    /// Writes the entries as PolyGlot `.bin` file, sorted by key and descending weight. The content
+    /// goes to a temporary file first that replaces the book, readers never see a partial file.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.mov.cmp(&b.mov)));
+        let mut buffer = Vec::with_capacity(entries.len() * 16);
+        for entry in entries {
+            buffer.extend_from_slice(&entry.key.to_be_bytes());
+            buffer.extend_from_slice(&entry.mov.to_be_bytes());
+            buffer.extend_from_slice(&entry.weight.to_be_bytes());
+            buffer.extend_from_slice(&entry.learn.to_be_bytes());
+        }
+        let path = path.as_ref();
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&buffer)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    }
+
+    /// Records the score (1, 0.5, 0) of a game in which the entry was played. The learn field
+    /// holds the games in the upper and the points (2 per win, 1 per draw) in the lower 16 bits,
+    /// the weight grows by an eighth after a win and shrinks by a quarter after a loss.
+    pub fn learn(&mut self, key: u64, mov: u16, score: f32) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.key == key && e.mov == mov) else {
+            return false;
+        };
+        let games = (entry.learn >> 16).saturating_add(1).min(u16::MAX as u32);
+        let points = (entry.learn & 0xFFFF).saturating_add((score * 2.0).round() as u32).min(u16::MAX as u32);
+        entry.learn = games << 16 | points;
+        if score > 0.5 {
+            entry.weight = entry.weight.saturating_add((entry.weight / 8).max(1));
+        } else if score < 0.5 {
+            entry.weight = entry.weight.saturating_sub((entry.weight / 4).max(1));
+        }
+        true
+    }
+
     /// Finds all matching PolyGlot entries for a given Board position.
     pub fn find_entries(&self, board: &Board) -> &[PolyglotEntry] {
         let key = polyglot_key(board);
@@ -245,13 +315,47 @@ impl PolyglotBook {
 
         let mut rng = thread_rng();
         if let Ok(choice) = matching.choose_weighted(&mut rng, |e| e.weight as u32) {
-            choice.to_algebraic()
+            choice.to_algebraic_for_board(board)
         } else if let Some(first) = matching.first() {
-            first.to_algebraic()
+            first.to_algebraic_for_board(board)
         } else {
             String::new()
         }
     }
+
+    /// Selects a move by `config.book_selection` among the entries weighing at least
+    /// `config.book_min_weight`. A non zero `config.book_seed` makes the choice depend only on
+    /// seed and position.
+    pub fn select_book_move(&self, board: &Board, config: &Config) -> String {
+        let matching: Vec<&PolyglotEntry> = self.find_entries(board).iter()
+            .filter(|e| e.weight as i32 >= config.book_min_weight)
+            .collect();
+        if matching.is_empty() {
+            return String::new();
+        }
+
+        let mut rng = if config.book_seed == 0 {
+            StdRng::from_entropy()
+        } else {
+            StdRng::seed_from_u64(config.book_seed ^ polyglot_key(board))
+        };
+        let exponent = match config.book_selection {
+            BookSelection::Temperature => 100.0 / config.book_temperature.max(1) as f64,
+            _ => 1.0,
+        };
+        // relative to the largest weight, so sharp temperatures cannot overflow
+        let max_weight = matching.iter().map(|e| e.weight).max().unwrap_or(0).max(1) as f64;
+        let choice = match config.book_selection {
+            // ties go to the lowest move code, so the choice does not depend on the file order
+            BookSelection::Best => matching.iter().max_by(|a, b| a.weight.cmp(&b.weight).then(b.mov.cmp(&a.mov))),
+            BookSelection::Uniform => matching.choose(&mut rng),
+            BookSelection::Weighted | BookSelection::Temperature => matching
+                .choose_weighted(&mut rng, |e| (e.weight as f64 / max_weight).powf(exponent))
+                .ok()
+                .or(matching.first()),
+        };
+        choice.map(|e| e.to_algebraic_for_board(board)).unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -289,6 +393,17 @@ mod tests {
         assert_eq!(entry.to_algebraic(), "e2e4");
     }
 
+    #[test]
+    fn test_polyglot_castling_decoding() {
+        // e1h1: from e1 = (0 << 9) | (4 << 6), to h1 = (0 << 3) | 7
+        let entry = PolyglotEntry { key: 0, mov: 263, weight: 1, learn: 0 };
+        let service = Service::new();
+        let mut board = service.fen.set_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(entry.to_algebraic_for_board(&board), "e1g1");
+        board.chess960 = true;
+        assert_eq!(entry.to_algebraic_for_board(&board), "e1h1");
+    }
+
     #[test]
     fn test_polyglot_in_memory_book_lookup() {
         let service = Service::new();
@@ -307,4 +422,63 @@ mod tests {
         let mov = book.get_random_book_move(&board);
         assert_eq!(mov, "e2e4");
     }
+
+    #[test]
+    fn test_polyglot_book_selection() {
+        let service = Service::new();
+        let board = service.fen.set_init_board();
+        let key = polyglot_key(&board);
+        let entry = |mov, weight| PolyglotEntry { key, mov, weight, learn: 0 };
+        // e2e4, d2d4, c2c4
+        let book = PolyglotBook::from_entries(vec![entry(796, 50), entry(731, 200), entry(666, 1)]);
+
+        let mut config = Config::for_tests();
+        config.book_selection = BookSelection::Best;
+        assert_eq!("d2d4", book.select_book_move(&board, &config));
+
+        config.book_selection = BookSelection::Uniform;
+        config.book_min_weight = 10;
+        for _ in 0..20 {
+            assert_ne!("c2c4", book.select_book_move(&board, &config));
+        }
+        config.book_min_weight = 201;
+        assert_eq!("", book.select_book_move(&board, &config));
+
+        // a seed fixes the choice, a low temperature approaches the best move
+        config.book_min_weight = 0;
+        config.book_selection = BookSelection::Temperature;
+        config.book_temperature = 1;
+        config.book_seed = 42;
+        let first = book.select_book_move(&board, &config);
+        assert_eq!("d2d4", first);
+        config.book_temperature = 1000;
+        let seeded: Vec<String> = (0..5).map(|_| book.select_book_move(&board, &config)).collect();
+        assert!(seeded.iter().all(|mov| *mov == seeded[0]));
+    }
+
+    #[test]
+    fn test_polyglot_learning_roundtrip() {
+        let service = Service::new();
+        let board = service.fen.set_init_board();
+        let key = polyglot_key(&board);
+        let mut book = PolyglotBook::from_entries(vec![
+            PolyglotEntry { key, mov: 796, weight: 80, learn: 0 },
+            PolyglotEntry { key, mov: 731, weight: 1, learn: 0 },
+        ]);
+
+        assert!(book.learn(key, 796, 1.0));
+        assert!(book.learn(key, 796, 0.5));
+        assert!(book.learn(key, 731, 0.0));
+        assert!(!book.learn(key, 666, 1.0));
+
+        let path = std::env::temp_dir().join(format!("polyglot_learn_test_{}.bin", std::process::id()));
+        book.write(&path).unwrap();
+        let loaded = PolyglotBook::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let e4 = loaded.find_entries(&board).iter().find(|e| e.mov == 796).unwrap();
+        assert_eq!((90, 2 << 16 | 3), (e4.weight, e4.learn));
+        let d4 = loaded.find_entries(&board).iter().find(|e| e.mov == 731).unwrap();
+        assert_eq!((0, 1 << 16), (d4.weight, d4.learn));
+    }
 }
@@ -1,9 +1,10 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 use crate::config::Config;
-use crate::model::{Board, GameStatus, SearchResult, Stats, Turn, Variant, SearchContext, EngineState, RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE};
+use crate::model::{Board, GameStatus, SearchLimit, SearchResult, Stats, Turn, Variant, SearchContext, EngineState, RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE};
 use crate::service::Service;
 use crate::move_gen_service::MoveGenService;
+use crate::eval_service::EvalService;
 
 
 pub struct SearchService;
@@ -58,10 +59,25 @@ impl SearchService {
         let mut turns = crate::model::MoveList::new();
         service.move_gen.generate_valid_moves_list(board, stats, config, &context, true, false, &mut turns);
 
+        // Only search the root moves which keep the tablebase result
+        if service.tablebases.filter_root_moves(board, &service.move_gen, config, &context, &mut turns) {
+            stats.tb_hits += 1;
+        }
+
+        // Order root moves by the effort of the previous iteration, its best move first
+        if config.enable_root_node_ordering {
+            let prev_root_move_nodes = engine_state.root_move_nodes.lock().expect(crate::model::RIP_COULDN_LOCK_MUTEX);
+            for (idx, (prev_turn, _)) in prev_root_move_nodes.iter().enumerate() {
+                if let Some(t) = turns.moves.iter_mut().take(turns.len).find(|t| *t == prev_turn) {
+                    t.rank = 1_000_000 - idx as i32;
+                }
+            }
+        }
+
         // Sorting and SEE are deferred (Lazy Move Picking & Lazy SEE)
 
         let mut prev_eval = None;
-        if depth > 2 && config.use_zobrist && config.enable_aspiration {
+        if depth > 2 && config.use_zobrist && config.enable_aspiration && config.multi_pv <= 1 {
             if let Some(entry) = zobrist_table.get_entry(&board.cached_hash) {
                 if entry.depth > 0 {
                     prev_eval = Some(entry.eval);
@@ -71,7 +87,7 @@ impl SearchService {
 
         let mut alpha: i16 = i16::MIN;
         let mut beta: i16 = i16::MAX;
-        let mut delta = 15;
+        let mut delta = config.aspiration_delta;
 
         if let Some(val) = prev_eval {
             // De-normalize mate score if present
@@ -111,8 +127,8 @@ impl SearchService {
                 }
                 turns.moves.swap(i, best_idx);
 
-                if turns.moves[i].capture != 0 && turns.moves[i].rank >= 0 && turns.moves[i].rank < 100000 && !self.see_ge(board, &turns.moves[i], 0, config, &service.move_gen) {
-                    turns.moves[i].rank -= 100000;
+                if turns.moves[i].capture != 0 && turns.moves[i].rank >= 0 && turns.moves[i].rank < config.see_rank_penalty && !self.see_ge(board, &turns.moves[i], 0, config, &service.move_gen) {
+                    turns.moves[i].rank -= config.see_rank_penalty;
                     continue; // rank decreased, re-evaluate this index to find the next best move
                 }
 
@@ -122,8 +138,8 @@ impl SearchService {
                 let elapsed = context.start_time.elapsed().as_millis() as i32;
                 if let Some(target) = context.target_time {
                     let mut dynamic_target = target;
-                    if target < i32::MAX - 1000000 && total_root_moves > 0 && (turn_counter * 100) / total_root_moves >= 85 {
-                        dynamic_target = (target * 13) / 10;
+                    if target < i32::MAX - 1000000 && total_root_moves > 0 && (turn_counter * 100) / total_root_moves >= config.root_time_extension_threshold {
+                        dynamic_target = target.saturating_mul(config.root_time_extension) / 100;
                     }
                     if elapsed >= dynamic_target {
                         stop_flag.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -159,6 +175,7 @@ impl SearchService {
                     root_moves_searched: context.root_moves_searched,
                 };
 
+                let nodes_before = stats.calculated_nodes;
                 let min_max_result = self.minimax(board, turn, depth - 1, !white,
                     current_alpha, current_beta, stats, config, service, &child_context, true, false, false, &mut child_pv,
                     1, &mut killer_moves, &mut history_table, &mut counter_moves);
@@ -171,8 +188,10 @@ impl SearchService {
                     search_result.stats.calc_time_ms = calc_time_ms as usize;
                     break;
                 }
+                search_result.root_move_nodes.push((*turn, (stats.calculated_nodes - nodes_before) as u64 + 1));
 
                 let min_max_eval = min_max_result.1;
+                search_result.root_move_scores.push((*turn, min_max_eval));
 
                 if white {
                     if min_max_eval > search_result.best_score {
@@ -194,7 +213,9 @@ impl SearchService {
                 if white {
                     if min_max_eval > best_eval {
                         best_eval = min_max_eval;
-                        current_alpha = current_alpha.max(min_max_eval);
+                        if config.multi_pv <= 1 {
+                            current_alpha = current_alpha.max(min_max_eval);
+                        }
                         let mut best_move_row = VecDeque::new();
                         best_move_row.push_back(Some(*turn));
                         for mv in child_pv.iter().take_while(|x| x.is_some()) {
@@ -217,7 +238,9 @@ impl SearchService {
                     }
                 } else if min_max_eval < best_eval {
                     best_eval = min_max_eval;
-                    current_beta = current_beta.min(min_max_eval);
+                    if config.multi_pv <= 1 {
+                        current_beta = current_beta.min(min_max_eval);
+                    }
                     let mut best_move_row = VecDeque::new();
                     best_move_row.push_back(Some(*turn));
                     for mv in child_pv.iter().take_while(|x| x.is_some()) {
@@ -251,7 +274,7 @@ impl SearchService {
             if best_score <= alpha || best_score >= beta {
                 alpha = best_score.saturating_sub(delta);
                 beta = best_score.saturating_add(delta);
-                delta = delta.saturating_mul(4);
+                delta = delta.saturating_mul(config.aspiration_growth);
                 continue;
             }
 
@@ -262,10 +285,68 @@ impl SearchService {
         search_result.stats = stats.clone();
         search_result.stats.calc_time_ms = calc_time_ms as usize;
         search_result.completed = !stop_flag.load(std::sync::atomic::Ordering::Relaxed);
+
+        let best_move = search_result.variants.first().and_then(|v| v.best_move);
+        search_result.root_move_nodes.sort_by(|a, b| {
+            (Some(b.0) == best_move).cmp(&(Some(a.0) == best_move)).then(b.1.cmp(&a.1))
+        });
+        if search_result.completed {
+            *engine_state.root_move_nodes.lock().expect(crate::model::RIP_COULDN_LOCK_MUTEX) = search_result.root_move_nodes.clone();
+        }
         search_result
     }
     
 
+    /// Iterative deepening within the limit for the tools outside of the UCI loop. Calls
+    /// `on_iteration` after each completed depth and returns the last completed result with the
+    /// nodes of all iterations.
+    pub fn search_with_limit(
+        &self,
+        board: &mut Board,
+        limit: SearchLimit,
+        config: &Config,
+        service: &Service,
+        engine_state: &Arc<EngineState>,
+        mut on_iteration: impl FnMut(&SearchResult),
+    ) -> (Option<SearchResult>, u64) {
+        engine_state.stop_flag.store(false, std::sync::atomic::Ordering::SeqCst);
+        // the root ordering of the previous position does not apply here
+        engine_state.root_move_nodes.lock().expect(crate::model::RIP_COULDN_LOCK_MUTEX).clear();
+        let white = board.white_to_move;
+        let (max_depth, target_time) = match limit {
+            SearchLimit::Movetime(ms) => (config.max_depth, Some(ms.min(i32::MAX as u64) as i32)),
+            SearchLimit::Depth(depth) => (depth, None),
+            SearchLimit::Nodes(_) => (config.max_depth, None),
+        };
+
+        let start_time = std::time::Instant::now();
+        let mut best_result = None;
+        let mut nodes = 0;
+        for depth in 1..=max_depth {
+            let mut stats = Stats::default();
+            let search_result = self.get_moves(board, depth, white, &mut stats, config, service, engine_state, start_time, target_time);
+            nodes += stats.calculated_nodes as u64;
+            if !search_result.completed || search_result.variants.first().and_then(|variant| variant.best_move).is_none() {
+                break;
+            }
+            on_iteration(&search_result);
+            let mate_found = search_result.get_eval().abs() > 32000;
+            best_result = Some(search_result);
+            if mate_found || matches!(limit, SearchLimit::Nodes(max_nodes) if nodes >= max_nodes) {
+                break;
+            }
+        }
+        (best_result, nodes)
+    }
+
+
+    /// White-relative score of a drawn position, contempt applies from the view of the side to move at the root.
+    #[inline(always)]
+    fn draw_score(&self, white: bool, ply: i32, config: &Config) -> i16 {
+        let root_white = if ply % 2 == 0 { white } else { !white };
+        if root_white { -config.contempt } else { config.contempt }
+    }
+
     #[inline(always)]
     fn get_piece_value(&self, piece: u8, _config: &Config) -> i16 {
         match piece {
@@ -398,6 +479,25 @@ impl SearchService {
             }
         }
 
+        // Drawn by repetition, fifty-move rule or a dead position
+        if ply > 0 && (board.game_status == GameStatus::Draw || EvalService::is_dead_position(board)) {
+            return (None, self.draw_score(white, ply, config));
+        }
+
+        // Syzygy WDL probe after captures and pawn moves
+        if ply > 0 && board.halfmove_clock == 0 && service.tablebases.can_probe(board)
+            && let Some(wdl) = service.tablebases.probe_wdl(board, &service.move_gen, config, context) {
+            stats.tb_hits += 1;
+            let score = match wdl {
+                crate::syzygy::Wdl::Win => crate::syzygy::TB_WIN_SCORE - ply as i16,
+                crate::syzygy::Wdl::CursedWin => 1,
+                crate::syzygy::Wdl::Draw => return (None, self.draw_score(white, ply, config)),
+                crate::syzygy::Wdl::BlessedLoss => -1,
+                crate::syzygy::Wdl::Loss => -crate::syzygy::TB_WIN_SCORE + ply as i16,
+            };
+            return (None, if white { score } else { -score });
+        }
+
         let orig_alpha = alpha;
         let orig_beta = beta;
         let mut tt_move = None;
@@ -477,8 +577,8 @@ impl SearchService {
             }
             board.field_for_en_passante = -1;
 
-            let dynamic_divisor = if config.nmp_dynamic_divisor > 0 { config.nmp_dynamic_divisor } else { 6 };
-            let reduction = config.nmp_reduction + (depth / dynamic_divisor);
+            let dynamic_reduction = if config.nmp_dynamic_divisor > 0 { depth / config.nmp_dynamic_divisor } else { 0 };
+            let reduction = config.nmp_reduction + dynamic_reduction;
             let mut reduced_depth = depth - 1 - reduction;
             if reduced_depth < 0 {
                 reduced_depth = 0;
@@ -544,6 +644,36 @@ impl SearchService {
             }
         }
 
+        // 0.6. Internal Iterative Reduction / Deepening for PV and null window nodes without a hash move,
+        // under PVS every non-PV node has a null window, expected cut nodes are not tracked
+        let mut depth = depth;
+        let mut iid_move = None;
+        let is_null_window = (orig_beta as i32 - orig_alpha as i32) <= 1;
+        if config.use_zobrist
+            && config.iir_mode != crate::config::IirMode::Off
+            && tt_move.is_none()
+            && (is_pv || is_null_window)
+            && depth >= config.iir_depth_threshold
+        {
+            match config.iir_mode {
+                crate::config::IirMode::Reduction => depth -= 1,
+                crate::config::IirMode::Deepening => {
+                    let mut iid_pv = [None; 128];
+                    let iid_depth = (depth - config.iid_reduction.max(1)).max(1);
+                    iid_move = self.minimax(
+                        board, turn, iid_depth, white,
+                        alpha, beta, stats, config, service, context,
+                        is_pv, skip_null_move, force_skip_validation, &mut iid_pv, ply, killer_moves, history_table, counter_moves
+                    ).0;
+                    if context.stop_flag.load(std::sync::atomic::Ordering::Relaxed) {
+                        return (None, if white { i16::MIN } else { i16::MAX });
+                    }
+                    tt_move = iid_move;
+                }
+                crate::config::IirMode::Off => {}
+            }
+        }
+
         let counter_move = if config.enable_counter_moves && ply > 0 {
             counter_moves[turn.from as usize][turn.to as usize]
         } else {
@@ -691,7 +821,7 @@ impl SearchService {
                     return match board.game_status {
                         GameStatus::WhiteWin => (None, i16::MAX - 1 - ply as i16),
                         GameStatus::BlackWin => (None, i16::MIN + 1 + ply as i16),
-                        GameStatus::Draw => (None, 0),
+                        GameStatus::Draw => (None, self.draw_score(white, ply, config)),
                         _ => panic!("RIP no defined game end"),
                     };
                 }
@@ -751,8 +881,8 @@ impl SearchService {
                         let mut dynamic_target = target;
                         let searched = context.root_moves_searched;
                         let total = context.root_moves_total;
-                        if target < i32::MAX - 1000000 && total > 0 && (searched * 100) / total >= 85 {
-                            dynamic_target = (target * 13) / 10;
+                        if target < i32::MAX - 1000000 && total > 0 && (searched * 100) / total >= config.root_time_extension_threshold {
+                            dynamic_target = target.saturating_mul(config.root_time_extension) / 100;
                         }
                         if elapsed >= dynamic_target {
                             context.stop_flag.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -838,11 +968,21 @@ impl SearchService {
             return match board.game_status {
                 GameStatus::WhiteWin => (None, i16::MAX - 1 - ply as i16),
                 GameStatus::BlackWin => (None, i16::MIN + 1 + ply as i16),
-                GameStatus::Draw => (None, 0),
+                GameStatus::Draw => (None, self.draw_score(white, ply, config)),
                 _ => panic!("RIP no defined game end"),
             };
         }
 
+        // The IID move may not have made it into the TT (e.g. overwritten), so rank it first explicitly
+        if let Some(iid_m) = iid_move {
+            for t in turns.moves.iter_mut().take(turns.len) {
+                if *t == iid_m {
+                    t.rank = 1_000_000;
+                    break;
+                }
+            }
+        }
+
         // Sorting and SEE are deferred (Lazy Move Picking & Lazy SEE)
 
         let mut turn_counter = 0;
@@ -860,8 +1000,8 @@ impl SearchService {
             }
             turns.moves.swap(i, best_idx);
 
-            if turns.moves[i].capture != 0 && turns.moves[i].rank >= 0 && turns.moves[i].rank < 100000 && !self.see_ge(board, &turns.moves[i], 0, config, &service.move_gen) {
-                turns.moves[i].rank -= 100000;
+            if turns.moves[i].capture != 0 && turns.moves[i].rank >= 0 && turns.moves[i].rank < config.see_rank_penalty && !self.see_ge(board, &turns.moves[i], 0, config, &service.move_gen) {
+                turns.moves[i].rank -= config.see_rank_penalty;
                 continue; // rank decreased, re-evaluate this index
             }
 
@@ -903,8 +1043,8 @@ impl SearchService {
                     let mut dynamic_target = target;
                     let searched = context.root_moves_searched;
                     let total = context.root_moves_total;
-                    if target < i32::MAX - 1000000 && total > 0 && (searched * 100) / total >= 85 {
-                        dynamic_target = (target * 13) / 10;
+                    if target < i32::MAX - 1000000 && total > 0 && (searched * 100) / total >= config.root_time_extension_threshold {
+                        dynamic_target = target.saturating_mul(config.root_time_extension) / 100;
                     }
                     if elapsed >= dynamic_target {
                         context.stop_flag.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -958,9 +1098,9 @@ impl SearchService {
 
                 // History-Koppelung: Verringere Reduktion für gute Züge, erhöhe für historisch extrem schwache
                 let hist_val = history_table[current_turn.from as usize][current_turn.to as usize];
-                if hist_val > 4000 {
+                if hist_val > config.lmr_history_good_threshold {
                     reduction = reduction.saturating_sub(1);
-                } else if hist_val < 500 {
+                } else if hist_val < config.lmr_history_bad_threshold {
                     reduction = reduction.saturating_add(1);
                 }
 
@@ -1190,6 +1330,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1342,6 +1483,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1393,6 +1535,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1454,6 +1597,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1505,6 +1649,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1558,6 +1703,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(table.clone()),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1627,6 +1773,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1668,6 +1815,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(500_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log.clone(),
         });
@@ -1677,6 +1825,7 @@ mod tests {
             zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(500_000))),
             pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
             pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
             logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
             log_sender: tx_log,
         });
@@ -1725,7 +1874,127 @@ mod tests {
             stats_disabled.calculated_nodes
         );
     }
-}
 
+    #[test]
+    fn test_iir_modes_without_tt_move() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let service = Service::new();
+
+        let new_engine_state = || {
+            let (tx_log, _rx_log) = std::sync::mpsc::channel();
+            Arc::new(EngineState {
+                stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                debug_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(500_000))),
+                pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+                pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+                root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
+                logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
+                log_sender: tx_log,
+            })
+        };
+
+        let mut node_counts = Vec::new();
+        for mode in [crate::config::IirMode::Off, crate::config::IirMode::Reduction, crate::config::IirMode::Deepening] {
+            let mut board = service.fen.set_fen(fen);
+            let board_before = board.clone();
+            let mut config = Config::for_tests();
+            config.use_zobrist = true;
+            config.iir_mode = mode;
+            config.iir_depth_threshold = 3;
+
+            let mut stats = Stats::new();
+            let result = service.search.get_moves(
+                &mut board,
+                5,
+                true,
+                &mut stats,
+                &config,
+                &service,
+                &new_engine_state(),
+                std::time::Instant::now(),
+                None,
+            );
+            assert!(result.variants.first().and_then(|v| v.best_move).is_some(), "{:?} should produce a best move", mode);
+            assert!(board == board_before, "Board must be restored after search with {:?}", mode);
+            node_counts.push(stats.calculated_nodes);
+        }
+
+        assert!(
+            node_counts[1] < node_counts[0],
+            "IIR nodes ({}) should be strictly less than nodes without IIR ({})",
+            node_counts[1],
+            node_counts[0]
+        );
+    }
 
+    #[test]
+    fn test_root_move_node_counts() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let service = Service::new();
+        let mut board = service.fen.set_fen(fen);
 
+        let (tx_log, _rx_log) = std::sync::mpsc::channel();
+        let engine_state = Arc::new(EngineState {
+            stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            debug_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(100_000))),
+            pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
+            logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
+            log_sender: tx_log,
+        });
+
+        let config = Config::for_tests();
+        let mut stats = Stats::new();
+        let result = service.search.get_moves(&mut board, 4, true, &mut stats, &config, &service, &engine_state, std::time::Instant::now(), None);
+
+        assert!(result.completed);
+        assert_eq!(27, result.root_move_nodes.len(), "Every legal root move should have a node count");
+        assert_eq!(result.variants[0].best_move, Some(result.root_move_nodes[0].0), "Best move comes first");
+        assert!(result.root_move_nodes[1..].windows(2).all(|w| w[0].1 >= w[1].1), "Remaining moves are sorted by effort");
+        assert!(result.root_move_nodes.iter().all(|(_, nodes)| *nodes > 0));
+        let share = result.get_best_move_node_share().expect("Node share should be known");
+        assert!(share > 0 && share <= 100);
+        assert_eq!(result.root_move_nodes, *engine_state.root_move_nodes.lock().unwrap(), "Counts are kept for the next iteration");
+
+        // next iteration searches the previous best move first
+        let mut stats = Stats::new();
+        let result_next = service.search.get_moves(&mut board, 5, true, &mut stats, &config, &service, &engine_state, std::time::Instant::now(), None);
+        assert!(result_next.completed);
+        assert_eq!(27, result_next.root_move_nodes.len());
+    }
+
+    #[test]
+    fn test_contempt_draw_score_is_relative_to_root_side() {
+        let service = Service::new();
+        let (tx_log, _rx_log) = std::sync::mpsc::channel();
+        let engine_state = Arc::new(EngineState {
+            stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            debug_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            zobrist_table: std::sync::RwLock::new(Arc::new(ZobristTable::with_capacity(1_000))),
+            pv_nodes: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            pv_nodes_len: Arc::new(std::sync::atomic::AtomicI32::new(0)),
+            root_move_nodes: Arc::new(std::sync::Mutex::new(Vec::new())),
+            logger: Arc::new(std::sync::RwLock::new(Arc::new(|_| {}))),
+            log_sender: tx_log,
+        });
+
+        let mut config = Config::for_tests();
+        config.contempt = 40;
+
+        // every move of the bare kings is a draw by insufficient material
+        let mut board = service.fen.set_fen("8/8/8/3k4/8/8/8/3K4 w - - 0 1");
+        let result = service.search.get_moves(&mut board, 3, true, &mut Stats::new(), &config, &service, &engine_state, std::time::Instant::now(), None);
+        assert_eq!(-40, result.get_eval(), "White at the root should dislike the draw");
+
+        let mut board = service.fen.set_fen("8/8/8/3k4/8/8/8/3K4 b - - 0 1");
+        let result = service.search.get_moves(&mut board, 3, false, &mut Stats::new(), &config, &service, &engine_state, std::time::Instant::now(), None);
+        assert_eq!(40, result.get_eval(), "Black at the root should dislike the draw");
+
+        config.contempt = 0;
+        let result = service.search.get_moves(&mut board, 3, false, &mut Stats::new(), &config, &service, &engine_state, std::time::Instant::now(), None);
+        assert_eq!(0, result.get_eval());
+    }
+}
@@ -0,0 +1,568 @@
+//! In-process self-play between two engine configurations.
+//!
+//! Games run on worker threads, each worker with its own services and hash tables for both
+//! engines. Every opening is played twice with swapped colors. Games end by the rules, by
+//! resign or draw adjudication on the search scores or at the ply limit; no tablebases are
+//! consulted. The match reports W/D/L from the view of the first engine, the logistic Elo
+//! difference with its 95% error and, when configured, stops on the verdict of an SPRT.
+
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+use crate::config::Config;
+use crate::eval_service::EvalService;
+use crate::fen_service::FenService;
+use crate::model::{EngineState, GameStatus, SearchLimit, Turn};
+use crate::move_gen_service::MoveGenService;
+use crate::pgn::{san_to_turn, write_pgn, PgnGame, START_FEN};
+use crate::service::Service;
+
+/// Start position of a game and the moves played from it before the engines take over.
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub fen: String,
+    pub turns: Vec<Turn>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    WhiteWin,
+    BlackWin,
+    Draw,
+}
+
+impl Outcome {
+    pub fn to_pgn(self) -> &'static str {
+        match self {
+            Outcome::WhiteWin => "1-0",
+            Outcome::BlackWin => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        }
+    }
+}
+
+/// Adjudication rules on white-relative search scores, a move count of 0 disables a rule.
+#[derive(Debug, Clone, Copy)]
+pub struct Adjudication {
+    /// Resign when both sides agree on at least this score for `resign_moves` moves each.
+    pub resign_score: i16,
+    pub resign_moves: usize,
+    /// Draw when both sides score within this margin for `draw_moves` moves each.
+    pub draw_score: i16,
+    pub draw_moves: usize,
+    /// First full move number on which a draw is adjudicated.
+    pub draw_after: u32,
+    /// Draw after this many plies from the opening position, 0 for no limit.
+    pub max_plies: usize,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Adjudication { resign_score: 1000, resign_moves: 3, draw_score: 10, draw_moves: 8, draw_after: 40, max_plies: 400 }
+    }
+}
+
+impl Adjudication {
+    /// Outcome adjudicated on the scores of the moves played so far.
+    pub fn adjudicate(&self, scores: &[i16], move_count: u32) -> Option<Outcome> {
+        let last = |moves: usize| (moves > 0 && scores.len() >= 2 * moves).then(|| &scores[scores.len() - 2 * moves..]);
+        if let Some(last) = last(self.resign_moves) {
+            if last.iter().all(|score| *score >= self.resign_score) {
+                return Some(Outcome::WhiteWin);
+            }
+            if last.iter().all(|score| *score <= -self.resign_score) {
+                return Some(Outcome::BlackWin);
+            }
+        }
+        if let Some(last) = last(self.draw_moves)
+            && move_count >= self.draw_after
+            && last.iter().all(|score| score.abs() <= self.draw_score) {
+            return Some(Outcome::Draw);
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// Moves from the opening position, the opening moves included.
+    pub turns: Vec<Turn>,
+    /// White-relative search score of each engine move, the opening moves excluded.
+    pub scores: Vec<i16>,
+    pub outcome: Outcome,
+    pub termination: String,
+}
+
+/// One engine of a game: its configuration and search state.
+pub struct Player<'a> {
+    pub config: &'a Config,
+    pub service: &'a Service,
+    pub engine_state: &'a Arc<EngineState>,
+}
+
+/// Plays one game from the opening until the rules or the adjudication end it.
+pub fn play_game(opening: &Opening, white: &Player, black: &Player, limit: SearchLimit, adjudication: &Adjudication) -> GameRecord {
+    let mut board = FenService.set_fen(&opening.fen);
+    for turn in &opening.turns {
+        board.do_move(turn);
+    }
+    white.engine_state.clear();
+    black.engine_state.clear();
+    white.service.pawn_table.clear();
+    black.service.pawn_table.clear();
+
+    let mut turns = opening.turns.clone();
+    let mut scores = Vec::new();
+    let end = |outcome: Outcome, termination: &str| (outcome, termination.to_string());
+    let (outcome, termination) = loop {
+        let player = if board.white_to_move { white } else { black };
+        let moves = player.service.move_gen.legal_moves(&mut board, player.config);
+        if moves.is_empty() {
+            break match (player.service.move_gen.is_in_check(&board), board.white_to_move) {
+                (true, true) => end(Outcome::BlackWin, "checkmate"),
+                (true, false) => end(Outcome::WhiteWin, "checkmate"),
+                (false, _) => end(Outcome::Draw, "stalemate"),
+            };
+        }
+        if board.game_status == GameStatus::Draw {
+            break end(Outcome::Draw, "repetition or 50 move rule");
+        }
+        if EvalService::is_insufficient_material(&board) {
+            break end(Outcome::Draw, "insufficient material");
+        }
+        if adjudication.max_plies > 0 && scores.len() >= adjudication.max_plies {
+            break end(Outcome::Draw, "ply limit");
+        }
+        if let Some(outcome) = adjudication.adjudicate(&scores, board.move_count as u32) {
+            break end(outcome, "adjudication");
+        }
+
+        let mut search_config = player.config.clone();
+        search_config.contempt = player.config.effective_contempt(false);
+        search_config.print_info_string_during_search = false;
+        let (result, _) = player.service.search.search_with_limit(&mut board, limit, &search_config, player.service, player.engine_state, |_| {});
+        // a search stopped before its first iteration still has to move
+        let (turn, score) = result
+            .and_then(|result| result.variants.first().and_then(|variant| variant.best_move).map(|turn| (turn, result.get_eval())))
+            .unwrap_or((moves[0], scores.last().copied().unwrap_or(0)));
+        board.do_move(&turn);
+        turns.push(turn);
+        scores.push(score);
+    };
+    GameRecord { turns, scores, outcome, termination }
+}
+
+/// Games won, drawn and lost by the first engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchScore {
+    pub fn add(&mut self, outcome: Outcome, engine1_white: bool) {
+        match (outcome, engine1_white) {
+            (Outcome::Draw, _) => self.draws += 1,
+            (Outcome::WhiteWin, true) | (Outcome::BlackWin, false) => self.wins += 1,
+            _ => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Mean points per game and their variance per game.
+    fn mean_variance(&self) -> (f64, f64) {
+        let n = self.games().max(1) as f64;
+        let (w, d, l) = (self.wins as f64 / n, self.draws as f64 / n, self.losses as f64 / n);
+        let mean = w + d / 2.0;
+        let variance = w * (1.0 - mean).powi(2) + d * (0.5 - mean).powi(2) + l * mean.powi(2);
+        (mean, variance)
+    }
+
+    pub fn score(&self) -> f64 {
+        self.mean_variance().0
+    }
+
+    /// Elo difference and the half width of its 95% confidence interval, infinite without
+    /// wins or without losses and draws.
+    pub fn elo(&self) -> (f64, f64) {
+        let (mean, variance) = self.mean_variance();
+        let deviation = (variance / self.games().max(1) as f64).sqrt();
+        let low = score_to_elo(mean - 1.96 * deviation);
+        let high = score_to_elo(mean + 1.96 * deviation);
+        (score_to_elo(mean), (high - low) / 2.0)
+    }
+}
+
+/// Logistic Elo difference of a score, infinite at 0 and 1.
+pub fn score_to_elo(score: f64) -> f64 {
+    if score <= 0.0 {
+        f64::NEG_INFINITY
+    } else if score >= 1.0 {
+        f64::INFINITY
+    } else {
+        -400.0 * (1.0 / score - 1.0).log10()
+    }
+}
+
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Sequential probability ratio test of H0 elo <= elo0 against H1 elo >= elo1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    /// Log-likelihood ratio of the match score in the trinomial normal approximation.
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        let (mean, variance) = score.mean_variance();
+        if score.games() == 0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (elo_to_score(self.elo0), elo_to_score(self.elo1));
+        score.games() as f64 * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * variance)
+    }
+
+    /// Lower and upper LLR bound, below H0 is accepted and above H1.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Some(true) when H1 is accepted, Some(false) for H0, None while the test runs.
+    pub fn verdict(&self, score: &MatchScore) -> Option<bool> {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            Some(true)
+        } else if llr <= lower {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Settings of one engine from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct EngineOptions {
+    pub params_file: Option<String>,
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineOptions {
+    pub fn name(&self, default: &str) -> String {
+        self.params_file.as_deref()
+            .and_then(|path| std::path::Path::new(path).file_stem())
+            .map_or(default.to_string(), |stem| stem.to_string_lossy().to_string())
+    }
+
+    pub fn config(&self) -> Result<Config, String> {
+        let mut config = Config::new();
+        if let Some(path) = &self.params_file {
+            let (_, rejected) = crate::params::load_params_file(&mut config, path)?;
+            rejected.iter().for_each(|msg| eprintln!("{}: {}", path, msg));
+        }
+        for (name, value) in &self.options {
+            crate::params::set_option(&mut config, name, value)?;
+        }
+        config.print_info_string_during_search = false;
+        Ok(config)
+    }
+}
+
+pub struct SelfplayOptions {
+    pub engines: [EngineOptions; 2],
+    pub games: usize,
+    pub concurrency: usize,
+    pub limit: SearchLimit,
+    pub hash_mb: usize,
+    pub openings: Option<String>,
+    pub pgn_out: Option<String>,
+    pub adjudication: Adjudication,
+    pub sprt: Option<Sprt>,
+}
+
+impl SelfplayOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let usage = "usage: suprah selfplay [--params1 <file>] [--params2 <file>] [--option1 <name>=<value>] [--option2 <name>=<value>]\n\
+                     \x20      [--games <n>] [--concurrency <n>] [movetime <ms>|depth <n>|nodes <n>] [--hash <mb>]\n\
+                     \x20      [--openings <epd|pgn>] [--pgn <file>] [--resign-score <cp>] [--resign-moves <n>]\n\
+                     \x20      [--draw-score <cp>] [--draw-moves <n>] [--draw-after <move>] [--max-plies <n>]\n\
+                     \x20      [--sprt <elo0> <elo1> [<alpha> <beta>]]";
+        let mut options = SelfplayOptions {
+            engines: [EngineOptions::default(), EngineOptions::default()],
+            games: 100,
+            concurrency: 1,
+            limit: SearchLimit::Movetime(100),
+            hash_mb: 16,
+            openings: None,
+            pgn_out: None,
+            adjudication: Adjudication::default(),
+            sprt: None,
+        };
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().cloned().ok_or(format!("missing value for {}\n{}", arg, usage));
+            let invalid = |_| format!("invalid value for {}", arg);
+            match arg.trim_start_matches("--") {
+                "params1" => options.engines[0].params_file = Some(value()?),
+                "params2" => options.engines[1].params_file = Some(value()?),
+                option @ ("option1" | "option2") => {
+                    let value = value()?;
+                    let (name, value) = value.split_once('=').ok_or(format!("expected <name>=<value> for {}", arg))?;
+                    let engine = if option == "option1" { 0 } else { 1 };
+                    options.engines[engine].options.push((name.trim().to_string(), value.trim().to_string()));
+                },
+                "games" => options.games = value()?.parse().map_err(invalid)?,
+                "concurrency" => options.concurrency = value()?.parse::<usize>().map_err(invalid)?.max(1),
+                "movetime" => options.limit = SearchLimit::Movetime(value()?.parse().map_err(invalid)?),
+                "depth" => options.limit = SearchLimit::Depth(value()?.parse().map_err(invalid)?),
+                "nodes" => options.limit = SearchLimit::Nodes(value()?.parse().map_err(invalid)?),
+                "hash" => options.hash_mb = value()?.parse::<usize>().map_err(invalid)?.max(1),
+                "openings" => options.openings = Some(value()?),
+                "pgn" => options.pgn_out = Some(value()?),
+                "resign-score" => options.adjudication.resign_score = value()?.parse().map_err(invalid)?,
+                "resign-moves" => options.adjudication.resign_moves = value()?.parse().map_err(invalid)?,
+                "draw-score" => options.adjudication.draw_score = value()?.parse().map_err(invalid)?,
+                "draw-moves" => options.adjudication.draw_moves = value()?.parse().map_err(invalid)?,
+                "draw-after" => options.adjudication.draw_after = value()?.parse().map_err(invalid)?,
+                "max-plies" => options.adjudication.max_plies = value()?.parse().map_err(invalid)?,
+                "sprt" => {
+                    let invalid = |_| format!("invalid value for {}", arg);
+                    let elo0 = value()?.parse().map_err(invalid)?;
+                    let elo1 = value()?.parse().map_err(invalid)?;
+                    let mut sprt = Sprt { elo0, elo1, alpha: 0.05, beta: 0.05 };
+                    if let Some(alpha) = iter.next_if(|next| next.parse::<f64>().is_ok()) {
+                        sprt.alpha = alpha.parse().map_err(invalid)?;
+                        sprt.beta = iter.next().ok_or(format!("missing beta for {}", arg))?.parse().map_err(invalid)?;
+                    }
+                    if sprt.elo0 >= sprt.elo1 || !(0.0..0.5).contains(&sprt.alpha) || !(0.0..0.5).contains(&sprt.beta) {
+                        return Err(format!("invalid SPRT bounds\n{}", usage));
+                    }
+                    options.sprt = Some(sprt);
+                },
+                _ => return Err(format!("unknown argument {}\n{}", arg, usage)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Openings of an EPD or PGN file, a PGN opening is its start position and main line.
+pub fn load_openings(path: &str, service: &Service, config: &Config) -> Result<Vec<Opening>, String> {
+    let content = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    let content = String::from_utf8_lossy(&content);
+    if !path.to_ascii_lowercase().ends_with(".pgn") {
+        return Ok(crate::epd::parse_epd(&content).into_iter().map(|position| Opening { fen: position.fen, turns: Vec::new() }).collect());
+    }
+
+    let mut openings = Vec::new();
+    for (i, game) in crate::pgn::parse_pgn(&content).iter().enumerate() {
+        let mut board = FenService.set_fen(game.start_fen());
+        let turns: Option<Vec<Turn>> = game.moves.iter().map(|mv| {
+            let turn = san_to_turn(&mut board, &mv.san, &service.move_gen, config)?;
+            board.do_move(&turn);
+            Some(turn)
+        }).collect();
+        match turns {
+            Some(turns) => openings.push(Opening { fen: game.start_fen().to_string(), turns }),
+            None => eprintln!("{} game {}: illegal move, skipped", path, i + 1),
+        }
+    }
+    Ok(openings)
+}
+
+fn format_elo(elo: f64) -> String {
+    if elo.is_finite() { format!("{:.1}", elo + 0.0) } else { "inf".to_string() }
+}
+
+/// Entry point of `suprah selfplay`, see `SelfplayOptions::parse` for the arguments.
+pub fn run(args: &[String]) {
+    let fail = |msg: String| -> ! {
+        eprintln!("{}", msg);
+        std::process::exit(2);
+    };
+    let options = SelfplayOptions::parse(args).unwrap_or_else(|e| fail(e));
+    let configs = [options.engines[0].config().unwrap_or_else(|e| fail(e)), options.engines[1].config().unwrap_or_else(|e| fail(e))];
+    let names = [options.engines[0].name("engine1"), options.engines[1].name("engine2")];
+    let openings = match &options.openings {
+        Some(path) => load_openings(path, &Service::new(), &configs[0]).unwrap_or_else(|e| fail(e)),
+        None => vec![Opening { fen: START_FEN.to_string(), turns: Vec::new() }],
+    };
+    if openings.is_empty() {
+        fail("no openings found".to_string());
+    }
+    let mut pgn_file = options.pgn_out.as_ref().map(|path| {
+        std::fs::File::create(path).unwrap_or_else(|e| fail(format!("could not create {}: {}", path, e)))
+    });
+
+    let move_gen = MoveGenService::new();
+    let zobrist_entries = options.hash_mb * 1024 * 1024 / 16;
+    let next_game = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel::<(usize, GameRecord)>();
+    let mut score = MatchScore::default();
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.min(options.games.max(1)) {
+            let tx = tx.clone();
+            let (configs, openings, options) = (&configs, &openings, &options);
+            let (next_game, stop) = (&next_game, &stop);
+            scope.spawn(move || {
+                let services = [Service::new(), Service::new()];
+                let engine_states = [EngineState::offline(zobrist_entries), EngineState::offline(zobrist_entries)];
+                let players: Vec<Player> = (0..2).map(|i| Player { config: &configs[i], service: &services[i], engine_state: &engine_states[i] }).collect();
+                while !stop.load(Ordering::SeqCst) {
+                    let index = next_game.fetch_add(1, Ordering::SeqCst);
+                    if index >= options.games {
+                        break;
+                    }
+                    let opening = &openings[(index / 2) % openings.len()];
+                    let (white, black) = if index % 2 == 0 { (&players[0], &players[1]) } else { (&players[1], &players[0]) };
+                    let record = play_game(opening, white, black, options.limit, &options.adjudication);
+                    if tx.send((index, record)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for (index, record) in rx.iter() {
+            if stop.load(Ordering::SeqCst) {
+                continue;
+            }
+            let engine1_white = index % 2 == 0;
+            score.add(record.outcome, engine1_white);
+            let (white, black) = if engine1_white { (&names[0], &names[1]) } else { (&names[1], &names[0]) };
+
+            if let Some(file) = pgn_file.as_mut() {
+                let opening = &openings[(index / 2) % openings.len()];
+                let board = FenService.set_fen(&opening.fen);
+                let mut game = PgnGame::from_turns(&board, &record.turns, record.outcome.to_pgn(), &move_gen, &configs[0]);
+                game.set_tag("Event", "suprah selfplay");
+                game.set_tag("Round", &(index + 1).to_string());
+                game.set_tag("White", white);
+                game.set_tag("Black", black);
+                game.set_tag("Termination", &record.termination);
+                file.write_all(write_pgn(&game).as_bytes()).unwrap_or_else(|e| fail(format!("could not write pgn: {}", e)));
+            }
+
+            let (elo, error) = score.elo();
+            let mut line = format!("game {} {} - {} {} ({}), score {}-{}-{} [{:.3}], elo {} +/- {}", index + 1, white, black,
+                record.outcome.to_pgn(), record.termination, score.wins, score.losses, score.draws, score.score(), format_elo(elo), format_elo(error));
+            if let Some(sprt) = &options.sprt {
+                let (lower, upper) = sprt.bounds();
+                line.push_str(&format!(", llr {:.2} ({:.2}, {:.2})", sprt.llr(&score), lower, upper));
+                if let Some(h1) = sprt.verdict(&score) {
+                    line.push_str(if h1 { ", H1 accepted" } else { ", H0 accepted" });
+                    stop.store(true, Ordering::SeqCst);
+                }
+            }
+            println!("{}", line);
+        }
+    });
+
+    let (elo, error) = score.elo();
+    println!("{} vs {}: {} games, W {} D {} L {}, score {:.3}, elo {} +/- {}", names[0], names[1], score.games(),
+        score.wins, score.draws, score.losses, score.score(), format_elo(elo), format_elo(error));
+    if let Some(sprt) = &options.sprt {
+        let verdict = match sprt.verdict(&score) {
+            Some(true) => "H1 accepted",
+            Some(false) => "H0 accepted",
+            None => "inconclusive",
+        };
+        println!("SPRT [{}, {}] alpha {} beta {}: llr {:.2}, {}", sprt.elo0, sprt.elo1, sprt.alpha, sprt.beta, sprt.llr(&score), verdict);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_score_test() {
+        let mut score = MatchScore::default();
+        score.add(Outcome::WhiteWin, true);
+        score.add(Outcome::WhiteWin, false);
+        score.add(Outcome::Draw, false);
+        score.add(Outcome::BlackWin, false);
+        assert_eq!(MatchScore { wins: 2, draws: 1, losses: 1 }, score);
+        assert!((score.score() - 0.625).abs() < 1e-9);
+
+        let even = MatchScore { wins: 30, draws: 40, losses: 30 };
+        let (elo, error) = even.elo();
+        assert!(elo.abs() < 1e-9);
+        assert!(error > 40.0 && error < 60.0, "error {}", error);
+        assert!((score_to_elo(0.75) - 190.8).abs() < 0.1);
+        assert!(MatchScore { wins: 3, draws: 0, losses: 0 }.elo().0.is_infinite());
+    }
+
+    #[test]
+    fn sprt_test() {
+        let sprt = Sprt { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 };
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+        assert_eq!(0.0, sprt.llr(&MatchScore::default()));
+        assert_eq!(None, sprt.verdict(&MatchScore { wins: 11, draws: 10, losses: 9 }));
+        assert_eq!(Some(true), sprt.verdict(&MatchScore { wins: 3000, draws: 4000, losses: 2000 }));
+        assert_eq!(Some(false), sprt.verdict(&MatchScore { wins: 2000, draws: 4000, losses: 3000 }));
+    }
+
+    #[test]
+    fn adjudication_test() {
+        let adjudication = Adjudication { resign_moves: 2, draw_moves: 2, draw_after: 10, ..Default::default() };
+        assert_eq!(None, adjudication.adjudicate(&[1200, 1100, 1000], 20));
+        assert_eq!(Some(Outcome::WhiteWin), adjudication.adjudicate(&[0, 1200, 1100, 1000, 1500], 20));
+        assert_eq!(Some(Outcome::BlackWin), adjudication.adjudicate(&[-1200, -1100, -1000, -1500], 20));
+        assert_eq!(Some(Outcome::Draw), adjudication.adjudicate(&[5, -5, 0, 10], 20));
+        assert_eq!(None, adjudication.adjudicate(&[5, -5, 0, 10], 9));
+        assert_eq!(None, Adjudication { draw_moves: 0, ..adjudication }.adjudicate(&[5, -5, 0, 10], 20));
+    }
+
+    #[test]
+    fn load_openings_test() {
+        let service = Service::new();
+        let path = std::env::temp_dir().join(format!("suprah_openings_{}.pgn", std::process::id()));
+        std::fs::write(&path, "[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n\n1. a8=R+ Kd7 *\n\n1. e4 e5 2. Ke3 *\n").unwrap();
+        // the openings are read with the engine defaults, which skip underpromotions in the search
+        let openings = load_openings(path.to_str().unwrap(), &service, &Config::new()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(1, openings.len());
+        let turns: Vec<String> = openings[0].turns.iter().map(|turn| turn.to_algebraic()).collect();
+        assert_eq!(vec!["a7a8r", "e8d7"], turns);
+    }
+
+    #[test]
+    fn play_game_test() {
+        let config = Config::for_tests();
+        let service = Service::new();
+        let engine_state = EngineState::offline(100_000);
+        let player = Player { config: &config, service: &service, engine_state: &engine_state };
+
+        let mate_in_one = Opening { fen: "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1".to_string(), turns: Vec::new() };
+        let record = play_game(&mate_in_one, &player, &player, SearchLimit::Depth(3), &Adjudication::default());
+        assert_eq!(Outcome::WhiteWin, record.outcome);
+        assert_eq!("checkmate", record.termination);
+        assert_eq!(1, record.turns.len());
+
+        let start = Opening { fen: START_FEN.to_string(), turns: Vec::new() };
+        let adjudication = Adjudication { max_plies: 6, ..Default::default() };
+        let record = play_game(&start, &player, &player, SearchLimit::Depth(2), &adjudication);
+        assert_eq!((Outcome::Draw, 6), (record.outcome, record.turns.len()));
+        let game = PgnGame::from_turns(&FenService.set_fen(START_FEN), &record.turns, record.outcome.to_pgn(), &service.move_gen, &config);
+        assert_eq!(6, game.moves.len());
+        assert_eq!(Some("1/2-1/2"), game.tag("Result"));
+    }
+}
@@ -6,6 +6,7 @@ use crate::search_service::SearchService;
 use crate::uci_parser_service::UciParserService;
 use crate::stdout_wrapper::StdoutWrapper;
 use crate::pawn_hash::PawnHashTable;
+use crate::syzygy::Tablebases;
 
 pub struct Service {
     pub fen: FenService,
@@ -15,6 +16,7 @@ pub struct Service {
     pub stdout: StdoutWrapper,
     pub uci_parser: UciParserService,
     pub pawn_table: PawnHashTable,
+    pub tablebases: Tablebases,
 }
 
 impl Service {
@@ -28,6 +30,7 @@ impl Service {
             stdout: StdoutWrapper,
             uci_parser: UciParserService::new(),
             pawn_table: PawnHashTable::new(Config::new().max_pawn_hash_entries),
+            tablebases: Tablebases::new(),
         }
     }
 }
@@ -0,0 +1,176 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::config::Config;
+use crate::model::{SearchResult, Turn};
+
+/// Lowest strength selectable via UCI_Elo. UCI_Elo maps linearly onto the skill levels between
+/// MIN_ELO and MAX_ELO, the ratings are rough estimates and not measured against rated engines.
+pub const MIN_ELO: i32 = 1320;
+/// Estimated strength of the engine at Skill Level 20.
+pub const MAX_ELO: i32 = 2850;
+/// Highest Skill Level, means full strength.
+pub const MAX_SKILL_LEVEL: i32 = 20;
+
+/// Weakening derived from `Skill Level` or `UCI_LimitStrength`/`UCI_Elo`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skill {
+    /// Skill level 0.0..20.0, fractional when mapped from an Elo value.
+    pub level: f64,
+    pub max_depth: i32,
+    pub max_nodes: u64,
+    /// Number of root moves that take part in the move selection.
+    pub multi_pv: usize,
+    /// Softmax temperature in cp, higher values pick worse moves more often.
+    pub temperature: f64,
+    pub eval_noise: i16,
+}
+
+impl Skill {
+    /// Returns None if the engine plays at full strength.
+    pub fn from_config(config: &Config) -> Option<Skill> {
+        let level = if config.limit_strength {
+            let elo = config.uci_elo.clamp(MIN_ELO, MAX_ELO);
+            (elo - MIN_ELO) as f64 * MAX_SKILL_LEVEL as f64 / (MAX_ELO - MIN_ELO) as f64
+        } else {
+            config.skill_level.clamp(0, MAX_SKILL_LEVEL) as f64
+        };
+        if level >= MAX_SKILL_LEVEL as f64 {
+            None
+        } else {
+            Some(Skill::from_level(level))
+        }
+    }
+
+    pub fn from_level(level: f64) -> Skill {
+        let level = level.clamp(0.0, MAX_SKILL_LEVEL as f64);
+        let weakness = MAX_SKILL_LEVEL as f64 - level;
+        Skill {
+            level,
+            max_depth: 2 + (level * 0.7) as i32,
+            max_nodes: (2000.0 * 2f64.powf(level / 2.0)) as u64,
+            multi_pv: 4,
+            temperature: 10.0 + weakness * 15.0,
+            eval_noise: (weakness * 8.0) as i16,
+        }
+    }
+
+    /// Restricts the search config to this skill.
+    pub fn apply(&self, config: &mut Config) {
+        config.max_depth = config.max_depth.min(self.max_depth);
+        config.multi_pv = config.multi_pv.max(self.multi_pv);
+        config.eval_noise = config.eval_noise.max(self.eval_noise);
+    }
+
+    /// Picks one of the best root moves with a softmax over their scores. A seed of 0 picks
+    /// non-deterministically, otherwise the choice only depends on seed and position.
+    pub fn pick_move(&self, search_result: &SearchResult, seed: u64, position_key: u64) -> Option<Turn> {
+        let sign = if search_result.is_white_move { 1 } else { -1 };
+        let mut candidates: Vec<(Turn, i32)> = search_result.root_move_scores.iter()
+            .map(|(turn, score)| (*turn, *score as i32 * sign))
+            .collect();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+        candidates.truncate(self.multi_pv.max(1));
+
+        let best = *candidates.first()?;
+        if best.1.abs() > 30000 || candidates.len() == 1 {
+            return Some(best.0);
+        }
+
+        let weights: Vec<f64> = candidates.iter()
+            .map(|(_, score)| ((score - best.1) as f64 / self.temperature).exp())
+            .collect();
+        let mut rng = if seed == 0 { StdRng::from_entropy() } else { StdRng::seed_from_u64(seed ^ position_key) };
+        let mut pick = rng.r#gen::<f64>() * weights.iter().sum::<f64>();
+        for (candidate, weight) in candidates.iter().zip(weights.iter()) {
+            if pick < *weight {
+                return Some(candidate.0);
+            }
+            pick -= weight;
+        }
+        Some(best.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation_util::NotationUtil;
+
+    fn result(white: bool, scores: &[(&str, i16)]) -> SearchResult {
+        SearchResult {
+            is_white_move: white,
+            root_move_scores: scores.iter()
+                .map(|(mv, score)| (NotationUtil::get_turn_from_notation(mv), *score))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn skill_mapping_test() {
+        let mut config = Config::new();
+        assert_eq!(None, Skill::from_config(&config));
+
+        config.skill_level = 0;
+        let weakest = Skill::from_config(&config).unwrap();
+        config.skill_level = 10;
+        let medium = Skill::from_config(&config).unwrap();
+        assert!(weakest.max_depth < medium.max_depth);
+        assert!(weakest.max_nodes < medium.max_nodes);
+        assert!(weakest.temperature > medium.temperature);
+        assert!(weakest.eval_noise > medium.eval_noise);
+
+        // UCI_Elo takes precedence over Skill Level when strength is limited
+        config.limit_strength = true;
+        config.uci_elo = MIN_ELO;
+        assert_eq!(Some(weakest), Skill::from_config(&config));
+        config.uci_elo = MAX_ELO;
+        assert_eq!(None, Skill::from_config(&config));
+        config.uci_elo = (MIN_ELO + MAX_ELO) / 2;
+        assert_eq!(10.0, Skill::from_config(&config).unwrap().level);
+
+        config.max_depth = 99;
+        medium.apply(&mut config);
+        assert_eq!(medium.max_depth, config.max_depth);
+        assert_eq!(4, config.multi_pv);
+    }
+
+    #[test]
+    fn pick_move_is_deterministic_under_seed() {
+        let skill = Skill::from_level(0.0);
+        let search_result = result(true, &[("e2e4", 30), ("d2d4", 25), ("g1f3", 20), ("b1c3", 10), ("a2a3", -5)]);
+        let first = skill.pick_move(&search_result, 42, 7);
+        for _ in 0..10 {
+            assert_eq!(first, skill.pick_move(&search_result, 42, 7));
+        }
+
+        // only the best multi_pv moves can be picked
+        let a2a3 = NotationUtil::get_turn_from_notation("a2a3");
+        for key in 0..200 {
+            assert_ne!(Some(a2a3), skill.pick_move(&search_result, 42, key));
+        }
+
+        // a weak level spreads its choices over the candidates
+        let picks: std::collections::HashSet<String> = (0..200)
+            .filter_map(|key| skill.pick_move(&search_result, 42, key).map(|turn| turn.to_algebraic()))
+            .collect();
+        assert!(picks.len() > 1);
+    }
+
+    #[test]
+    fn pick_move_prefers_best_for_black_and_keeps_mates() {
+        let skill = Skill::from_level(19.0);
+        let search_result = result(false, &[("e7e5", 300), ("d7d5", -400), ("g8f6", 200)]);
+        let d7d5 = NotationUtil::get_turn_from_notation("d7d5");
+        let d7d5_count = (0..100).filter(|key| skill.pick_move(&search_result, 1, *key) == Some(d7d5)).count();
+        assert!(d7d5_count > 95);
+
+        let skill = Skill::from_level(0.0);
+        let search_result = result(true, &[("e2e4", 30), ("d1h5", 31999)]);
+        let d1h5 = NotationUtil::get_turn_from_notation("d1h5");
+        for key in 0..50 {
+            assert_eq!(Some(d1h5), skill.pick_move(&search_result, 3, key));
+        }
+    }
+}
@@ -0,0 +1,1240 @@
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+
+use crate::config::Config;
+use crate::model::{Board, GameStatus, MoveList, SearchContext, Stats};
+use crate::move_gen_service::MoveGenService;
+
+/// Score of a tablebase win, stays below the mate scores (> 30000).
+pub const TB_WIN_SCORE: i16 = 29000;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+const TB_PIECES: usize = 7;
+const MAX_DTZ: i32 = 1 << 18;
+
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// Piece counts [color][pawn, knight, bishop, rook, queen, king], white first.
+type MaterialKey = [[u8; 6]; 2];
+
+/// Tablebase result from the view of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss = -2,
+    /// Loss, but saved by the fifty-move rule.
+    BlessedLoss = -1,
+    Draw = 0,
+    /// Win, but drawn by the fifty-move rule.
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+
+    fn sign(self) -> i32 {
+        (self as i32).signum()
+    }
+}
+
+impl std::ops::Neg for Wdl {
+    type Output = Wdl;
+    fn neg(self) -> Wdl {
+        Wdl::from_value(-(self as i32))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProbeState {
+    Fail,
+    Ok,
+    /// DTZ table stores the other side to move.
+    ChangeStm,
+    /// Best move zeroes the fifty-move counter, the DTZ value is not stored.
+    ZeroingBestMove,
+}
+
+/// Index tables of the Syzygy position encoding.
+struct Encoding {
+    map_pawns: [usize; 64],
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [usize; 64],
+    map_kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; 6],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+static ENCODING: Lazy<Encoding> = Lazy::new(Encoding::new);
+
+/// Rank minus file, negative below the a1-h8 diagonal.
+fn off_a1h8(sq: usize) -> i32 {
+    (sq >> 3) as i32 - (sq & 7) as i32
+}
+
+impl Encoding {
+    fn new() -> Self {
+        let mut e = Encoding {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        // squares below the a1-h8 diagonal to 0..27
+        let mut code = 0;
+        for sq in 0..64 {
+            if off_a1h8(sq) < 0 {
+                e.map_b1h1h7[sq] = code;
+                code += 1;
+            }
+        }
+
+        // a1-d1-d4 triangle to 0..9, the diagonal squares last
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for sq in 0..=27 {
+            if off_a1h8(sq) < 0 && sq & 7 <= 3 {
+                e.map_a1d1d4[sq] = code;
+                code += 1;
+            } else if off_a1h8(sq) == 0 && sq & 7 <= 3 {
+                diagonal.push(sq);
+            }
+        }
+        for sq in diagonal {
+            e.map_a1d1d4[sq] = code;
+            code += 1;
+        }
+
+        // the 462 legal king pairs with the first king in the a1-d1-d4 triangle
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10 {
+            for s1 in 0..=27 {
+                if e.map_a1d1d4[s1] != idx || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64 {
+                    let file_distance = ((s1 & 7) as i32 - (s2 & 7) as i32).abs();
+                    let rank_distance = ((s1 >> 3) as i32 - (s2 >> 3) as i32).abs();
+                    // adjacent kings or first on the diagonal and second above
+                    if (file_distance <= 1 && rank_distance <= 1) || (off_a1h8(s1) == 0 && off_a1h8(s2) > 0) {
+                        continue;
+                    } else if off_a1h8(s1) == 0 && off_a1h8(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        e.map_kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            e.map_kk[idx][s2] = code;
+            code += 1;
+        }
+
+        e.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6.min(n + 1) {
+                e.binomial[k][n] = if k > 0 { e.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { e.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // a2-h7 to 0..47, the leading pawn has the highest value
+        let mut available_squares = 47;
+        for lead_pawns_cnt in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let sq = rank * 8 + file;
+                    if lead_pawns_cnt == 1 {
+                        e.map_pawns[sq] = available_squares;
+                        e.map_pawns[sq ^ 7] = available_squares - 1;
+                        available_squares = available_squares.saturating_sub(2);
+                    }
+                    e.lead_pawn_idx[lead_pawns_cnt][sq] = idx;
+                    idx += e.binomial[lead_pawns_cnt - 1][e.map_pawns[sq]];
+                }
+                e.lead_pawns_size[lead_pawns_cnt][file] = idx;
+            }
+        }
+        e
+    }
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("RIP slice of 4 bytes"))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().expect("RIP slice of 4 bytes"))
+}
+
+fn read_u64_be(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(bytes[offset..offset + 8].try_into().expect("RIP slice of 8 bytes"))
+}
+
+/// Left child of a symbol in the pairing tree, entries are packed in 3 bytes.
+fn btree_left(bytes: &[u8], btree: usize, sym: usize) -> usize {
+    let lr = &bytes[btree + 3 * sym..btree + 3 * sym + 3];
+    (((lr[1] & 0xF) as usize) << 8) | lr[0] as usize
+}
+
+fn btree_right(bytes: &[u8], btree: usize, sym: usize) -> usize {
+    let lr = &bytes[btree + 3 * sym..btree + 3 * sym + 3];
+    ((lr[2] as usize) << 4) | (lr[1] >> 4) as usize
+}
+
+/// Decoding information of one compressed sub table, all positions are byte offsets into the file.
+#[derive(Debug, Default, Clone)]
+struct PairsData {
+    flags: u8,
+    max_sym_len: u8,
+    min_sym_len: u8,
+    num_blocks: u32,
+    block_size: u64,
+    span: u64,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: u32,
+    sparse_index: usize,
+    sparse_index_size: u64,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u8>,
+    pieces: [u8; TB_PIECES],
+    group_idx: [u64; TB_PIECES + 1],
+    group_len: [usize; TB_PIECES + 1],
+    map_idx: [u16; 4],
+}
+
+impl PairsData {
+    fn set_sizes(&mut self, bytes: &[u8], mut data: usize) -> usize {
+        self.flags = bytes[data];
+        data += 1;
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            self.min_sym_len = bytes[data];
+            return data + 1;
+        }
+
+        let groups = self.group_len.iter().position(|&len| len == 0).unwrap_or(TB_PIECES);
+        let tb_size = self.group_idx[groups];
+
+        self.block_size = 1 << bytes[data];
+        self.span = 1 << bytes[data + 1];
+        self.sparse_index_size = tb_size.div_ceil(self.span);
+        let padding = bytes[data + 2] as u32;
+        self.num_blocks = read_u32_le(bytes, data + 3);
+        self.block_length_size = self.num_blocks + padding;
+        self.max_sym_len = bytes[data + 7];
+        self.min_sym_len = bytes[data + 8];
+        data += 9;
+        self.lowest_sym = data;
+
+        // canonical huffman code, longer symbols have lower values
+        let base_len = (self.max_sym_len as usize + 1).saturating_sub(self.min_sym_len as usize).max(1);
+        self.base64 = vec![0; base_len];
+        for i in (0..base_len - 1).rev() {
+            self.base64[i] = self.base64[i + 1]
+                .wrapping_add(read_u16_le(bytes, self.lowest_sym + 2 * i) as u64)
+                .wrapping_sub(read_u16_le(bytes, self.lowest_sym + 2 * (i + 1)) as u64) / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base = base.checked_shl((64 - i - self.min_sym_len as usize) as u32).unwrap_or(0);
+        }
+        data += base_len * 2;
+
+        let sym_count = read_u16_le(bytes, data) as usize;
+        data += 2;
+        self.btree = data;
+        self.symlen = vec![0; sym_count];
+        let mut visited = vec![false; sym_count];
+        for sym in 0..sym_count {
+            if !visited[sym] {
+                self.symlen[sym] = self.set_symlen(bytes, sym, &mut visited);
+            }
+        }
+        data + sym_count * 3 + (sym_count & 1)
+    }
+
+    /// Number of symbols a pair symbol expands to, minus one.
+    fn set_symlen(&mut self, bytes: &[u8], sym: usize, visited: &mut [bool]) -> u8 {
+        visited[sym] = true;
+        let right = btree_right(bytes, self.btree, sym);
+        if right == 0xFFF {
+            return 0;
+        }
+        let left = btree_left(bytes, self.btree, sym);
+        if !visited[left] {
+            self.symlen[left] = self.set_symlen(bytes, left, visited);
+        }
+        if !visited[right] {
+            self.symlen[right] = self.set_symlen(bytes, right, visited);
+        }
+        self.symlen[left].wrapping_add(self.symlen[right]).wrapping_add(1)
+    }
+
+    /// Value stored for the position with the given index.
+    fn decompress(&self, bytes: &[u8], idx: u64) -> i32 {
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            return self.min_sym_len as i32;
+        }
+
+        let k = (idx / self.span) as usize;
+        let mut block = read_u32_le(bytes, self.sparse_index + 6 * k) as usize;
+        let mut offset = read_u16_le(bytes, self.sparse_index + 6 * k + 4) as i64;
+        offset += (idx % self.span) as i64 - (self.span / 2) as i64;
+
+        let block_length = |block: usize| read_u16_le(bytes, self.block_length + 2 * block) as i64;
+        while offset < 0 {
+            block -= 1;
+            offset += block_length(block) + 1;
+        }
+        while offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+        }
+
+        let mut ptr = self.data + block * self.block_size as usize;
+        let mut buf64 = read_u64_be(bytes, ptr);
+        ptr += 8;
+        let mut buf64_size = 64;
+        let min_sym_len = self.min_sym_len as usize;
+        let mut sym;
+
+        loop {
+            let mut len = 0;
+            while buf64 < self.base64[len] {
+                len += 1;
+            }
+            sym = (buf64 - self.base64[len]).checked_shr((64 - len - min_sym_len) as u32).unwrap_or(0) as usize;
+            sym += read_u16_le(bytes, self.lowest_sym + 2 * len) as usize;
+            if offset < self.symlen[sym] as i64 + 1 {
+                break;
+            }
+            offset -= self.symlen[sym] as i64 + 1;
+            len += min_sym_len;
+            buf64 <<= len;
+            buf64_size -= len;
+            if buf64_size <= 32 {
+                buf64_size += 32;
+                buf64 |= (read_u32_be(bytes, ptr) as u64) << (64 - buf64_size);
+                ptr += 4;
+            }
+        }
+
+        // expand the pair symbol until the leaf at offset
+        while self.symlen[sym] != 0 {
+            let left = btree_left(bytes, self.btree, sym);
+            if offset < self.symlen[left] as i64 + 1 {
+                sym = left;
+            } else {
+                offset -= self.symlen[left] as i64 + 1;
+                sym = btree_right(bytes, self.btree, sym);
+            }
+        }
+        btree_left(bytes, self.btree, sym) as i32
+    }
+}
+
+/// Parsed content of a table file.
+struct TableData {
+    bytes: Vec<u8>,
+    /// Sub tables indexed by side * 4 + file.
+    pairs: Vec<PairsData>,
+    /// Start of the DTZ value map.
+    map: usize,
+}
+
+/// A WDL or DTZ table of one material configuration, loaded on first probe.
+struct TbTable {
+    path: PathBuf,
+    dtz: bool,
+    key: MaterialKey,
+    key2: MaterialKey,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    /// Pawns of the leading color first.
+    pawn_count: [usize; 2],
+    data: OnceCell<Option<TableData>>,
+}
+
+impl TbTable {
+    fn new(path: PathBuf, dtz: bool, key: MaterialKey) -> Self {
+        let key2 = [key[1], key[0]];
+        let piece_count = key.iter().flatten().map(|&c| c as usize).sum();
+        let has_pawns = key[0][0] + key[1][0] > 0;
+        let has_unique_pieces = key.iter().any(|side| side[..5].contains(&1));
+
+        // leading color is the side with less pawns
+        let (white_pawns, black_pawns) = (key[0][0] as usize, key[1][0] as usize);
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count = if white_leads { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] };
+
+        TbTable { path, dtz, key, key2, piece_count, has_pawns, has_unique_pieces, pawn_count, data: OnceCell::new() }
+    }
+
+    fn data(&self) -> Option<&TableData> {
+        self.data.get_or_init(|| std::fs::read(&self.path).ok().and_then(|bytes| self.parse(bytes))).as_ref()
+    }
+
+    fn pairs_idx(&self, stm: usize, file: usize) -> usize {
+        let side = if self.dtz { 0 } else { stm };
+        side * 4 + if self.has_pawns { file } else { 0 }
+    }
+
+    fn parse(&self, bytes: Vec<u8>) -> Option<TableData> {
+        let magic = if self.dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if bytes.len() < 5 || bytes[..4] != magic || bytes.len() % 64 != 16 {
+            return None;
+        }
+        if (bytes[4] & 2 != 0) != self.has_pawns {
+            return None;
+        }
+
+        let mut pairs = vec![PairsData::default(); 8];
+        let mut data = 5;
+        let sides = if !self.dtz && self.key != self.key2 { 2 } else { 1 };
+        let max_file = if self.has_pawns { 3 } else { 0 };
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+
+        for file in 0..=max_file {
+            let order = [
+                [bytes[data] & 0xF, if pp { bytes[data + 1] & 0xF } else { 0xF }],
+                [bytes[data] >> 4, if pp { bytes[data + 1] >> 4 } else { 0xF }],
+            ];
+            data += 1 + pp as usize;
+            for k in 0..self.piece_count {
+                for (side, d) in pairs.iter_mut().skip(file).step_by(4).take(sides).enumerate() {
+                    d.pieces[k] = if side == 1 { bytes[data] >> 4 } else { bytes[data] & 0xF };
+                }
+                data += 1;
+            }
+            for side in 0..sides {
+                self.set_groups(&mut pairs[side * 4 + file], order[side], file);
+            }
+        }
+        data += data & 1;
+
+        for file in 0..=max_file {
+            for side in 0..sides {
+                data = pairs[side * 4 + file].set_sizes(&bytes, data);
+            }
+        }
+
+        let map = data;
+        if self.dtz {
+            data = Self::set_dtz_map(&mut pairs, &bytes, data, max_file);
+        }
+
+        for file in 0..=max_file {
+            for side in 0..sides {
+                let d = &mut pairs[side * 4 + file];
+                d.sparse_index = data;
+                data += d.sparse_index_size as usize * 6;
+            }
+        }
+        for file in 0..=max_file {
+            for side in 0..sides {
+                let d = &mut pairs[side * 4 + file];
+                d.block_length = data;
+                data += d.block_length_size as usize * 2;
+            }
+        }
+        for file in 0..=max_file {
+            for side in 0..sides {
+                let d = &mut pairs[side * 4 + file];
+                data = (data + 0x3F) & !0x3F;
+                d.data = data;
+                data += d.num_blocks as usize * d.block_size as usize;
+            }
+        }
+
+        if data > bytes.len() {
+            return None;
+        }
+        Some(TableData { bytes, pairs, map })
+    }
+
+    fn set_groups(&self, d: &mut PairsData, order: [u8; 2], file: usize) {
+        let encoding = &*ENCODING;
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns { 0 } else if self.has_unique_pieces { 3 } else { 2 };
+        d.group_len[n] = 1;
+
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        // the leading group is at position order[0] and the remaining pawns at order[1]
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if pp { 2 } else { 1 };
+        let mut free_squares = 64 - d.group_len[0] - if pp { d.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    encoding.lead_pawns_size[d.group_len[0]][file]
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] as usize {
+                d.group_idx[1] = idx;
+                idx *= encoding.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                d.group_idx[next] = idx;
+                idx *= encoding.binomial[d.group_len[next]][free_squares];
+                free_squares -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+    }
+
+    fn set_dtz_map(pairs: &mut [PairsData], bytes: &[u8], mut data: usize, max_file: usize) -> usize {
+        let map = data;
+        for d in pairs.iter_mut().take(max_file + 1) {
+            if d.flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+            if d.flags & FLAG_WIDE != 0 {
+                data += data & 1;
+                for i in 0..4 {
+                    d.map_idx[i] = ((data - map) / 2 + 1) as u16;
+                    data += 2 * read_u16_le(bytes, data) as usize + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    d.map_idx[i] = (data - map + 1) as u16;
+                    data += bytes[data] as usize + 1;
+                }
+            }
+        }
+        data + (data & 1)
+    }
+
+    /// Converts a stored DTZ value to plies.
+    fn map_dtz_score(&self, td: &TableData, file: usize, value: i32, wdl: Wdl) -> i32 {
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let d = &td.pairs[self.pairs_idx(0, file)];
+        let mut value = value;
+        if d.flags & FLAG_MAPPED != 0 {
+            let idx = d.map_idx[WDL_MAP[(wdl as i32 + 2) as usize]] as usize + value as usize;
+            value = if d.flags & FLAG_WIDE != 0 {
+                read_u16_le(&td.bytes, td.map + 2 * idx) as i32
+            } else {
+                td.bytes[td.map + idx] as i32
+            };
+        }
+        if (wdl == Wdl::Win && d.flags & FLAG_WIN_PLIES == 0)
+            || (wdl == Wdl::Loss && d.flags & FLAG_LOSS_PLIES == 0)
+            || wdl == Wdl::CursedWin
+            || wdl == Wdl::BlessedLoss
+        {
+            value *= 2;
+        }
+        value + 1
+    }
+
+    /// Looks up the position, WDL tables return the wdl value, DTZ tables the distance in plies.
+    fn probe(&self, board: &Board, wdl: Wdl, state: &mut ProbeState) -> i32 {
+        let Some(td) = self.data() else {
+            *state = ProbeState::Fail;
+            return 0;
+        };
+
+        let mut placed = [(0usize, 0u8); TB_PIECES];
+        let mut size = 0;
+        let mut b = board.occupied;
+        while b != 0 {
+            let sq = b.trailing_zeros() as usize;
+            placed[size] = (sq, tb_piece(board.get_piece_at(sq as u8)));
+            size += 1;
+            b &= b - 1;
+        }
+        let black_stronger = material_key(board) != self.key;
+        let Some((pairs_idx, tb_file, idx)) = self.encode(&td.pairs, &placed[..size], board.white_to_move, black_stronger) else {
+            *state = ProbeState::ChangeStm;
+            return 0;
+        };
+
+        let value = td.pairs[pairs_idx].decompress(&td.bytes, idx);
+        if self.dtz {
+            self.map_dtz_score(td, tb_file, value, wdl)
+        } else {
+            value - 2
+        }
+    }
+
+    /// Sub table, file of the leading pawn and index of the position given as (square, Syzygy piece)
+    /// in ascending square order. None if the DTZ table stores the other side to move.
+    fn encode(&self, pairs: &[PairsData], placed: &[(usize, u8)], white_to_move: bool, black_stronger: bool) -> Option<(usize, usize, u64)> {
+        let encoding = &*ENCODING;
+
+        // tables store the stronger side as white, symmetric tables only white to move
+        let symmetric_black_to_move = self.key == self.key2 && !white_to_move;
+        let flip = symmetric_black_to_move || black_stronger;
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ !white_to_move as usize;
+
+        let mut squares = [0usize; TB_PIECES];
+        let mut pieces = [0u8; TB_PIECES];
+        let mut size = 0;
+        let mut lead_pawns_cnt = 0;
+        let mut lead_pawn = 0;
+        let mut tb_file = 0;
+
+        // pawn tables are split by the file of the leading pawn
+        if self.has_pawns {
+            lead_pawn = pairs[0].pieces[0] ^ flip_color;
+            for &(sq, _) in placed.iter().filter(|(_, piece)| *piece == lead_pawn) {
+                squares[size] = sq ^ flip_squares;
+                size += 1;
+            }
+            lead_pawns_cnt = size;
+            let lead = (0..size).fold(0, |best, i| if encoding.map_pawns[squares[i]] > encoding.map_pawns[squares[best]] { i } else { best });
+            squares.swap(0, lead);
+            let file = squares[0] & 7;
+            tb_file = file.min(7 - file);
+        }
+
+        if self.dtz {
+            let flags = pairs[self.pairs_idx(stm, tb_file)].flags;
+            if (flags & FLAG_STM) as usize != stm && (self.key != self.key2 || self.has_pawns) {
+                return None;
+            }
+        }
+
+        for &(sq, piece) in placed.iter().filter(|(_, piece)| !self.has_pawns || *piece != lead_pawn) {
+            squares[size] = sq ^ flip_squares;
+            pieces[size] = piece ^ flip_color;
+            size += 1;
+        }
+
+        let pairs_idx = self.pairs_idx(stm, tb_file);
+        let d = &pairs[pairs_idx];
+
+        // same piece sequence as the table
+        for i in lead_pawns_cnt..size.saturating_sub(1) {
+            if let Some(j) = (i + 1..size).find(|&j| d.pieces[i] == pieces[j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // leading piece to the a1-d1-d4 triangle
+        if squares[0] & 7 > 3 {
+            for sq in squares.iter_mut().take(size) {
+                *sq ^= 7;
+            }
+        }
+
+        let mut idx: u64;
+        if self.has_pawns {
+            idx = encoding.lead_pawn_idx[lead_pawns_cnt][squares[0]];
+            squares[1..lead_pawns_cnt].sort_by_key(|&sq| encoding.map_pawns[sq]);
+            for (i, sq) in squares.iter().enumerate().take(lead_pawns_cnt).skip(1) {
+                idx += encoding.binomial[i][encoding.map_pawns[*sq]];
+            }
+        } else {
+            if squares[0] >> 3 > 3 {
+                for sq in squares.iter_mut().take(size) {
+                    *sq ^= 56;
+                }
+            }
+
+            // first piece of the leading group off the diagonal goes below it
+            for i in 0..d.group_len[0] {
+                let off = off_a1h8(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for sq in squares.iter_mut().take(size).skip(i) {
+                        *sq = ((*sq >> 3) | (*sq << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            if self.has_unique_pieces {
+                let s = [squares[0] as u64, squares[1] as u64, squares[2] as u64];
+                let adjust1 = (s[1] > s[0]) as u64;
+                let adjust2 = (s[2] > s[0]) as u64 + (s[2] > s[1]) as u64;
+                let rank = |sq: u64| sq >> 3;
+
+                idx = if off_a1h8(squares[0]) != 0 {
+                    (encoding.map_a1d1d4[squares[0]] as u64 * 63 + (s[1] - adjust1)) * 62 + s[2] - adjust2
+                } else if off_a1h8(squares[1]) != 0 {
+                    (6 * 63 + rank(s[0]) * 28 + encoding.map_b1h1h7[squares[1]]) * 62 + s[2] - adjust2
+                } else if off_a1h8(squares[2]) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + rank(s[0]) * 7 * 28 + (rank(s[1]) - adjust1) * 28 + encoding.map_b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s[0]) * 7 * 6 + (rank(s[1]) - adjust1) * 6 + (rank(s[2]) - adjust2)
+                };
+            } else {
+                idx = encoding.map_kk[encoding.map_a1d1d4[squares[0]]][squares[1]];
+            }
+        }
+
+        // remaining groups, each in ascending square order
+        idx *= d.group_idx[0];
+        let mut group_start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[group_start..group_start + len].sort_unstable();
+            let mut n = 0;
+            for i in 0..len {
+                let sq = squares[group_start + i];
+                let adjust = squares[..group_start].iter().filter(|&&s| sq > s).count();
+                n += encoding.binomial[i + 1][sq - adjust - if remaining_pawns { 8 } else { 0 }];
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            group_start += len;
+            next += 1;
+        }
+
+        Some((pairs_idx, tb_file, idx))
+    }
+}
+
+/// Syzygy piece code, white 1..6 and black 9..14 (pawn, knight, bishop, rook, queen, king).
+fn tb_piece(piece: u8) -> u8 {
+    let piece_type = match piece % 10 {
+        0 => 1,
+        1 => 4,
+        2 => 2,
+        3 => 3,
+        4 => 5,
+        _ => 6,
+    };
+    if piece >= 20 { piece_type + 8 } else { piece_type }
+}
+
+fn material_key(board: &Board) -> MaterialKey {
+    let mut key = [[0u8; 6]; 2];
+    for (color, side) in key.iter_mut().enumerate() {
+        let bb = &board.bitboards[color * 6..color * 6 + 6];
+        *side = [bb[0], bb[2], bb[3], bb[1], bb[4], bb[5]].map(|b| b.count_ones() as u8);
+    }
+    key
+}
+
+/// Material of a table name like "KRPvKR", None if it isn't one.
+fn parse_material(name: &str) -> Option<MaterialKey> {
+    let (white, black) = name.split_once('v')?;
+    let mut key = [[0u8; 6]; 2];
+    for (side, pieces) in key.iter_mut().zip([white, black]) {
+        if !pieces.starts_with('K') {
+            return None;
+        }
+        for c in pieces.chars() {
+            let idx = "PNBRQK".find(c)?;
+            side[idx] += 1;
+        }
+        if side[5] != 1 {
+            return None;
+        }
+    }
+    Some(key)
+}
+
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+        Wdl::Draw => 0,
+    }
+}
+
+#[derive(Default)]
+struct TableSet {
+    wdl: Vec<TbTable>,
+    dtz: Vec<TbTable>,
+    /// Material key (both colorings) to the table index.
+    index: HashMap<MaterialKey, usize>,
+}
+
+/// Syzygy WDL/DTZ tablebases found in the `SyzygyPath` directories.
+pub struct Tablebases {
+    tables: RefCell<TableSet>,
+    max_pieces: Cell<usize>,
+}
+
+impl Tablebases {
+    pub fn new() -> Self {
+        Tablebases { tables: RefCell::new(TableSet::default()), max_pieces: Cell::new(0) }
+    }
+
+    /// Registers all tables of the given directories (separated like PATH), replaces the previous ones.
+    pub fn init(&self, paths: &str) -> usize {
+        let mut set = TableSet::default();
+        let paths = paths.trim();
+        if !paths.is_empty() && paths != "<empty>" {
+            for dir in std::env::split_paths(paths) {
+                Self::scan_dir(&dir, &mut set);
+            }
+        }
+        let max_pieces = set.wdl.iter().map(|t| t.piece_count).max().unwrap_or(0);
+        let count = set.wdl.len();
+        *self.tables.borrow_mut() = set;
+        self.max_pieces.set(max_pieces);
+        count
+    }
+
+    fn scan_dir(dir: &Path, set: &mut TableSet) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        let mut names: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        names.sort();
+        for path in names {
+            if path.extension().and_then(|e| e.to_str()) != Some("rtbw") {
+                continue;
+            }
+            let Some(key) = path.file_stem().and_then(|s| s.to_str()).and_then(parse_material) else { continue };
+            if set.index.contains_key(&key) {
+                continue;
+            }
+            let table = TbTable::new(path.clone(), false, key);
+            let dtz_path = path.with_extension("rtbz");
+            set.dtz.push(TbTable::new(dtz_path, true, key));
+            set.index.insert(table.key, set.wdl.len());
+            set.index.insert(table.key2, set.wdl.len());
+            set.wdl.push(table);
+        }
+    }
+
+    /// Piece count of the largest available table, 0 if none are loaded.
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces.get()
+    }
+
+    /// Tables don't know castling rights, positions with more pieces than the tables aren't stored.
+    pub fn can_probe(&self, board: &Board) -> bool {
+        let max_pieces = self.max_pieces.get();
+        max_pieces > 0
+            && board.occupied.count_ones() as usize <= max_pieces
+            && !board.white_possible_to_castle_long
+            && !board.white_possible_to_castle_short
+            && !board.black_possible_to_castle_long
+            && !board.black_possible_to_castle_short
+    }
+
+    /// Win/draw/loss of the side to move, None if a table is missing.
+    pub fn probe_wdl(&self, board: &mut Board, movegen: &MoveGenService, config: &Config, context: &SearchContext) -> Option<Wdl> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        let mut state = ProbeState::Ok;
+        let wdl = self.search(board, movegen, config, context, false, &mut state);
+        (state != ProbeState::Fail).then_some(wdl)
+    }
+
+    /// Keeps only the root moves which preserve the tablebase result, winning moves with the
+    /// shortest DTZ. Returns false if the position could not be probed.
+    pub fn filter_root_moves(&self, board: &mut Board, movegen: &MoveGenService, config: &Config, context: &SearchContext, turns: &mut MoveList) -> bool {
+        if !self.can_probe(board) || turns.len == 0 {
+            return false;
+        }
+        let mut ranks = Vec::with_capacity(turns.len);
+        for turn in turns.moves.iter().take(turns.len) {
+            let mi = board.do_move(turn);
+            let mut state = ProbeState::Ok;
+            let mut dtz = if board.halfmove_clock == 0 {
+                dtz_before_zeroing(-self.search(board, movegen, config, context, false, &mut state))
+            } else {
+                let dtz = -self.dtz(board, movegen, config, context, &mut state);
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && movegen.is_in_check(board) && legal_moves(board, movegen, config, context).len == 0 {
+                dtz = 1;
+            }
+            board.undo_move(turn, mi);
+            if state == ProbeState::Fail {
+                return false;
+            }
+            ranks.push(match dtz {
+                d if d > 0 => MAX_DTZ - d,
+                d if d < 0 => -MAX_DTZ - d,
+                _ => 0,
+            });
+        }
+
+        let best_rank = *ranks.iter().max().expect("RIP no root moves");
+        let mut kept = 0;
+        for (i, rank) in ranks.iter().enumerate() {
+            if *rank == best_rank {
+                turns.moves[kept] = turns.moves[i];
+                kept += 1;
+            }
+        }
+        turns.len = kept;
+        true
+    }
+
+    fn probe_table(&self, board: &Board, dtz: bool, wdl: Wdl, state: &mut ProbeState) -> i32 {
+        if board.occupied.count_ones() == 2 {
+            return 0;
+        }
+        let tables = self.tables.borrow();
+        let Some(&idx) = tables.index.get(&material_key(board)) else {
+            *state = ProbeState::Fail;
+            return 0;
+        };
+        let table = if dtz { &tables.dtz[idx] } else { &tables.wdl[idx] };
+        table.probe(board, wdl, state)
+    }
+
+    /// Resolves captures (and pawn moves if check_zeroing) before the table lookup, tables
+    /// hold no information about en passant and may store "don't care" values otherwise.
+    fn search(&self, board: &mut Board, movegen: &MoveGenService, config: &Config, context: &SearchContext, check_zeroing: bool, state: &mut ProbeState) -> Wdl {
+        let turns = legal_moves(board, movegen, config, context);
+        let mut best = Wdl::Loss;
+        let mut move_count = 0;
+
+        for turn in turns.moves.iter().take(turns.len) {
+            if turn.capture == 0 && (!check_zeroing || !is_pawn(board.get_piece_at(turn.from))) {
+                continue;
+            }
+            move_count += 1;
+            let mi = board.do_move(turn);
+            let value = -self.search(board, movegen, config, context, false, state);
+            board.undo_move(turn, mi);
+            if *state == ProbeState::Fail {
+                return Wdl::Draw;
+            }
+            if value > best {
+                best = value;
+                if value >= Wdl::Win {
+                    *state = ProbeState::ZeroingBestMove;
+                    return value;
+                }
+            }
+        }
+
+        let no_more_moves = move_count > 0 && move_count == turns.len;
+        let value = if no_more_moves {
+            best
+        } else {
+            let value = Wdl::from_value(self.probe_table(board, false, Wdl::Draw, state));
+            if *state == ProbeState::Fail {
+                return Wdl::Draw;
+            }
+            value
+        };
+
+        if best >= value {
+            *state = if best > Wdl::Draw || no_more_moves { ProbeState::ZeroingBestMove } else { ProbeState::Ok };
+            return best;
+        }
+        *state = ProbeState::Ok;
+        value
+    }
+
+    /// Plies to the next zeroing move with the sign of the result, 0 for draws.
+    fn dtz(&self, board: &mut Board, movegen: &MoveGenService, config: &Config, context: &SearchContext, state: &mut ProbeState) -> i32 {
+        let wdl = self.search(board, movegen, config, context, true, state);
+        if *state == ProbeState::Fail || wdl == Wdl::Draw {
+            return 0;
+        }
+        if *state == ProbeState::ZeroingBestMove {
+            return dtz_before_zeroing(wdl);
+        }
+
+        let dtz = self.probe_table(board, true, wdl, state);
+        if *state == ProbeState::Fail {
+            return 0;
+        }
+        if *state != ProbeState::ChangeStm {
+            let cursed = wdl == Wdl::BlessedLoss || wdl == Wdl::CursedWin;
+            return (dtz + if cursed { 100 } else { 0 }) * wdl.sign();
+        }
+
+        // table stores the other side to move, take the best reply
+        let turns = legal_moves(board, movegen, config, context);
+        let mut min_dtz = 0xFFFF;
+        for turn in turns.moves.iter().take(turns.len) {
+            let zeroing = turn.capture != 0 || is_pawn(board.get_piece_at(turn.from));
+            let mi = board.do_move(turn);
+            let mut dtz = if zeroing {
+                -dtz_before_zeroing(self.search(board, movegen, config, context, false, state))
+            } else {
+                -self.dtz(board, movegen, config, context, state)
+            };
+            if dtz == 1 && movegen.is_in_check(board) && legal_moves(board, movegen, config, context).len == 0 {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.sign() {
+                min_dtz = dtz;
+            }
+            board.undo_move(turn, mi);
+            if *state == ProbeState::Fail {
+                return 0;
+            }
+        }
+        if min_dtz == 0xFFFF { -1 } else { min_dtz }
+    }
+}
+
+fn is_pawn(piece: u8) -> bool {
+    piece == 10 || piece == 20
+}
+
+/// Legal moves regardless of a repetition or fifty-move draw, the tables decide those.
+fn legal_moves(board: &mut Board, movegen: &MoveGenService, config: &Config, context: &SearchContext) -> MoveList {
+    let game_status = std::mem::replace(&mut board.game_status, GameStatus::Normal);
+    // underpromotions decide some tablebase results even when the search skips them
+    let mut move_config = config.clone();
+    move_config.use_underpromotions = true;
+    let mut turns = MoveList::new();
+    movegen.generate_valid_moves_list(board, &mut Stats::new(), &move_config, context, false, false, &mut turns);
+    board.game_status = game_status;
+    turns
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    #[test]
+    fn encoding_tables_test() {
+        let encoding = &*ENCODING;
+        let max_kk = encoding.map_kk.iter().flatten().max().unwrap();
+        assert_eq!(461, *max_kk);
+        assert_eq!(27, *encoding.map_b1h1h7.iter().max().unwrap());
+        assert_eq!(9, *encoding.map_a1d1d4.iter().max().unwrap());
+        assert_eq!(1, encoding.binomial[0][63]);
+        assert_eq!(63, encoding.binomial[1][63]);
+        assert_eq!(1953, encoding.binomial[2][63]);
+        // a2 is the most edgy lowest pawn square
+        assert_eq!(47, encoding.map_pawns[8]);
+        assert_eq!(46, encoding.map_pawns[15]);
+        assert_eq!(0, encoding.map_pawns[0]);
+        // a single leading pawn has 6 squares per file
+        assert_eq!([6, 6, 6, 6], encoding.lead_pawns_size[1]);
+    }
+
+    #[test]
+    fn material_key_test() {
+        let fen = Service::new().fen;
+        let board = fen.set_fen("8/8/8/4k3/8/8/3R4/4K3 w - - 0 1");
+        let key = parse_material("KRvK").unwrap();
+        assert_eq!(key, material_key(&board));
+
+        let board = fen.set_fen("8/8/8/4k3/3r4/8/8/4K3 w - - 0 1");
+        let table = TbTable::new(PathBuf::from("KRvK.rtbw"), false, key);
+        assert_eq!(table.key2, material_key(&board));
+        assert!(table.has_unique_pieces);
+        assert!(!table.has_pawns);
+
+        let table = TbTable::new(PathBuf::new(), false, parse_material("KPvKPP").unwrap());
+        assert_eq!([1, 2], table.pawn_count);
+        assert_eq!(5, table.piece_count);
+
+        assert_eq!(None, parse_material("KRK"));
+        assert_eq!(None, parse_material("RvK"));
+        assert_eq!(None, parse_material("KXvK"));
+    }
+
+    #[test]
+    fn missing_tables_test() {
+        let service = Service::new();
+        let tablebases = Tablebases::new();
+        assert_eq!(0, tablebases.init("<empty>"));
+        assert_eq!(0, tablebases.init("/non/existent/syzygy"));
+        assert_eq!(0, tablebases.max_pieces());
+
+        let dir = std::env::temp_dir().join(format!("suprah_syzygy_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("KRvK.rtbw"), [0u8; 80]).unwrap();
+        std::fs::write(dir.join("notes.txt"), "KQvK").unwrap();
+        assert_eq!(1, tablebases.init(dir.to_str().unwrap()));
+        assert_eq!(3, tablebases.max_pieces());
+
+        // a broken table file fails the probe instead of crashing the search
+        let mut board = service.fen.set_fen("8/8/8/4k3/8/8/3R4/4K3 w - - 0 1");
+        let config = Config::for_tests();
+        let zobrist_table = crate::zobrist::ZobristTable::with_capacity(1024);
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let pv_nodes = std::sync::Mutex::new(HashMap::new());
+        let history_table = [[0u32; 64]; 64];
+        let context = SearchContext {
+            zobrist_table: &zobrist_table,
+            stop_flag: &stop_flag,
+            pv_nodes: &pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &history_table,
+            counter_move: None,
+            start_time: std::time::Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+        assert_eq!(None, tablebases.probe_wdl(&mut board, &service.move_gen, &config, &context));
+        let mut turns = MoveList::new();
+        service.move_gen.generate_valid_moves_list(&mut board, &mut Stats::new(), &config, &context, false, false, &mut turns);
+        let move_count = turns.len;
+        assert!(!tablebases.filter_root_moves(&mut board, &service.move_gen, &config, &context, &mut turns));
+        assert_eq!(move_count, turns.len);
+
+        // KvK is always a draw, no table needed
+        let mut board = service.fen.set_fen("8/8/8/4k3/8/8/8/4K3 w - - 0 1");
+        assert_eq!(Some(Wdl::Draw), tablebases.probe_wdl(&mut board, &service.move_gen, &config, &context));
+
+        // castling rights are not in the tables
+        let board = service.fen.set_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1");
+        assert!(!tablebases.can_probe(&board));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn underpromotions_test() {
+        let service = Service::new();
+        let config = Config::new();
+        assert!(!config.use_underpromotions);
+        let zobrist_table = crate::zobrist::ZobristTable::with_capacity(1024);
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let pv_nodes = std::sync::Mutex::new(HashMap::new());
+        let history_table = [[0u32; 64]; 64];
+        let context = SearchContext {
+            zobrist_table: &zobrist_table,
+            stop_flag: &stop_flag,
+            pv_nodes: &pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &history_table,
+            counter_move: None,
+            start_time: std::time::Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+        let mut board = service.fen.set_fen("k7/2P5/1K6/8/8/8/8/8 w - - 0 1");
+        let turns = legal_moves(&mut board, &service.move_gen, &config, &context);
+        let moves: Vec<String> = turns.moves.iter().take(turns.len).map(|turn| turn.to_algebraic()).collect();
+        for promotion in ["c7c8q", "c7c8r", "c7c8b", "c7c8n"] {
+            assert!(moves.contains(&promotion.to_string()), "{} missing", promotion);
+        }
+    }
+
+    /// Needs the official KRvK, KQvK, KBvK, KNvK, KPvK and KQvKR tables (.rtbw and .rtbz) from
+    /// https://tablebase.lichess.ovh/tables/standard/3-4-5/ in assets/syzygy.
+    #[test]
+    #[ignore = "needs the official tables in assets/syzygy"]
+    fn official_tables_test() {
+        let service = Service::new();
+        let tablebases = Tablebases::new();
+        assert_eq!(6, tablebases.init(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/syzygy")));
+        assert_eq!(4, tablebases.max_pieces());
+
+        let config = Config::for_tests();
+        let zobrist_table = crate::zobrist::ZobristTable::with_capacity(1024);
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let pv_nodes = std::sync::Mutex::new(HashMap::new());
+        let history_table = [[0u32; 64]; 64];
+        let context = SearchContext {
+            zobrist_table: &zobrist_table,
+            stop_flag: &stop_flag,
+            pv_nodes: &pv_nodes,
+            killer_moves: [None; 2],
+            history_table: &history_table,
+            counter_move: None,
+            start_time: std::time::Instant::now(),
+            target_time: None,
+            root_moves_total: 0,
+            root_moves_searched: 0,
+        };
+        let probe = |fen: &str| {
+            let mut board = service.fen.set_fen(fen);
+            let wdl = tablebases.probe_wdl(&mut board, &service.move_gen, &config, &context).unwrap();
+            let mut state = ProbeState::Ok;
+            let dtz = tablebases.dtz(&mut board, &service.move_gen, &config, &context, &mut state);
+            assert_ne!(ProbeState::Fail, state, "{}", fen);
+            (wdl, dtz)
+        };
+
+        // KRvK: mate in one, the same position a move earlier, mated and a hanging rook
+        assert_eq!((Wdl::Win, 1), probe("k7/8/1K6/8/8/8/8/7R w - - 0 1"));
+        assert_eq!((Wdl::Loss, -2), probe("k7/8/1K6/8/8/8/8/7R b - - 0 1"));
+        assert_eq!(Wdl::Loss, probe("R5k1/8/6K1/8/8/8/8/8 b - - 0 1").0);
+        assert_eq!((Wdl::Draw, 0), probe("8/8/8/8/8/5k2/6R1/K7 b - - 0 1"));
+
+        // KPvK: king in front of the pawn, rook pawn and promotion
+        assert_eq!(Wdl::Win, probe("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").0);
+        assert_eq!(Wdl::Loss, probe("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").0);
+        assert_eq!((Wdl::Draw, 0), probe("k7/8/8/8/8/8/P7/K7 w - - 0 1"));
+        assert_eq!((Wdl::Win, 1), probe("8/4P3/8/8/8/8/k7/4K3 w - - 0 1"));
+        assert_eq!((Wdl::Loss, -2), probe("8/4P3/8/8/8/8/k7/4K3 b - - 0 1"));
+
+        // KQvKR: mate in one, the hanging queen for both colorings of the table
+        assert_eq!((Wdl::Win, 1), probe("k7/8/2K5/8/8/7r/8/1Q6 w - - 0 1"));
+        assert_eq!((Wdl::Win, 1), probe("k7/8/8/8/3Q4/8/3r4/7K b - - 0 1"));
+        assert_eq!((Wdl::Win, 1), probe("k7/8/8/8/3q4/8/3R4/7K w - - 0 1"));
+
+        // only the mate keeps the win with the shortest DTZ
+        let mut board = service.fen.set_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1");
+        let mut turns = legal_moves(&mut board, &service.move_gen, &config, &context);
+        assert!(tablebases.filter_root_moves(&mut board, &service.move_gen, &config, &context, &mut turns));
+        assert_eq!(1, turns.len);
+        assert_eq!("h1h8", turns.moves[0].to_algebraic());
+
+        let mut board = service.fen.set_fen("k7/8/8/8/3Q4/8/3r4/7K b - - 0 1");
+        let mut turns = legal_moves(&mut board, &service.move_gen, &config, &context);
+        assert!(tablebases.filter_root_moves(&mut board, &service.move_gen, &config, &context, &mut turns));
+        assert_eq!(1, turns.len);
+        assert_eq!("d2d4", turns.moves[0].to_algebraic());
+    }
+}
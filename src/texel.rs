@@ -0,0 +1,330 @@
+//! Texel tuning of the hand crafted eval weights.
+//!
+//! Fits the static eval of a labeled dataset to the game results through a sigmoid and minimizes
+//! the mean squared error by gradient descent over the `params::PARAMS` entries with tuning bounds. The dataset should
+//! contain quiet positions, the tuner doesn't run a quiescence search.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+
+use rayon::prelude::*;
+
+use crate::config::Config;
+use crate::eval_service::EvalService;
+use crate::fen_service::FenService;
+use crate::model::Board;
+use crate::move_gen_service::MoveGenService;
+use crate::params::{Param, PARAMS};
+use crate::pawn_hash::PawnHashTable;
+
+/// Eval weights of the parameter registry with tuning bounds.
+pub fn tunable_params() -> impl Iterator<Item = &'static Param> {
+    PARAMS.iter().filter(|param| param.tuning.is_some())
+}
+
+fn weight(param: &Param, config: &Config) -> i16 {
+    (param.get)(config).parse().expect("RIP tunable parameter is no i16 spin")
+}
+
+fn set_weight(param: &Param, config: &mut Config, value: i16) {
+    (param.set)(config, &value.to_string());
+}
+
+fn tuning_bounds(param: &Param) -> (i16, i16) {
+    param.tuning.expect("RIP parameter has no tuning bounds")
+}
+
+/// Position of the dataset with the game result from the view of white (1.0 win, 0.5 draw, 0.0 loss).
+pub struct TexelEntry {
+    pub board: Board,
+    pub result: f64,
+}
+
+/// Options of the `texel` subcommand.
+pub struct TexelOptions {
+    pub dataset: String,
+    pub out: String,
+    pub iterations: usize,
+    pub learning_rate: f64,
+    pub k: Option<f64>,
+    pub params: Vec<&'static Param>,
+}
+
+impl TexelOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = TexelOptions {
+            dataset: String::new(),
+            out: "texel_parameters.json".to_string(),
+            iterations: 100,
+            learning_rate: 1.0,
+            k: None,
+            params: tunable_params().collect(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--out" => options.out = value()?.clone(),
+                "--iterations" => options.iterations = value()?.parse().map_err(|_| "invalid --iterations".to_string())?,
+                "--lr" => options.learning_rate = value()?.parse().map_err(|_| "invalid --lr".to_string())?,
+                "--k" => options.k = Some(value()?.parse().map_err(|_| "invalid --k".to_string())?),
+                "--params" => {
+                    options.params = value()?.split(',').map(|name| {
+                        tunable_params().find(|param| param.matches(name)).ok_or(format!("unknown or untunable parameter {}", name))
+                    }).collect::<Result<_, _>>()?;
+                }
+                _ if options.dataset.is_empty() && !arg.starts_with("--") => options.dataset = arg.clone(),
+                _ => return Err(format!("unknown argument {}", arg)),
+            }
+        }
+        if options.dataset.is_empty() {
+            return Err("usage: suprah texel <dataset> [--out <file>] [--iterations <n>] [--lr <f>] [--k <f>] [--params <a,b,..>]".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Result of a dataset line: `1-0`, `0-1`, `1/2-1/2` (also inside an EPD `c9` opcode), `[1.0]` or a trailing 1, 0.5, 0.
+fn parse_result(rest: &str) -> Option<f64> {
+    if rest.contains("1/2-1/2") || rest.contains("[0.5]") {
+        return Some(0.5);
+    }
+    if rest.contains("1-0") || rest.contains("[1.0]") || rest.contains("[1]") {
+        return Some(1.0);
+    }
+    if rest.contains("0-1") || rest.contains("[0.0]") || rest.contains("[0]") {
+        return Some(0.0);
+    }
+    let last = rest.split(|c: char| c.is_whitespace() || c == '|' || c == ';' || c == ',').rfind(|token| !token.is_empty())?;
+    match last.parse::<f64>() {
+        Ok(result) if result == 0.0 || result == 0.5 || result == 1.0 => Some(result),
+        _ => None,
+    }
+}
+
+/// Splits a dataset line into the FEN and the result, EPD lines without move counters get "0 1".
+pub fn parse_entry(line: &str) -> Option<(String, f64)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() < 5 {
+        return None;
+    }
+    let counters = tokens.len() > 5 && tokens[4].parse::<u32>().is_ok() && tokens[5].parse::<u32>().is_ok();
+    let fen_len = if counters { 6 } else { 4 };
+    let fen = if counters { tokens[..6].join(" ") } else { format!("{} 0 1", tokens[..4].join(" ")) };
+    let result = parse_result(&tokens[fen_len..].join(" "))?;
+    Some((fen, result))
+}
+
+pub fn load_dataset(path: &str) -> Result<Vec<TexelEntry>, String> {
+    let file = File::open(path).map_err(|e| format!("could not open {}: {}", path, e))?;
+    let fen_service = FenService;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("could not read {}: {}", path, e))?;
+        if let Some((fen, result)) = parse_entry(&line) {
+            entries.push(TexelEntry { board: fen_service.set_fen(&fen), result });
+        }
+    }
+    Ok(entries)
+}
+
+/// Expected score of white for a white relative eval.
+pub fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+/// Config the tuner evaluates with: hand crafted eval only, without noise and lazy cutoffs. The
+/// exact endgame scores are off, they don't depend on the weights.
+pub fn tuning_config(config: &Config) -> Config {
+    let mut config = config.clone();
+    config.use_nnue = false;
+    config.enable_lazy_eval = false;
+    config.eval_noise = 0;
+    config.enable_endgame_evaluators = false;
+    config.enable_kpk_bitbase = false;
+    config
+}
+
+/// White relative static eval of every entry.
+pub fn evaluate_all(entries: &[TexelEntry], config: &Config) -> Vec<i16> {
+    let eval_service = EvalService::new(config);
+    let movegen = MoveGenService::new();
+    entries.par_chunks(4096).flat_map_iter(|chunk| {
+        let pawn_table = PawnHashTable::new(1 << 14);
+        chunk.iter().map(|entry| eval_service.calc_eval(&entry.board, config, &movegen, &pawn_table, i16::MIN, i16::MAX, 0)).collect::<Vec<_>>()
+    }).collect()
+}
+
+fn mean_error(entries: &[TexelEntry], evals: &[i16], k: f64) -> f64 {
+    let sum: f64 = entries.iter().zip(evals).map(|(entry, &eval)| (entry.result - sigmoid(eval as f64, k)).powi(2)).sum();
+    sum / entries.len().max(1) as f64
+}
+
+/// Mean squared error between the results and the sigmoid of the eval.
+pub fn error(entries: &[TexelEntry], config: &Config, k: f64) -> f64 {
+    mean_error(entries, &evaluate_all(entries, config), k)
+}
+
+/// Scaling constant K with the lowest error for the current weights, by golden section search.
+pub fn compute_k(entries: &[TexelEntry], config: &Config) -> f64 {
+    let evals = evaluate_all(entries, config);
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = (0.0, 4.0);
+    while high - low > 1e-4 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+        if mean_error(entries, &evals, a) < mean_error(entries, &evals, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Adam over the registry weights with central difference gradients. Returns the tuned config.
+pub fn tune(entries: &[TexelEntry], config: &Config, params: &[&Param], k: f64, iterations: usize, learning_rate: f64) -> Config {
+    let mut config = tuning_config(config);
+    let mut theta: Vec<f64> = params.iter().map(|param| weight(param, &config) as f64).collect();
+    let mut m = vec![0.0; params.len()];
+    let mut v = vec![0.0; params.len()];
+    let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+
+    for iteration in 1..=iterations {
+        let gradient: Vec<f64> = params.iter().map(|param| {
+            let value = weight(param, &config);
+            let mut probe = config.clone();
+            set_weight(param, &mut probe, value.saturating_add(1));
+            let error_up = error(entries, &probe, k);
+            set_weight(param, &mut probe, value.saturating_sub(1));
+            let error_down = error(entries, &probe, k);
+            (error_up - error_down) / 2.0
+        }).collect();
+
+        for (i, param) in params.iter().enumerate() {
+            m[i] = beta1 * m[i] + (1.0 - beta1) * gradient[i];
+            v[i] = beta2 * v[i] + (1.0 - beta2) * gradient[i] * gradient[i];
+            let m_hat = m[i] / (1.0 - beta1.powi(iteration as i32));
+            let v_hat = v[i] / (1.0 - beta2.powi(iteration as i32));
+            let (min, max) = tuning_bounds(param);
+            theta[i] = (theta[i] - learning_rate * m_hat / (v_hat.sqrt() + epsilon)).clamp(min as f64, max as f64);
+            set_weight(param, &mut config, theta[i].round() as i16);
+        }
+        println!("iteration {} error {:.8}", iteration, error(entries, &config, k));
+    }
+    config
+}
+
+/// Writes the weights in the schema of `tuning/parameters.json`.
+pub fn write_params(path: &str, config: &Config, params: &[&Param]) -> Result<(), String> {
+    let body: Vec<String> = params.iter().map(|param| {
+        let (min, max) = tuning_bounds(param);
+        format!("    \"{}\": {{\n        \"value\": {},\n        \"min\": {},\n        \"max\": {}\n    }}", param.field, weight(param, config), min, max)
+    }).collect();
+    let mut file = File::create(path).map_err(|e| format!("could not create {}: {}", path, e))?;
+    writeln!(file, "{{\n{}\n}}", body.join(",\n")).map_err(|e| format!("could not write {}: {}", path, e))
+}
+
+/// Entry point of `suprah texel ...`.
+pub fn run(args: &[String]) {
+    let options = match TexelOptions::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let entries = load_dataset(&options.dataset).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    println!("loaded {} positions, tuning {} parameters", entries.len(), options.params.len());
+
+    let config = tuning_config(&Config::new());
+    let k = options.k.unwrap_or_else(|| compute_k(&entries, &config));
+    println!("K {:.4} initial error {:.8}", k, error(&entries, &config, k));
+
+    let tuned = tune(&entries, &config, &options.params, k, options.iterations, options.learning_rate);
+    if let Err(e) = write_params(&options.out, &tuned, &options.params) {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    println!("wrote {}", options.out);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entry_test() {
+        let (fen, result) = parse_entry("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 [1.0]").unwrap();
+        assert_eq!("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", fen);
+        assert_eq!(1.0, result);
+
+        let (fen, result) = parse_entry("8/8/8/4k3/8/8/4P3/4K3 w - - c9 \"1/2-1/2\";").unwrap();
+        assert_eq!("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1", fen);
+        assert_eq!(0.5, result);
+
+        assert_eq!(0.0, parse_entry("8/8/8/4k3/8/8/4P3/4K3 w - - 12 40 | 35 | 0").unwrap().1);
+        assert_eq!(0.0, parse_entry("8/8/8/4k3/8/8/4P3/4K3 w - - 0-1").unwrap().1);
+        assert!(parse_entry("8/8/8/4k3/8/8/4P3/4K3 w - -").is_none());
+        assert!(parse_entry("").is_none());
+    }
+
+    #[test]
+    fn registry_test() {
+        let config = Config::new();
+        assert!(tunable_params().count() > 80);
+        for param in tunable_params() {
+            let (min, max) = tuning_bounds(param);
+            assert!(min <= weight(param, &config) && weight(param, &config) <= max, "{}", param.field);
+            let mut changed = config.clone();
+            set_weight(param, &mut changed, weight(param, &config) + 1);
+            assert_eq!(weight(param, &config) + 1, weight(param, &changed), "{}", param.field);
+        }
+    }
+
+    #[test]
+    fn sigmoid_and_k_test() {
+        assert_eq!(0.5, sigmoid(0.0, 1.0));
+        assert!(sigmoid(400.0, 1.0) > 0.9);
+        assert!(sigmoid(-400.0, 1.0) < 0.1);
+
+        let fen_service = FenService;
+        let entries: Vec<TexelEntry> = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0.5),
+            ("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 1.0),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1", 0.0),
+            ("rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0.5),
+        ].iter().map(|(fen, result)| TexelEntry { board: fen_service.set_fen(fen), result: *result }).collect();
+        let config = tuning_config(&Config::new());
+        let k = compute_k(&entries, &config);
+        assert!(k > 0.0 && k < 4.0, "k={}", k);
+        assert!(error(&entries, &config, k) <= error(&entries, &config, k * 2.0));
+
+        // the tuner moves the weights towards a lower error
+        let params: Vec<&Param> = tunable_params().filter(|param| param.field == "your_turn_bonus").collect();
+        let tuned = tune(&entries, &config, &params, k, 3, 2.0);
+        assert!(error(&entries, &tuned, k) <= error(&entries, &config, k));
+
+        let path = std::env::temp_dir().join(format!("texel_test_{}.json", std::process::id()));
+        write_params(path.to_str().unwrap(), &tuned, &params).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(written.starts_with("{\n    \"your_turn_bonus\": {\n        \"value\": "));
+    }
+
+    #[test]
+    fn tuning_config_endgames_test() {
+        // known wins and KPK draws are evaluated by the weights instead of an exact score, a drawn
+        // KPK would be exactly 0
+        let entries: Vec<TexelEntry> = [("8/8/8/4k3/8/8/3R4/4K3 w - - 0 1", 1.0), ("8/8/8/8/8/k7/P7/K7 w - - 0 1", 0.5)].iter()
+            .map(|(fen, result)| TexelEntry { board: FenService.set_fen(fen), result: *result })
+            .collect();
+        let evals = evaluate_all(&entries, &tuning_config(&Config::new()));
+        assert!(evals[0] > 0 && evals[0] < 2000, "{:?}", evals);
+        assert_ne!(0, evals[1]);
+    }
+}
@@ -35,6 +35,8 @@ pub fn std_reader(sender: mpsc::Sender<String>, _config: &Config) {
             }
         }
     }
+    // the game loop finishes the running game (book learning) before the engine exits
+    let _ = sender.send("quit".to_string());
 }
 
 
@@ -61,46 +63,9 @@ pub fn uci_command_processor(
                     };
                     stdout.write(&name_str);
                     stdout.write("id author Jan Lange");
-                    stdout.write("option name Hash type spin default 128 min 1 max 1024");
-                    stdout.write("option name Threads type spin default 1 min 1 max 8");
-                    stdout.write("option name Move Overhead type spin default 0 min 0 max 5000");
-                    stdout.write("option name SyzygyPath type string default <empty>");
-                    stdout.write("option name BookFile type string default <empty>");
-                    stdout.write("option name OwnBook type check default true");
-                    stdout.write("option name CacheBookInRam type check default true");
-                    stdout.write("option name Aggressiveness type string default Normal");
-                    stdout.write("option name EnablePositionalCap type check default true");
-                    stdout.write("option name PositionalCapDamping type spin default 5 min 1 max 100");
-                    stdout.write("option name KingOpenFileMalus type spin default 40 min 0 max 500");
-                    stdout.write("option name KingHalfOpenFileMalus type spin default 20 min 0 max 500");
-                    stdout.write("option name KingRingDefenderValue type spin default 1 min 0 max 10");
-                    stdout.write("option name ThreatMinorAttacksRook type spin default 15 min 0 max 200");
-                    stdout.write("option name ThreatMinorAttacksQueen type spin default 30 min 0 max 200");
-                    stdout.write("option name ThreatRookAttacksQueen type spin default 20 min 0 max 200");
-                    stdout.write("option name LogPath type string default <empty>");
-                    stdout.write("option name ConnectedPassedPawnMg type spin default 15 min -500 max 500");
-                    stdout.write("option name ConnectedPassedPawnEg type spin default 30 min -500 max 500");
-                    stdout.write("option name KnightOutpostTrueMg type spin default 30 min -500 max 500");
-                    stdout.write("option name KnightOutpostTrueEg type spin default 15 min -500 max 500");
-                    stdout.write("option name BishopOutpostTrueMg type spin default 20 min -500 max 500");
-                    stdout.write("option name BishopOutpostTrueEg type spin default 10 min -500 max 500");
-                    stdout.write("option name KingPawnShieldKingside type spin default 39 min -500 max 500");
-                    stdout.write("option name KingPawnShieldQueenside type spin default 25 min -500 max 500");
-                    stdout.write("option name KingPieceShieldKingside type spin default 16 min -500 max 500");
-                    stdout.write("option name KingPieceShieldQueenside type spin default 10 min -500 max 500");
-                    stdout.write("option name OppositeBishopsDrawScale type spin default 50 min 0 max 100");
-                    stdout.write("option name RookBehindEnemyPassedPawnMg type spin default 10 min -500 max 500");
-                    stdout.write("option name RookBehindEnemyPassedPawnEg type spin default 25 min -500 max 500");
-                    stdout.write("option name EnableLazyEval type check default true");
-                    stdout.write("option name LazyEvalMinGamePhase type spin default 50 min 0 max 256");
-                    stdout.write("option name LazyEvalMarginSearch type spin default 180 min 10 max 1000");
-                    stdout.write("option name LazyEvalMarginQs type spin default 120 min 10 max 1000");
-                    stdout.write("option name EnableFutilityPruning type check default true");
-                    stdout.write("option name FutilityMaxDepth type spin default 4 min 1 max 10");
-                    stdout.write("option name FutilityMarginBase type spin default 120 min 0 max 500");
-                    stdout.write("option name FutilityMarginSlope type spin default 80 min 0 max 300");
-                    stdout.write("option name UseNNUE type check default false");
-                    stdout.write("option name NnueModelPath type string default eval_models/quantised.bin");
+                    for option in crate::params::uci_options(config) {
+                        stdout.write(&option);
+                    }
                     stdout.write("uciok");
                 }
 
@@ -130,6 +95,18 @@ pub fn uci_command_processor(
                     tx_game_command.send(uci_token).expect("RIP Could not send 'go' as internal cmd");
                 }
 
+                else if uci_token.trim() == "eval" {
+                    tx_game_command.send("eval".to_string()).expect("RIP Could not send 'eval' as internal cmd");
+                }
+
+                else if uci_token.trim().starts_with("result") {
+                    tx_game_command.send(uci_token.trim().to_string()).expect("RIP Could not send 'result' as internal cmd");
+                }
+
+                else if uci_token.trim().starts_with("dumpparams") {
+                    tx_game_command.send(uci_token.trim().to_string()).expect("RIP Could not send 'dumpparams' as internal cmd");
+                }
+
                 else if uci_token.trim().starts_with("test") {
                     time_check::run_time_check(&engine_state);
                 }
@@ -183,57 +160,7 @@ pub fn uci_command_processor(
                         if let Some(val_str) = parts.last() {
                             active_use_nnue = val_str.to_lowercase() == "true";
                         }
-                    } else if token_lower.contains("name threads") && token_lower.contains("value") {
-                        let parts: Vec<&str> = uci_token.split_whitespace().collect();
-                        if let Some(val_str) = parts.last() {
-                            if let Ok(threads) = val_str.parse::<i32>() {
-                                if threads > 0 {
-                                    logger.send(format!("Single-threaded engine. Ignoring setoption threads to {}", threads))
-                                        .expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
-                                }
-                            }
-                        }
-                    } else if token_lower.contains("name hash") && token_lower.contains("value") {
-                        let parts: Vec<&str> = uci_token.split_whitespace().collect();
-                        if let Some(val_str) = parts.last() {
-                            if let Ok(hash_size_mb) = val_str.parse::<i32>() {
-                                let entries = (hash_size_mb as usize * 1024 * 1024) / 16;
-                                *engine_state.zobrist_table.write().unwrap() = std::sync::Arc::new(crate::zobrist::ZobristTable::with_capacity(entries));
-                                logger.send(format!("Hash size dynamically set to {} MB ({} entries)", hash_size_mb, entries))
-                                    .expect(RIP_COULDN_SEND_TO_LOG_BUFFER_QUEUE);
-                            }
-                        }
                     } else {
-                        if token_lower.contains("name logpath") || token_lower.contains("name log_path") {
-                            let parts: Vec<&str> = uci_token.split_whitespace().collect();
-                            if let Some(val_idx) = parts.iter().position(|&r| r.to_lowercase() == "value") {
-                                let val_str = parts[val_idx+1..].join(" ");
-                                let path = std::path::Path::new(&val_str);
-                                let file_path = if path.is_dir() {
-                                    path.join(format!("engine_{}.log", std::process::id()))
-                                } else {
-                                    path.to_path_buf()
-                                };
-
-                                if let Some(parent) = file_path.parent() {
-                                    let _ = std::fs::create_dir_all(parent);
-                                }
-
-                                if let Ok(file) = OpenOptions::new()
-                                    
-                                    .append(true)
-                                    .create(true)
-                                    .open(&file_path)
-                                {
-                                    let file = Arc::new(Mutex::new(file));
-                                    let logger_function: Arc<dyn Fn(String) + Send + Sync> = Arc::new(move |msg: String| {
-                                        let mut file = file.lock().unwrap();
-                                        let _ = file.write_all(msg.as_bytes());
-                                    });
-                                    *engine_state.logger.write().unwrap() = logger_function;
-                                }
-                            }
-                        }
                         tx_game_command.send(uci_token.clone()).ok();
                     }
                 }
@@ -277,6 +204,34 @@ pub fn logger_buffer_thread(engine_state: Arc<EngineState>, _config: &Config, rx
 }
 
 
+/// Directs the log output to the file, a directory gets a log file per engine process.
+pub fn open_log_file(engine_state: &EngineState, log_path: &str) {
+    let path = std::path::Path::new(log_path);
+    let file_path = if path.is_dir() {
+        path.join(format!("engine_{}.log", std::process::id()))
+    } else {
+        path.to_path_buf()
+    };
+
+    if let Some(parent) = file_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    if let Ok(file) = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&file_path)
+    {
+        let file = Arc::new(Mutex::new(file));
+        let logger_function: Arc<dyn Fn(String) + Send + Sync> = Arc::new(move |msg: String| {
+            let mut file = file.lock().unwrap();
+            let _ = file.write_all(msg.as_bytes());
+        });
+        *engine_state.logger.write().unwrap() = logger_function;
+    }
+}
+
+
 fn logger_thread(engine_state: Arc<EngineState>, _config: &Config, rx_log_msg: Receiver<String>) {
     while let Ok(log_msg) = rx_log_msg.recv() {
         let logger_function = engine_state.logger.read().unwrap().clone();
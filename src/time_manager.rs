@@ -0,0 +1,288 @@
+use crate::config::Config;
+use crate::model::Turn;
+
+/// Soft and hard time limits in ms for one `go` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLimits {
+    /// No new iteration is started once the (scaled) soft limit is reached.
+    pub soft: u64,
+    /// The search never runs longer than this.
+    pub hard: u64,
+    /// Fixed time per move (movetime): the search uses the hard limit as is, without scaling or
+    /// root time extension.
+    pub fixed: bool,
+}
+
+/// Summary of one completed iteration of the iterative deepening loop.
+#[derive(Debug, Clone, Copy)]
+pub struct IterationInfo {
+    pub best_move: Option<Turn>,
+    /// Score in cp from the point of view of the side to move at the root.
+    pub score: i16,
+    /// Time spent in this iteration in ms.
+    pub time_ms: u64,
+    /// Percentage of root nodes spent below the best move, if known.
+    pub best_move_node_share: Option<u64>,
+}
+
+/// Decides between iterations whether to go one level deeper, based on the iteration history.
+pub struct TimeManager {
+    limits: TimeLimits,
+    history: Vec<IterationInfo>,
+}
+
+impl TimeManager {
+    pub fn new(limits: TimeLimits) -> Self {
+        TimeManager {
+            limits,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn limits(&self) -> TimeLimits {
+        self.limits
+    }
+
+    pub fn push_iteration(&mut self, info: IterationInfo) {
+        self.history.push(info);
+    }
+
+    /// Target time handed to the search. The search extends its target by `root_time_extension` once
+    /// most root moves are searched, so the target is chosen such that this extension ends at the hard limit.
+    /// Fixed limits are searched without that extension, their target is the hard limit.
+    pub fn search_target(&self, config: &Config) -> i32 {
+        let extension = if self.limits.fixed { 100 } else { config.root_time_extension.max(100) as u64 };
+        (self.limits.hard * 100 / extension).clamp(1, i32::MAX as u64) as i32
+    }
+
+    /// Number of trailing iterations that agree on the best move of the last iteration.
+    fn stable_iterations(&self) -> usize {
+        match self.history.last() {
+            Some(last) => self.history.iter().rev().take_while(|i| i.best_move == last.best_move).count(),
+            None => 0,
+        }
+    }
+
+    /// Soft limit scaled by best-move changes, score trend and stability, never above the hard limit.
+    pub fn scaled_soft_limit(&self, config: &Config) -> u64 {
+        let soft = self.limits.soft.min(self.limits.hard);
+        if self.limits.fixed || !config.enable_dynamic_time || self.history.len() < 2 {
+            return soft;
+        }
+
+        let last = self.history[self.history.len() - 1];
+        let prev = self.history[self.history.len() - 2];
+        let mut percent: u64 = 100;
+
+        // Extend when the best move changed in recent iterations
+        let window_start = self.history.len().saturating_sub(config.tm_instability_window.max(1) + 1);
+        let changes = self.history[window_start..]
+            .windows(2)
+            .filter(|w| w[0].best_move != w[1].best_move)
+            .count() as u64;
+        percent += changes * config.tm_best_move_change_extension;
+
+        // Extend when the score dropped versus the previous iteration
+        let score_drop = prev.score as i32 - last.score as i32;
+        if score_drop >= config.tm_score_drop_threshold as i32 {
+            percent += config.tm_score_drop_extension;
+        }
+
+        // Shrink when the best move dominates the node count or is stable
+        if percent == 100 {
+            if last.best_move_node_share.is_some_and(|share| share >= config.tm_node_dominance_percent) {
+                percent = config.tm_dominant_scale;
+            } else if self.stable_iterations() >= config.tm_stable_iterations {
+                percent = config.tm_stable_scale;
+            }
+        }
+
+        (soft * percent / 100).min(self.limits.hard)
+    }
+
+    /// Estimated growth factor in percent from one iteration to the next.
+    fn branching_factor(&self) -> u64 {
+        let len = self.history.len();
+        if len >= 2 && self.history[len - 2].time_ms > 0 {
+            (self.history[len - 1].time_ms * 100 / self.history[len - 2].time_ms).clamp(150, 500)
+        } else {
+            200
+        }
+    }
+
+    /// Returns false if the soft limit is reached or the next iteration is unlikely to finish before the search is aborted.
+    pub fn should_start_iteration(&self, elapsed_ms: u64, config: &Config) -> bool {
+        if elapsed_ms >= self.scaled_soft_limit(config) {
+            return false;
+        }
+        if let Some(last) = self.history.last() {
+            let predicted = last.time_ms * self.branching_factor() / 100;
+            if elapsed_ms + predicted > self.search_target(config) as u64 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iteration(best_move: &str, score: i16, time_ms: u64, share: Option<u64>) -> IterationInfo {
+        IterationInfo {
+            best_move: Some(crate::notation_util::NotationUtil::get_turn_from_notation(best_move)),
+            score,
+            time_ms,
+            best_move_node_share: share,
+        }
+    }
+
+    fn manager(history: &[IterationInfo]) -> TimeManager {
+        let mut tm = TimeManager::new(TimeLimits { soft: 1000, hard: 3000, fixed: false });
+        for info in history {
+            tm.push_iteration(*info);
+        }
+        tm
+    }
+
+    #[test]
+    fn stable_best_move_shrinks_soft_limit() {
+        let config = Config::new();
+        let tm = manager(&[
+            iteration("e2e4", 20, 1, None),
+            iteration("e2e4", 25, 3, None),
+            iteration("e2e4", 22, 8, None),
+            iteration("e2e4", 24, 20, None),
+        ]);
+        assert_eq!(700, tm.scaled_soft_limit(&config));
+
+        let tm = manager(&[
+            iteration("e2e4", 20, 1, None),
+            iteration("e2e4", 25, 3, None),
+            iteration("e2e4", 22, 8, None),
+            iteration("e2e4", 24, 20, Some(95)),
+        ]);
+        assert_eq!(500, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn dominating_best_move_stops_early() {
+        let config = Config::new();
+        let tm = manager(&[
+            iteration("d2d4", 20, 1, None),
+            iteration("e2e4", 25, 3, None),
+            iteration("e2e4", 22, 8, Some(92)),
+        ]);
+        // the change from d2d4 is still in the window
+        assert_eq!(1350, tm.scaled_soft_limit(&config));
+
+        let tm = manager(&[
+            iteration("e2e4", 20, 1, None),
+            iteration("e2e4", 22, 8, Some(92)),
+        ]);
+        assert_eq!(500, tm.scaled_soft_limit(&config));
+        assert!(!tm.should_start_iteration(500, &config));
+
+        let tm = manager(&[
+            iteration("e2e4", 20, 1, None),
+            iteration("e2e4", 22, 8, Some(60)),
+        ]);
+        assert_eq!(1000, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn best_move_changes_extend_soft_limit() {
+        let config = Config::new();
+        let tm = manager(&[
+            iteration("e2e4", 20, 1, None),
+            iteration("e2e4", 20, 3, None),
+            iteration("d2d4", 20, 8, None),
+            iteration("e2e4", 20, 20, None),
+        ]);
+        assert_eq!(1700, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn score_drop_extends_soft_limit() {
+        let config = Config::new();
+        let tm = manager(&[
+            iteration("e2e4", 40, 1, None),
+            iteration("e2e4", -10, 3, None),
+        ]);
+        assert_eq!(1400, tm.scaled_soft_limit(&config));
+
+        // a rising score is no reason to extend
+        let tm = manager(&[
+            iteration("e2e4", -10, 1, None),
+            iteration("e2e4", 40, 3, None),
+        ]);
+        assert_eq!(1000, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn scaled_soft_limit_is_capped_by_hard_limit() {
+        let config = Config::new();
+        let tm = manager(&[
+            iteration("e2e4", 300, 1, None),
+            iteration("d2d4", 200, 3, None),
+            iteration("g1f3", 100, 8, None),
+            iteration("c2c4", 0, 20, None),
+        ]);
+        assert_eq!(2450, tm.scaled_soft_limit(&config));
+
+        let mut config = Config::new();
+        config.tm_best_move_change_extension = 500;
+        assert_eq!(3000, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn dynamic_time_can_be_disabled() {
+        let mut config = Config::new();
+        config.enable_dynamic_time = false;
+        let tm = manager(&[
+            iteration("e2e4", 40, 1, None),
+            iteration("d2d4", -60, 3, None),
+        ]);
+        assert_eq!(1000, tm.scaled_soft_limit(&config));
+    }
+
+    #[test]
+    fn no_iteration_is_started_that_is_unlikely_to_finish() {
+        let config = Config::new();
+        let tm = manager(&[]);
+        assert!(tm.should_start_iteration(0, &config));
+        assert!(!tm.should_start_iteration(1000, &config));
+
+        // last iteration took 400ms, twice as long as the one before: next one needs ~800ms
+        let tm = manager(&[
+            iteration("e2e4", 20, 200, None),
+            iteration("d2d4", 20, 400, None),
+        ]);
+        assert!(tm.should_start_iteration(600, &config));
+
+        // the soft limit is not reached, but an iteration of ~4000ms would exceed the search target
+        let tm = manager(&[
+            iteration("e2e4", 20, 250, None),
+            iteration("d2d4", 20, 1000, None),
+        ]);
+        assert!(!tm.should_start_iteration(900, &config));
+        assert_eq!(2307, tm.search_target(&config));
+
+        let mut no_extension = config.clone();
+        no_extension.root_time_extension = 100;
+        assert_eq!(3000, tm.search_target(&no_extension));
+    }
+
+    #[test]
+    fn fixed_limits_use_the_full_time() {
+        let config = Config::new();
+        let mut tm = TimeManager::new(TimeLimits { soft: 1000, hard: 1000, fixed: true });
+        tm.push_iteration(iteration("e2e4", 20, 10, Some(95)));
+        tm.push_iteration(iteration("e2e4", 20, 30, Some(95)));
+        assert_eq!(1000, tm.search_target(&config));
+        assert_eq!(1000, tm.scaled_soft_limit(&config));
+        assert!(tm.should_start_iteration(900, &config));
+    }
+}
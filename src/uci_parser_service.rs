@@ -140,12 +140,13 @@ impl UciParserService {
         let mut stats = stats.clone();
         let stats = stats.calculate();        
         let cp = if search_result.is_white_move { search_result.get_eval() } else { -search_result.get_eval() };
-        format!("info depth {} score cp {} time {} nodes {} nps {} pv {}",
+        format!("info depth {} score cp {} time {} nodes {} nps {} tbhits {} pv {}",
             search_result.get_depth(),
             cp,
             stats.calc_time_ms,
             stats.created_nodes,
             stats.created_nodes / (stats.calc_time_ms + 1),
+            stats.tb_hits,
             search_result.get_best_move_row())
     }
 }
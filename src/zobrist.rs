@@ -152,6 +152,9 @@ impl TranspositionEntry {
         };
 
         let mut capture = board.mailbox[to as usize];
+        if capture != 0 && (10..=15).contains(&capture) == board.white_to_move {
+            capture = 0; // Chess960 castling, king captures own rook
+        }
         if capture == 0 {
             let moved_piece = board.mailbox[from as usize];
             if (moved_piece == 10 || moved_piece == 20) && (to as i8 == board.field_for_en_passante) {
@@ -659,34 +662,24 @@ pub fn calc_incremental_hash(board: &Board, turn: &crate::model::Turn) -> u64 {
     hash ^= CASTLING_RIGHTS[old_castle_index];
 
     // Compute new castling rights based on from/to
-    let mut w_short = board.white_possible_to_castle_short;
-    let mut w_long = board.white_possible_to_castle_long;
-    let mut b_short = board.black_possible_to_castle_short;
-    let mut b_long = board.black_possible_to_castle_long;
-    
-    match from {
-        56 => b_long = false,
-        63 => b_short = false,
-        60 => { b_long = false; b_short = false; }
-        0 => w_long = false,
-        7 => w_short = false,
-        4 => { w_long = false; w_short = false; }
-        _ => {}
-    }
-    match to {
-        56 => b_long = false,
-        63 => b_short = false,
-        0 => w_long = false,
-        7 => w_short = false,
-        _ => {}
-    }
+    let [w_long, w_short, b_long, b_short] = board.castle_rights_after_move(from as u8, to as u8);
     let new_castle_index = (if w_short { 1 } else { 0 })
         | (if w_long { 2 } else { 0 })
         | (if b_short { 4 } else { 0 })
         | (if b_long { 8 } else { 0 });
     hash ^= CASTLING_RIGHTS[new_castle_index];
 
-    // 4. Move piece
+    // 4. Castling moves king and rook, Chess960 castling must not be treated as a capture of the own rook
+    if let Some(castling) = board.get_castling(from as u8, to as u8) {
+        let rook_bb_idx = if moved_piece == 15 { crate::model::WHITE_ROOK } else { crate::model::BLACK_ROOK };
+        hash ^= ZOBRIST_TABLE[castling.king_from as usize][moved_bb_idx];
+        hash ^= ZOBRIST_TABLE[castling.king_to as usize][moved_bb_idx];
+        hash ^= ZOBRIST_TABLE[castling.rook_from as usize][rook_bb_idx];
+        hash ^= ZOBRIST_TABLE[castling.rook_to as usize][rook_bb_idx];
+        return hash;
+    }
+
+    // 5. Move piece
     hash ^= ZOBRIST_TABLE[from][moved_bb_idx]; // remove from 'from'
     if turn.is_promotion() {
         let promo_bb_idx = Board::piece_to_bb_idx(turn.promotion);
@@ -695,7 +688,7 @@ pub fn calc_incremental_hash(board: &Board, turn: &crate::model::Turn) -> u64 {
         hash ^= ZOBRIST_TABLE[to][moved_bb_idx]; // add moved piece to 'to'
     }
 
-    // 5. Handle capture
+    // 6. Handle capture
     let mut actual_capture = turn.capture;
     if actual_capture == 0 {
         let piece_at_to = board.mailbox[to];
@@ -716,31 +709,5 @@ pub fn calc_incremental_hash(board: &Board, turn: &crate::model::Turn) -> u64 {
         hash ^= ZOBRIST_TABLE[capture_sq][capture_bb_idx];
     }
 
-    // 6. Handle rook movement in castling
-    if moved_piece == 15 || moved_piece == 25 {
-        let is_castling = (to as i8 - from as i8).abs() == 2;
-        if is_castling {
-            match to {
-                6 => {
-                    hash ^= ZOBRIST_TABLE[7][crate::model::WHITE_ROOK];
-                    hash ^= ZOBRIST_TABLE[5][crate::model::WHITE_ROOK];
-                }
-                2 => {
-                    hash ^= ZOBRIST_TABLE[0][crate::model::WHITE_ROOK];
-                    hash ^= ZOBRIST_TABLE[3][crate::model::WHITE_ROOK];
-                }
-                62 => {
-                    hash ^= ZOBRIST_TABLE[63][crate::model::BLACK_ROOK];
-                    hash ^= ZOBRIST_TABLE[61][crate::model::BLACK_ROOK];
-                }
-                58 => {
-                    hash ^= ZOBRIST_TABLE[56][crate::model::BLACK_ROOK];
-                    hash ^= ZOBRIST_TABLE[59][crate::model::BLACK_ROOK];
-                }
-                _ => {}
-            }
-        }
-    }
-
     hash
 }